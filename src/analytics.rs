@@ -0,0 +1,160 @@
+use std::cell::RefCell;
+
+use macroquad::prelude::*;
+
+use sudoku::{text, theme};
+
+use crate::keys::{self, Action};
+use crate::language::{tr, Text};
+
+
+// Length of the longest bar, in characters of the panel font
+const BAR_WIDTH: f32 = 24.;
+
+
+// Grid solved this session, from its seed, after the restarts it took
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Solve {
+    pub seed: u64,
+    pub restarts: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary {
+    pub solves: usize,
+    pub min: usize,
+    pub mean: f64,
+    pub max: usize,
+    // The solve that took the most restarts, the first of them on ties
+    pub worst: Solve,
+    // Share of all the attempts that ended in a contradiction
+    pub dead_ends: f64,
+}
+
+thread_local! {
+    static SOLVES: RefCell<Vec<Solve>> = const { RefCell::new(Vec::new()) };
+}
+
+pub fn record(seed: u64, restarts: usize) {
+    SOLVES.with(|solves| solves.borrow_mut().push(Solve { seed, restarts }));
+}
+
+pub fn solves() -> Vec<Solve> {
+    SOLVES.with(|solves| solves.borrow().clone())
+}
+
+pub fn summarize(solves: &[Solve]) -> Option<Summary> {
+    let worst = *solves.iter().rev().max_by_key(|solve| solve.restarts)?;
+    let restarts: usize = solves.iter().map(|solve| solve.restarts).sum();
+    Some(Summary {
+        solves: solves.len(),
+        min: solves.iter().map(|solve| solve.restarts).min()?,
+        mean: restarts as f64 / solves.len() as f64,
+        max: worst.restarts,
+        worst,
+        dead_ends: restarts as f64 / (restarts + solves.len()) as f64,
+    })
+}
+
+// Restarts grouped by powers of two, the long tail of unlucky solves taking few bars:
+// none, 1, 2-3, 4-7 and so on
+pub fn bucket(restarts: usize) -> usize {
+    (usize::BITS - restarts.leading_zeros()) as usize
+}
+
+fn bucket_label(bucket: usize) -> String {
+    match bucket {
+        0 | 1 => bucket.to_string(),
+        _ => format!("{}-{}", 1usize << (bucket - 1), (1usize << bucket) - 1),
+    }
+}
+
+// How many solves fall in each bucket, up to the one of the worst solve
+pub fn histogram(solves: &[Solve]) -> Vec<usize> {
+    let mut counts = vec![0; solves.iter().map(|solve| bucket(solve.restarts) + 1).max().unwrap_or(0)];
+    for solve in solves {
+        counts[bucket(solve.restarts)] += 1;
+    }
+    counts
+}
+
+// Restarts each grid solved this session took, watched or raced, until going back
+pub async fn run() {
+    let solves = solves();
+    let summary = summarize(&solves);
+    let counts = histogram(&solves);
+    loop {
+        if is_quit_requested() {
+            std::process::exit(0);
+        }
+        if keys::pressed(Action::Back) {
+            return;
+        }
+
+        let mut lines = vec![tr(Text::AnalyticsTitle, &[&keys::label(Action::Back)]), String::new()];
+        match &summary {
+            Some(summary) => {
+                lines.push(tr(Text::AnalyticsSolves, &[&summary.solves, &format!("{:.0}", summary.dead_ends * 100.)]));
+                lines.push(tr(Text::AnalyticsRestarts, &[&summary.min, &format!("{:.1}", summary.mean), &summary.max]));
+                lines.push(tr(Text::AnalyticsWorst, &[&summary.worst.seed, &summary.worst.restarts]));
+            }
+            None => lines.push(tr(Text::AnalyticsNone, &[])),
+        }
+        draw(&lines, &counts);
+        next_frame().await;
+    }
+}
+
+// Panel of the summary lines over a bar for each bucket, the labels aligned left
+fn draw(lines: &[String], counts: &[usize]) {
+    let style = &theme::current().browser;
+    let labels: Vec<String> = (0..counts.len()).map(bucket_label).collect();
+    let label_width = labels.iter().map(|label| text::measure(label, style.font_size).width).fold(0., f32::max);
+    let bar_width = BAR_WIDTH * text::measure("0", style.font_size).width;
+    let width = lines
+        .iter()
+        .map(|line| text::measure(line, style.font_size).width)
+        .fold(label_width + bar_width + 4. * style.font_size, f32::max) + 2. * style.padding;
+    // A blank line between the summary and the bars
+    let rows = lines.len() + if counts.is_empty() { 0 } else { counts.len() + 1 };
+    let height = rows as f32 * style.font_size + 2. * style.padding;
+    let panel = Rect::new((screen_width() - width) / 2., (screen_height() - height) / 2., width, height);
+    draw_rectangle(panel.x, panel.y, panel.w, panel.h, style.panel_color);
+
+    let x = panel.x + style.padding;
+    let top = |row: usize| panel.y + style.padding + row as f32 * style.font_size;
+    let baseline = |row: usize| top(row) + style.font_size - style.font_size / 4.;
+    for (row, line) in lines.iter().enumerate() {
+        let color = if row == 0 { style.heading_color } else { style.text_color };
+        text::draw(line, x, baseline(row), style.font_size, color);
+    }
+    let most = counts.iter().copied().max().unwrap_or(0).max(1);
+    for (i, (label, &count)) in labels.iter().zip(counts).enumerate() {
+        let row = lines.len() + 1 + i;
+        text::draw(label, x, baseline(row), style.font_size, style.text_color);
+        let bar_x = x + label_width + style.font_size;
+        let length = bar_width * count as f32 / most as f32;
+        draw_rectangle(bar_x, top(row) + style.font_size / 6., length, style.font_size * 2. / 3., style.heading_color);
+        text::draw(&count.to_string(), bar_x + length + style.font_size / 2., baseline(row), style.font_size, style.text_color);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Session statistics over a few made up solves
+    #[test]
+    fn restarts_are_summarized() {
+        let solves: Vec<Solve> = [(1, 0), (2, 5), (3, 1), (4, 5)].map(|(seed, restarts)| Solve { seed, restarts }).to_vec();
+        let summary = summarize(&solves).unwrap();
+        assert_eq!((summary.min, summary.max, summary.worst.seed), (0, 5, 2));
+        assert_eq!(summary.mean, 2.75);
+        assert_eq!(summary.dead_ends, 11. / 15.);
+        // None, one, then 4-7 restarts
+        assert_eq!(histogram(&solves), [1, 1, 0, 2]);
+        assert!(summarize(&[]).is_none());
+        assert!(histogram(&[]).is_empty());
+    }
+}
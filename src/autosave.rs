@@ -0,0 +1,86 @@
+use std::path::PathBuf;
+
+use sudoku::grid::Grid;
+use sudoku::{json, state};
+
+use crate::play::Play;
+
+
+const APP_DIR: &str = "wave-function-collapse-sudoku";
+const FILE_NAME: &str = "autosave.json";
+
+
+pub struct Saved {
+    pub grid: Grid,
+    pub play: Option<Play>,
+}
+
+// File of the game in the platform's config directory, none on the web
+pub fn config_path(file_name: &str) -> Option<PathBuf> {
+    let home = || std::env::var_os("HOME").map(PathBuf::from);
+    let config = if cfg!(windows) {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        home().map(|home| home.join("Library").join("Application Support"))
+    } else {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| home().map(|home| home.join(".config")))
+    };
+    config.map(|dir| dir.join(APP_DIR).join(file_name))
+}
+
+fn path() -> Option<PathBuf> {
+    config_path(FILE_NAME)
+}
+
+// Keeps the solve and the game played over it for the next launch. Finished solves
+// not being played are dropped instead, there would be nothing left to resume
+pub fn save(grid: &Grid, play: Option<&Play>) -> Result<(), String> {
+    let Some(path) = path() else {
+        return Ok(());
+    };
+    if play.is_none() && grid.is_resolve() {
+        discard();
+        return Ok(());
+    }
+    let error = |err: std::io::Error| format!("{}: {}", path.display(), err);
+    std::fs::create_dir_all(path.parent().unwrap()).map_err(error)?;
+    let play = play.map_or("null".to_string(), Play::serialize);
+    let text = format!("{{\"solve\":{},\"play\":{}}}\n", state::serialize(grid).trim_end(), play);
+    std::fs::write(&path, text).map_err(error)
+}
+
+fn discard() {
+    if let Some(path) = path() {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+fn load() -> Result<Option<Saved>, String> {
+    let Some(path) = path().filter(|path| path.exists()) else {
+        return Ok(None);
+    };
+    let text = std::fs::read_to_string(&path).map_err(|err| err.to_string())?;
+    let root = json::parse(&text)?;
+    let grid = state::from_value(root.get("solve")?)?;
+    let play = match root.get("play")? {
+        json::Value::Null => None,
+        play => Some(Play::from_value(play, &grid)?),
+    };
+    Ok(Some(Saved { grid, play }))
+}
+
+pub fn exists() -> bool {
+    path().is_some_and(|path| path.exists())
+}
+
+// The game left at the last exit, if any. Unreadable ones are deleted
+pub fn resume() -> Option<Saved> {
+    load().unwrap_or_else(|err| {
+        eprintln!("Couldn't read the autosave, starting over: {}", err);
+        discard();
+        None
+    })
+}
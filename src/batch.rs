@@ -0,0 +1,54 @@
+use sudoku::generator::{self, Target};
+use sudoku::grid::{BoardSize, Variants};
+use sudoku::puzzle;
+
+use crate::date::Date;
+
+
+// Generates puzzles on target from consecutive seeds, printing each on one line, and
+// writes them to an .sdm collection too when a path is given. With minimal, each is
+// checked to need every given, any it can spare being taken away even if that breaks
+// the symmetry
+pub fn run(
+    count: usize,
+    size: BoardSize,
+    variants: &Variants,
+    seed: u64,
+    target: Target,
+    sdm: Option<&str>,
+    minimal: bool,
+) -> Result<(), String> {
+    if sdm.is_some() && size != BoardSize::default() {
+        return Err(".sdm collections only hold 9x9 puzzles".to_string());
+    }
+
+    let mut puzzles = Vec::with_capacity(count);
+    for i in 0..count as u64 {
+        let mut givens = generator::generate_target(size, variants, seed.wrapping_add(i), target)
+            .map_err(|err| format!("puzzle {}: {}", i + 1, err))?;
+        if minimal {
+            let redundant = generator::redundant_clues(size, variants, &givens);
+            if !redundant.is_empty() {
+                eprintln!("Puzzle {} could spare {} givens, taking them away", i + 1, redundant.len());
+                givens = generator::minimize(size, variants, givens, redundant);
+            }
+        }
+        println!("{}", puzzle::serialize_line(&givens, size));
+        puzzles.push(givens);
+    }
+
+    if let Some(path) = sdm {
+        let header = [
+            "Generated by wave-function-collapse-sudoku".to_string(),
+            format!("Date {}", Date::today()),
+            format!("Seeds {} to {}", seed, seed.wrapping_add(count as u64 - 1)),
+            format!("Puzzles {}", count),
+        ];
+        std::fs::write(path, puzzle::serialize_collection(&puzzles, &header)).map_err(|err| format!("{}: {}", path, err))?;
+        eprintln!("{} puzzles written to {}", count, path);
+    }
+    if minimal {
+        eprintln!("{} puzzles checked minimal", count);
+    }
+    Ok(())
+}
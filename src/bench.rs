@@ -0,0 +1,68 @@
+use std::time::{Duration, Instant};
+
+use sudoku::grid::Grid;
+use sudoku::heuristic::Heuristic;
+
+
+pub const DEFAULT_RUNS: usize = 100;
+
+struct Run {
+    time: Duration,
+    restarts: usize,
+    eliminations: usize,
+}
+
+fn solve(grid: &mut Grid) -> Run {
+    let start = Instant::now();
+    grid.reset();
+    let mut restarts = 0;
+    let mut eliminations = 0;
+
+    while !grid.is_resolve() {
+        if grid.step().is_err() {
+            eliminations += grid.eliminations;
+            restarts += 1;
+            grid.reset();
+        }
+    }
+    eliminations += grid.eliminations;
+
+    Run { time: start.elapsed(), restarts, eliminations }
+}
+
+fn report(name: &str, runs: &mut [Run]) {
+    let count = runs.len().max(1);
+    runs.sort_by_key(|r| r.time);
+
+    let total_time: Duration = runs.iter().map(|r| r.time).sum();
+    let median_time = runs.get(runs.len() / 2).map_or(Duration::ZERO, |r| r.time);
+    let restarts: usize = runs.iter().map(|r| r.restarts).sum();
+    let eliminations: usize = runs.iter().map(|r| r.eliminations).sum();
+
+    println!(
+        "{:<24}{:>8}{:>12.3}{:>12.3}{:>12.2}{:>16.1}",
+        name,
+        runs.len(),
+        total_time.as_secs_f64() * 1000. / count as f64,
+        median_time.as_secs_f64() * 1000.,
+        restarts as f64 / count as f64,
+        eliminations as f64 / count as f64,
+    );
+}
+
+pub fn run(runs: usize, mut grid: Grid) {
+    println!(
+        "{:<24}{:>8}{:>12}{:>12}{:>12}{:>16}",
+        "heuristic", "runs", "avg ms", "median ms", "restarts", "eliminations"
+    );
+
+    for heuristic in Heuristic::ALL {
+        for backjumping in [false, true] {
+            grid.heuristic = heuristic;
+            grid.backjumping = backjumping;
+            let mut results: Vec<Run> = (0..runs).map(|_| solve(&mut grid)).collect();
+            let name = if backjumping { format!("{} backjump", heuristic.name()) } else { heuristic.name().to_string() };
+            report(&name, &mut results);
+        }
+    }
+}
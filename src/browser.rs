@@ -0,0 +1,145 @@
+use macroquad::prelude::*;
+
+use sudoku::library::{Difficulty, PUZZLES};
+use sudoku::{text, theme};
+
+use crate::keys::{self, Action};
+use crate::language::{self, tr, Text};
+use crate::records;
+
+
+pub enum Choice {
+    // Index in the library
+    Solve(usize),
+    Play(usize),
+    Close,
+}
+
+#[derive(Clone, Copy)]
+enum Row {
+    Heading(Difficulty),
+    Puzzle(usize),
+}
+
+// Menu of the bundled puzzles grouped by difficulty, picked with the arrows or the mouse,
+// the best time of each difficulty next to its heading
+pub struct Browser {
+    // Among the puzzle rows
+    selected: usize,
+    best_times: Vec<(Difficulty, f64)>,
+}
+
+impl Browser {
+    pub fn new() -> Self {
+        Self { selected: 0, best_times: records::load() }
+    }
+
+    fn rows() -> Vec<Row> {
+        Difficulty::ALL
+            .iter()
+            .flat_map(|&difficulty| {
+                let puzzles = (0..PUZZLES.len()).filter(move |&i| PUZZLES[i].difficulty == difficulty);
+                std::iter::once(Row::Heading(difficulty)).chain(puzzles.map(Row::Puzzle))
+            })
+            .collect()
+    }
+
+    fn puzzles() -> Vec<usize> {
+        Self::rows().into_iter().filter_map(|row| match row {
+            Row::Puzzle(i) => Some(i),
+            Row::Heading(_) => None,
+        }).collect()
+    }
+
+    fn label(&self, row: Row) -> String {
+        match row {
+            Row::Heading(difficulty) => match records::best(&self.best_times, difficulty) {
+                Some(seconds) => format!("{}   {}", language::difficulty(difficulty), tr(Text::Best, &[&records::format_time(seconds)])),
+                None => language::difficulty(difficulty),
+            },
+            Row::Puzzle(i) => {
+                let givens = PUZZLES[i].givens().iter().flatten().count();
+                format!("    {}  ({})", PUZZLES[i].name, tr(Text::Givens, &[&givens]))
+            }
+        }
+    }
+
+    fn title() -> String {
+        tr(Text::BrowserTitle, &[&keys::label(Action::Pick), &keys::label(Action::Play), &keys::label(Action::Back)])
+    }
+
+    // Panel centered on the screen and a box for each row under the title
+    fn boxes(&self) -> (Rect, Vec<(Row, Rect)>) {
+        let style = &theme::current().browser;
+        let rows = Self::rows();
+        let width = rows
+            .iter()
+            .map(|&row| self.label(row))
+            .chain(std::iter::once(Self::title()))
+            .map(|line| text::measure(&line, style.font_size).width)
+            .fold(0., f32::max) + 2. * style.padding;
+        let height = (rows.len() + 1) as f32 * style.font_size + 2. * style.padding;
+        let panel = Rect::new((screen_width() - width) / 2., (screen_height() - height) / 2., width, height);
+        let boxes = rows
+            .into_iter()
+            .enumerate()
+            .map(|(i, row)| {
+                let y = panel.y + style.padding + (i + 1) as f32 * style.font_size;
+                (row, Rect::new(panel.x, y, width, style.font_size))
+            })
+            .collect();
+        (panel, boxes)
+    }
+
+    pub fn update(&mut self) -> Option<Choice> {
+        let puzzles = Self::puzzles();
+        if is_key_pressed(KeyCode::Down) {
+            self.selected = (self.selected + 1) % puzzles.len();
+        }
+        if is_key_pressed(KeyCode::Up) {
+            self.selected = (self.selected + puzzles.len() - 1) % puzzles.len();
+        }
+
+        let position = mouse_position().into();
+        let hovered = self.boxes().1.into_iter().find_map(|(row, rect)| match row {
+            Row::Puzzle(i) if rect.contains(position) => Some(i),
+            _ => None,
+        });
+        if let Some(i) = hovered {
+            self.selected = puzzles.iter().position(|&puzzle| puzzle == i).unwrap();
+        }
+
+        let puzzle = puzzles[self.selected];
+        if keys::pressed(Action::Pick) || (hovered.is_some() && is_mouse_button_pressed(MouseButton::Left)) {
+            Some(Choice::Solve(puzzle))
+        } else if keys::pressed(Action::Play) {
+            Some(Choice::Play(puzzle))
+        } else if keys::pressed(Action::Back) {
+            Some(Choice::Close)
+        } else {
+            None
+        }
+    }
+
+    pub fn draw(&self) {
+        let style = &theme::current().browser;
+        let (panel, boxes) = self.boxes();
+        draw_rectangle(panel.x, panel.y, panel.w, panel.h, style.panel_color);
+        let baseline = |y: f32| y + style.font_size - style.font_size / 4.;
+        text::draw(&Self::title(), panel.x + style.padding, baseline(panel.y + style.padding), style.font_size, style.heading_color);
+
+        let selected = Self::puzzles()[self.selected];
+        for (row, rect) in boxes {
+            let color = match row {
+                Row::Heading(_) => style.heading_color,
+                Row::Puzzle(i) => {
+                    if i == selected {
+                        draw_rectangle(rect.x, rect.y, rect.w, rect.h, style.selected_color);
+                    }
+                    style.text_color
+                }
+            };
+            text::draw(&self.label(row), rect.x + style.padding, baseline(rect.y), style.font_size, color);
+        }
+    }
+}
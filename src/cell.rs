@@ -0,0 +1,60 @@
+use ::rand::seq::SliceRandom;
+use ::rand::Rng;
+
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Cell {
+    pub possible_values: Vec<u8>,
+}
+
+// Cloning into a cell reuses its room, grids resetting to their start without allocating
+impl Clone for Cell {
+    fn clone(&self) -> Self {
+        Self { possible_values: self.possible_values.clone() }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.possible_values.clone_from(&source.possible_values);
+    }
+}
+
+impl Cell {
+    pub fn new(size: usize) -> Self {
+        Self { possible_values: (1..=size as u8).collect() }
+    }
+
+    pub fn collapse(&mut self, rng: &mut impl Rng) -> u8 {
+        if self.possible_values.len() > 1 {
+            let value = *self.possible_values.choose(rng).unwrap();
+            self.possible_values.clear();
+            self.possible_values.push(value);
+        }
+        self.possible_values[0]
+    }
+
+    pub fn remove_possibility(&mut self, value: u8) -> Result<(), ()> {
+        if self.possible_values.len() > 1 {
+            self.possible_values.retain(|&val| val != value);
+        } else {
+            if self.possible_values[0] == value {
+                return Err(());
+            }
+        }
+        Ok(())
+    }
+
+    // Keep only the values accepted by `keep`, returns whether any was removed
+    pub fn retain_possibilities(&mut self, keep: impl Fn(u8) -> bool) -> Result<bool, ()> {
+        let count = self.possible_values.len();
+        self.possible_values.retain(|&v| keep(v));
+        if self.possible_values.is_empty() {
+            return Err(());
+        }
+        Ok(self.possible_values.len() != count)
+    }
+
+    // Candidates as a bit set, value v being bit v
+    pub fn mask(&self) -> u32 {
+        self.possible_values.iter().fold(0, |mask, v| mask | 1 << v)
+    }
+}
@@ -0,0 +1,148 @@
+use std::str::FromStr;
+
+use crate::web;
+
+
+enum Kind {
+    Flag,
+    // Placeholder of the value shown in the help
+    Value(&'static str),
+    // Value that may be left out, when no other option follows
+    Optional(&'static str),
+}
+
+use Kind::{Flag, Optional, Value};
+
+const OPTIONS: &[(&str, Kind, &str)] = &[
+    ("--help", Flag, "show this help"),
+    ("-h", Flag, "show this help"),
+    ("--version", Flag, "show the version"),
+    ("-V", Flag, "show the version"),
+    ("--seed", Value("N"), "seed of the first solve"),
+    ("--daily", Flag, "solve the grid of the day, the same for everyone"),
+    ("--backjump", Flag, "go back to the collapse behind a contradiction instead of starting over"),
+    ("--speed", Value("SECONDS"), "time between solver steps"),
+    ("--watchdog", Value("SECONDS"), "time without progress before a stall is reported"),
+    ("--preset", Value("NAME|FILE"), "bundled or custom settings to start from"),
+    ("--save-preset", Value("FILE"), "write the settings to a preset file"),
+    ("--size", Value("SIDE"), "board side, such as 4, 6, 9, 12 or 16"),
+    ("--variant", Value("FILE"), "rules to solve with"),
+    ("--save-variant", Value("FILE"), "write the rules to a variant file, where the editor saves"),
+    ("--regions", Value("FILE"), "jigsaw regions replacing the boxes"),
+    ("--diagonal", Flag, "both diagonals hold each digit once"),
+    ("--windoku", Flag, "four extra boxes hold each digit once"),
+    ("--anti-knight", Flag, "cells a knight's move apart differ"),
+    ("--anti-king", Flag, "cells a king's move apart differ"),
+    ("--non-consecutive", Flag, "neighbouring cells don't hold consecutive digits"),
+    ("--disjoint-groups", Flag, "cells at the same place in their box differ"),
+    ("--puzzle", Value("FILE"), "givens to solve from, .sdk, .sdm and .ss files too"),
+    ("--givens", Value("DIGITS"), "givens written out, . or 0 for the empty cells"),
+    ("--puzzle-number", Value("N"), "puzzle to take from an .sdm collection"),
+    ("--restore", Value("FILE"), "carry on with a saved solve"),
+    ("--theme", Value("FILE"), "colors and sizes, theme.toml when there"),
+    ("--save-theme", Value("FILE"), "write the theme to a file"),
+    ("--keys", Value("FILE"), "key bindings, keys.txt when there"),
+    ("--save-keys", Value("FILE"), "write the key bindings to a file"),
+    ("--input", Value("cell|digit"), "play by picking a cell then its digit, or a digit then its cells"),
+    ("--language", Value("CODE"), "en or fr for the on-screen text, from the locale by default"),
+    ("--edit", Flag, "start in the variant editor"),
+    ("--race", Optional("GRIDS"), "start with grids solving side by side, 4 by default"),
+    ("--compare", Optional("A,B"), "start with two heuristics solving the same grid, min-entropy,scan by default"),
+    ("--tui", Flag, "solve in the terminal"),
+    ("--bench", Optional("RUNS"), "time solves without a window"),
+    ("--generate", Value("COUNT"), "print puzzles with a single solution"),
+    ("--sdm", Value("FILE"), "write the generated puzzles to an .sdm collection"),
+    ("--symmetry", Value("KIND"), "none, rotational, mirror or diagonal pattern of the generated givens"),
+    ("--clues", Value("N"), "number of givens of the generated puzzles"),
+    ("--difficulty", Value("LEVEL"), "easy, medium, hard or expert rating of the generated puzzles"),
+    ("--minimal", Flag, "check every generated puzzle needs all its givens"),
+    ("--dump-frames", Value("DIR"), "render a solve to numbered PNGs"),
+    ("--resolution", Value("WxH"), "size of the dumped frames"),
+    ("--fps", Value("N"), "frames per second of the dumped frames"),
+    ("--export-replay", Value("FILE"), "write a solve to a standalone HTML player"),
+    ("--trace", Value("FILE"), "log every collapse and elimination of the solver"),
+    ("--metrics", Value("FILE"), "append a CSV row for each grid solved"),
+    ("--self-test", Flag, "run the checks and report"),
+    ("--headless", Flag, "skip the checks needing a window"),
+];
+
+
+fn find(name: &str) -> Option<&'static Kind> {
+    OPTIONS.iter().find(|(option, ..)| *option == name).map(|(_, kind, _)| kind)
+}
+
+pub fn help() -> String {
+    let mut lines = vec![
+        format!("Wave Function Collapse Sudoku {}", env!("CARGO_PKG_VERSION")),
+        String::new(),
+        "Usage: sudoku [OPTIONS]".to_string(),
+        String::new(),
+        "Options:".to_string(),
+    ];
+    let usage = |name: &str, kind: &Kind| match kind {
+        Flag => name.to_string(),
+        Value(placeholder) => format!("{} <{}>", name, placeholder),
+        Optional(placeholder) => format!("{} [{}]", name, placeholder),
+    };
+    // Short forms go without saying
+    let options = OPTIONS.iter().filter(|(name, ..)| name.starts_with("--"));
+    let width = options.clone().map(|(name, kind, _)| usage(name, kind).len()).max().unwrap_or(0);
+    lines.extend(options.map(|(name, kind, help)| format!("  {:<w$}  {}", usage(name, kind), help, w = width)));
+    lines.join("\n")
+}
+
+// Command line checked against the options above, values kept by option name
+pub struct Args {
+    values: Vec<(&'static str, Option<String>)>,
+}
+
+impl Args {
+    // Exits after printing the help or the version when asked for
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut values = Vec::new();
+        let mut args = args.iter().skip(1).peekable();
+        while let Some(arg) = args.next() {
+            let (name, kind) = OPTIONS
+                .iter()
+                .find(|(name, ..)| name == arg)
+                .map(|(name, kind, _)| (*name, kind))
+                .ok_or_else(|| format!("Unknown argument '{}', see --help", arg))?;
+            let value = match kind {
+                Flag => None,
+                Value(placeholder) => {
+                    let value = args.next().ok_or_else(|| format!("{} needs a value <{}>", name, placeholder))?;
+                    Some(value.clone())
+                }
+                Optional(_) => args.next_if(|value| find(value).is_none()).cloned(),
+            };
+            values.push((name, value));
+        }
+
+        let parsed = Self { values };
+        if parsed.flag("--help") || parsed.flag("-h") {
+            println!("{}", help());
+            std::process::exit(0);
+        }
+        if parsed.flag("--version") || parsed.flag("-V") {
+            println!("sudoku {}", env!("CARGO_PKG_VERSION"));
+            std::process::exit(0);
+        }
+        Ok(parsed)
+    }
+
+    pub fn flag(&self, name: &str) -> bool {
+        self.values.iter().any(|(option, _)| *option == name)
+    }
+
+    // Last value given to the option
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.values.iter().rev().find(|(option, _)| *option == name).and_then(|(_, value)| value.as_deref())
+    }
+
+    // Value read as a number or the like, failing with what was expected
+    pub fn parsed<T: FromStr>(&self, name: &str, expected: &str) -> Option<T> {
+        self.value(name).map(|value| {
+            value.parse().unwrap_or_else(|_| web::fail(&format!("Invalid value for {}: {}, expected {}", name, value, expected)))
+        })
+    }
+}
@@ -0,0 +1,43 @@
+use macroquad::miniquad;
+use macroquad::prelude::*;
+
+use sudoku::grid::{BoardSize, Grid};
+use sudoku::puzzle;
+
+use crate::keys::{self, Action};
+
+
+// Plain keys sharing these letters are left alone while it's held
+pub fn modifier_down() -> bool {
+    [KeyCode::LeftControl, KeyCode::RightControl, KeyCode::LeftSuper, KeyCode::RightSuper]
+        .into_iter()
+        .any(is_key_down)
+}
+
+pub fn copy_pressed() -> bool {
+    modifier_down() && keys::pressed(Action::Copy)
+}
+
+pub fn paste_pressed() -> bool {
+    modifier_down() && keys::pressed(Action::Paste)
+}
+
+// Resolved cells on one line, `.` for the others, the way other tools take grids
+pub fn copy(grid: &Grid) {
+    let values: Vec<Option<u8>> = grid.cells
+        .iter()
+        .map(|cell| match cell.possible_values[..] {
+            [value] => Some(value),
+            _ => None,
+        })
+        .collect();
+    let text = puzzle::serialize_line(&values, grid.size);
+    miniquad::clipboard::set(unsafe { get_internal_gl() }.quad_context, &text);
+}
+
+// Givens of the puzzle on the clipboard, in the layout puzzle files have
+pub fn paste(size: BoardSize) -> Result<Vec<Option<u8>>, String> {
+    let text = miniquad::clipboard::get(unsafe { get_internal_gl() }.quad_context)
+        .ok_or_else(|| "the clipboard holds no text".to_string())?;
+    puzzle::parse(&text, size)
+}
@@ -0,0 +1,88 @@
+use macroquad::prelude::*;
+
+use sudoku::grid::Grid;
+use sudoku::heuristic::Heuristic;
+use sudoku::layout::{Layout, Margins};
+use sudoku::{text, theme};
+
+use crate::input;
+use crate::keys::{self, Action};
+use crate::language::{tr, Text};
+use crate::race;
+use crate::screen::Screen;
+
+
+pub const DEFAULT_HEURISTICS: [Heuristic; 2] = [Heuristic::MinEntropy, Heuristic::Scan];
+
+// Heuristics to compare, as given on the command line
+pub fn parse(names: &str) -> Option<[Heuristic; 2]> {
+    let (left, right) = names.split_once(',')?;
+    Some([Heuristic::parse(left)?, Heuristic::parse(right)?])
+}
+
+struct Side {
+    grid: Grid,
+    steps: usize,
+    restarts: usize,
+}
+
+impl Side {
+    fn new(new_grid: &impl Fn(u64) -> Grid, seed: u64, heuristic: Heuristic) -> Self {
+        let mut grid = new_grid(seed);
+        grid.heuristic = heuristic;
+        Self { grid, steps: 0, restarts: 0 }
+    }
+
+    fn label(&self) -> String {
+        let text = if self.grid.is_resolve() { Text::CompareSolved } else { Text::CompareSolving };
+        tr(text, &[&self.grid.heuristic.name(), &self.steps, &self.restarts])
+    }
+}
+
+// The same grid solved from the same seed by two heuristics side by side, one step each
+// at a time, so how many steps and restarts each takes shows as they go
+pub async fn run(heuristics: [Heuristic; 2], new_grid: impl Fn(u64) -> Grid, tick_seconds: f64) -> Screen {
+    let new_sides = |seed| heuristics.map(|heuristic| Side::new(&new_grid, seed, heuristic));
+    let mut seed: u64 = ::rand::random();
+    let mut sides = new_sides(seed);
+    let mut tick = get_time();
+    loop {
+        if is_quit_requested() {
+            std::process::exit(0);
+        }
+        if keys::pressed(Action::Menu) {
+            return Screen::Title;
+        }
+        if keys::pressed(Action::NewGrid) {
+            seed = ::rand::random();
+            sides = new_sides(seed);
+        }
+        input::toggle_fullscreen();
+
+        if get_time() - tick > tick_seconds {
+            tick = get_time();
+            for side in sides.iter_mut().filter(|side| !side.grid.is_resolve()) {
+                side.steps += 1;
+                if side.grid.step().is_err() {
+                    side.grid.reset();
+                    side.restarts += 1;
+                }
+            }
+        }
+
+        let theme = theme::current();
+        clear_background(theme.board.background_color);
+        let help = tr(Text::CompareHelp, &[&seed, &keys::label(Action::NewGrid), &keys::label(Action::Menu)]);
+        text::draw(&help, 0., theme.text.font_size, theme.text.font_size, theme.text.color);
+        for (side, tile) in sides.iter().zip(race::tiles(2)) {
+            let label_height = theme.text.font_size;
+            let mut layout = Layout::fit(side.grid.size, Margins::default(), tile.w, tile.h - label_height);
+            layout.origin += vec2(tile.x, tile.y + label_height);
+            side.grid.draw(&layout);
+            let label = side.label();
+            let x = tile.x + (tile.w - text::measure(&label, theme.text.font_size).width) / 2.;
+            text::draw(&label, x, tile.y + label_height, theme.text.font_size, theme.text.color);
+        }
+        next_frame().await;
+    }
+}
@@ -0,0 +1,88 @@
+use macroquad::prelude::*;
+
+use crate::cell::Cell;
+use crate::layout::Layout;
+use crate::notation::format_cells;
+use crate::theme;
+
+use super::{draw_cell_path, Aggregate, Constraint};
+
+
+// Digits along the shaft sum to the digit in the circle, the first cell
+#[derive(Debug)]
+pub struct Arrow {
+    cells: Vec<usize>,
+}
+
+impl Arrow {
+    pub fn new(cells: Vec<usize>) -> Self {
+        Self { cells }
+    }
+
+    // Bounds the circle by the totals over the shaft, then each shaft digit by the circle
+    fn prune_sums(&self, cells: &mut [Cell], aggregate: &mut Aggregate) -> Result<bool, ()> {
+        if !aggregate.update(cells, &self.cells) && aggregate.settled {
+            return Ok(false);
+        }
+        let circle = self.cells[0];
+        let (min_sum, max_sum) = aggregate.others(0);
+        let (circle_min, circle_max) = aggregate.bounds(0);
+
+        let mut changed = cells[circle]
+            .retain_possibilities(|v| (min_sum..=max_sum).contains(&(v as u32)))?;
+        let values = &cells[circle].possible_values;
+        let (low, high) = (values[0] as u32, *values.last().unwrap() as u32);
+
+        // Each shaft digit leaves a sum the other shaft cells can reach
+        for (slot, &idx) in self.cells.iter().enumerate().skip(1) {
+            let (others_min, others_max) = aggregate.others(slot);
+            let (others_min, others_max) = (others_min - circle_min, others_max - circle_max);
+            changed |= cells[idx].retain_possibilities(|v| {
+                v as u32 + others_min <= high && v as u32 + others_max >= low
+            })?;
+        }
+        aggregate.settled = !changed;
+        Ok(changed)
+    }
+}
+
+impl Constraint for Arrow {
+    fn affected_cells(&self) -> &[usize] {
+        &self.cells
+    }
+
+    fn prune(&self, cells: &mut [Cell]) -> Result<bool, ()> {
+        self.prune_sums(cells, &mut Aggregate::default())
+    }
+
+    fn prune_with(&self, cells: &mut [Cell], aggregate: &mut Aggregate) -> Result<bool, ()> {
+        self.prune_sums(cells, aggregate)
+    }
+
+    fn draw(&self, layout: &Layout) {
+        let style = &theme::current().constraints;
+        let center = |idx: usize| layout.cell_position(idx) + Vec2::splat(layout.cell_size / 2.);
+        let thickness = style.arrow_thickness * layout.cell_size;
+        let radius = style.arrow_circle_radius * layout.cell_size;
+
+        let circle = center(self.cells[0]);
+        draw_circle_lines(circle.x, circle.y, radius, thickness, style.arrow_color);
+
+        let first = center(self.cells[1]);
+        let start = circle + (first - circle).normalize() * radius;
+        draw_line(start.x, start.y, first.x, first.y, thickness, style.arrow_color);
+        draw_cell_path(layout, &self.cells[1..], thickness, style.arrow_color);
+
+        let tip = center(self.cells[self.cells.len() - 1]);
+        let before = center(self.cells[self.cells.len() - 2]);
+        let back = (before - tip).normalize() * style.arrow_head_length * layout.cell_size;
+        for side in [-1., 1.] {
+            let wing = tip + back + back.perp() * side * 0.6;
+            draw_line(tip.x, tip.y, wing.x, wing.y, thickness, style.arrow_color);
+        }
+    }
+
+    fn rule(&self, side: usize) -> String {
+        format!("arrow {}", format_cells(&self.cells, side))
+    }
+}
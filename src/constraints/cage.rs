@@ -0,0 +1,232 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use macroquad::prelude::*;
+
+use crate::cell::Cell;
+use crate::layout::Layout;
+use crate::notation::format_cells;
+use crate::text;
+use crate::theme;
+
+use super::{apply_supports, mask_values, Aggregate, Constraint};
+
+
+// Cages up to this size get full combination based pruning
+const GAC_MAX_CELLS: usize = 6;
+
+
+// Cell count, sum and board side: cages sharing these share their digit sets
+type Signature = (usize, u32, usize);
+
+thread_local! {
+    // Digit sets of every cage signature met so far
+    static COMBINATIONS: RefCell<HashMap<Signature, Rc<Vec<u32>>>> = RefCell::default();
+}
+
+// Sets of `count` distinct digits from 1 to `side` adding up to `sum`, as masks
+fn combinations(count: usize, sum: u32, side: usize) -> Rc<Vec<u32>> {
+    fn collect(count: usize, sum: u32, from: u32, side: u32, mask: u32, found: &mut Vec<u32>) {
+        if count == 0 {
+            if sum == 0 {
+                found.push(mask);
+            }
+            return;
+        }
+        for digit in from..=side.min(sum) {
+            collect(count - 1, sum - digit, digit + 1, side, mask | 1 << digit, found);
+        }
+    }
+
+    COMBINATIONS.with(|cache| {
+        cache
+            .borrow_mut()
+            .entry((count, sum, side))
+            .or_insert_with(|| {
+                let mut found = Vec::new();
+                collect(count, sum, 1, side as u32, 0, &mut found);
+                Rc::new(found)
+            })
+            .clone()
+    })
+}
+
+
+// Killer cage: distinct digits adding up to `sum`
+#[derive(Debug)]
+pub struct Cage {
+    sum: u32,
+    side: usize,
+    cells: Vec<usize>,
+}
+
+impl Cage {
+    pub fn new(sum: u32, side: usize, cells: Vec<usize>) -> Self {
+        Self { sum, side, cells }
+    }
+
+    // Whether the cells from `used.count_ones()` on can take the remaining digits of
+    // `combination`, recording in `supports` the digits that lead to a full assignment
+    fn explore(
+        candidates: &[u32],
+        combination: u32,
+        used: u32,
+        memo: &mut HashMap<u32, bool>,
+        supports: &mut [u32],
+    ) -> bool {
+        let pos = used.count_ones() as usize;
+        if pos == candidates.len() {
+            return true;
+        }
+        if let Some(&feasible) = memo.get(&used) {
+            return feasible;
+        }
+
+        let mut feasible = false;
+        for v in mask_values(candidates[pos] & combination & !used) {
+            if Self::explore(candidates, combination, used | 1 << v, memo, supports) {
+                supports[pos] |= 1 << v;
+                feasible = true;
+            }
+        }
+        memo.insert(used, feasible);
+        feasible
+    }
+
+    fn prune_combinations(&self, cells: &mut [Cell]) -> Result<bool, ()> {
+        let candidates: Vec<u32> = self.cells.iter().map(|&idx| cells[idx].mask()).collect();
+        let available = candidates.iter().fold(0, |all, mask| all | mask);
+        let mut supports = vec![0; candidates.len()];
+
+        for &combination in combinations(self.cells.len(), self.sum, self.side).iter() {
+            if combination & !available == 0 {
+                Self::explore(&candidates, combination, 0, &mut HashMap::new(), &mut supports);
+            }
+        }
+        if supports.contains(&0) {
+            return Err(());
+        }
+        apply_supports(cells, &self.cells, &supports)
+    }
+
+    fn prune_sums(&self, cells: &mut [Cell], aggregate: &mut Aggregate) -> Result<bool, ()> {
+        if !aggregate.update(cells, &self.cells) && aggregate.settled {
+            return Ok(false);
+        }
+        if aggregate.unfilled == 0 && aggregate.placed_sum != self.sum {
+            return Err(());
+        }
+
+        let mut changed = false;
+
+        // Digits don't repeat within the cage
+        for &idx in &self.cells {
+            if let [value] = cells[idx].possible_values[..] {
+                for &other in self.cells.iter().filter(|&&other| other != idx) {
+                    changed |= cells[other].retain_possibilities(|v| v != value)?;
+                }
+            }
+        }
+
+        // A digit has to leave a reachable sum for the rest of the cage
+        for (slot, &idx) in self.cells.iter().enumerate() {
+            let (others_min, others_max) = aggregate.others(slot);
+            changed |= cells[idx].retain_possibilities(|v| {
+                others_min + v as u32 <= self.sum && others_max + v as u32 >= self.sum
+            })?;
+        }
+
+        if self.cells.len() <= GAC_MAX_CELLS {
+            changed |= self.prune_combinations(cells)?;
+        }
+        aggregate.settled = !changed;
+        Ok(changed)
+    }
+
+    fn contains(&self, row: isize, col: isize) -> bool {
+        let side = self.side as isize;
+        (0..side).contains(&row)
+            && (0..side).contains(&col)
+            && self.cells.contains(&((row * side + col) as usize))
+    }
+}
+
+impl Constraint for Cage {
+    fn affected_cells(&self) -> &[usize] {
+        &self.cells
+    }
+
+    fn prune(&self, cells: &mut [Cell]) -> Result<bool, ()> {
+        self.prune_sums(cells, &mut Aggregate::default())
+    }
+
+    fn prune_with(&self, cells: &mut [Cell], aggregate: &mut Aggregate) -> Result<bool, ()> {
+        self.prune_sums(cells, aggregate)
+    }
+
+    fn draw(&self, layout: &Layout) {
+        let theme = theme::current();
+        let style = &theme.constraints;
+        let inset = style.cage_inset * layout.cell_size;
+        let size = layout.cell_size;
+        let side = self.side;
+
+        for &idx in &self.cells {
+            let (row, col) = ((idx / side) as isize, (idx % side) as isize);
+            let p = layout.cell_position(idx);
+            let (up, down) = (self.contains(row - 1, col), self.contains(row + 1, col));
+            let (left, right) = (self.contains(row, col - 1), self.contains(row, col + 1));
+
+            // Sides facing outside the cage, extended toward neighbours inside it
+            let x0 = if left { p.x } else { p.x + inset };
+            let x1 = if right { p.x + size } else { p.x + size - inset };
+            let y0 = if up { p.y } else { p.y + inset };
+            let y1 = if down { p.y + size } else { p.y + size - inset };
+            if !up {
+                draw_dashed_line(vec2(x0, p.y + inset), vec2(x1, p.y + inset), layout);
+            }
+            if !down {
+                draw_dashed_line(vec2(x0, p.y + size - inset), vec2(x1, p.y + size - inset), layout);
+            }
+            if !left {
+                draw_dashed_line(vec2(p.x + inset, y0), vec2(p.x + inset, y1), layout);
+            }
+            if !right {
+                draw_dashed_line(vec2(p.x + size - inset, y0), vec2(p.x + size - inset, y1), layout);
+            }
+        }
+
+        // Sum in the corner of the top-left cell
+        if let Some(&first) = self.cells.iter().min() {
+            let p = layout.cell_position(first);
+            let font_size = style.cage_sum_font_size * layout.scale();
+            let dimensions = text::measure(&self.sum.to_string(), font_size);
+            draw_rectangle(p.x + inset / 2., p.y + inset / 2., dimensions.width + inset, dimensions.height + inset, theme.board.background_color);
+            text::draw(
+                &self.sum.to_string(),
+                p.x + inset,
+                p.y + inset + dimensions.offset_y,
+                font_size,
+                style.cage_color);
+        }
+    }
+
+    fn rule(&self, side: usize) -> String {
+        format!("cage {} {}", self.sum, format_cells(&self.cells, side))
+    }
+}
+
+fn draw_dashed_line(from: Vec2, to: Vec2, layout: &Layout) {
+    let style = &theme::current().constraints;
+    let length = from.distance(to);
+    let dash = style.cage_dash_length * layout.cell_size;
+    let direction = (to - from) / length;
+    let mut start = 0.;
+    while start < length {
+        let a = from + direction * start;
+        let b = from + direction * (start + dash).min(length);
+        draw_line(a.x, a.y, b.x, b.y, style.cage_thickness * layout.scale(), style.cage_color);
+        start += 2. * dash;
+    }
+}
@@ -0,0 +1,89 @@
+use macroquad::prelude::*;
+
+use crate::cell::Cell;
+use crate::layout::Layout;
+use crate::notation::format_cells;
+use crate::theme;
+
+use super::{draw_cell_path, Constraint};
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineClasses {
+    // Low, middle and high thirds of the digits
+    Entropy,
+    // Remainder of the digit modulo 3
+    Modular,
+}
+
+// Every three consecutive cells of the line hold one digit of each class
+#[derive(Debug)]
+pub struct ClassLine {
+    classes: LineClasses,
+    side: usize,
+    cells: Vec<usize>,
+}
+
+impl ClassLine {
+    pub fn new(classes: LineClasses, side: usize, cells: Vec<usize>) -> Self {
+        Self { classes, side, cells }
+    }
+
+    fn class_of(&self, value: u8) -> usize {
+        match self.classes {
+            LineClasses::Entropy => (value as usize - 1) * 3 / self.side,
+            LineClasses::Modular => value as usize % 3,
+        }
+    }
+}
+
+impl Constraint for ClassLine {
+    fn affected_cells(&self) -> &[usize] {
+        &self.cells
+    }
+
+    fn prune(&self, cells: &mut [Cell]) -> Result<bool, ()> {
+        // Cells three apart share their class, so positions fall in three groups
+        // of possible classes, each group taking a different class
+        let mut possible = [0b111u8; 3];
+        for (pos, &idx) in self.cells.iter().enumerate() {
+            let classes = cells[idx].possible_values
+                .iter()
+                .fold(0, |classes, &v| classes | 1 << self.class_of(v));
+            possible[pos % 3] &= classes;
+        }
+
+        let mut supported = [0u8; 3];
+        for order in [[0, 1, 2], [0, 2, 1], [1, 0, 2], [1, 2, 0], [2, 0, 1], [2, 1, 0]] {
+            if (0..3).all(|group| possible[group] & 1 << order[group] != 0) {
+                for group in 0..3 {
+                    supported[group] |= 1 << order[group];
+                }
+            }
+        }
+
+        let mut changed = false;
+        for (pos, &idx) in self.cells.iter().enumerate() {
+            let supported = supported[pos % 3];
+            changed |= cells[idx].retain_possibilities(|v| supported & 1 << self.class_of(v) != 0)?;
+        }
+        Ok(changed)
+    }
+
+    fn draw(&self, layout: &Layout) {
+        let style = &theme::current().constraints;
+        let color = match self.classes {
+            LineClasses::Entropy => style.entropy_color,
+            LineClasses::Modular => style.modular_color,
+        };
+        draw_cell_path(layout, &self.cells, style.class_line_thickness * layout.cell_size, color);
+    }
+
+    fn rule(&self, side: usize) -> String {
+        let name = match self.classes {
+            LineClasses::Entropy => "entropy",
+            LineClasses::Modular => "modular",
+        };
+        format!("{} {}", name, format_cells(&self.cells, side))
+    }
+}
@@ -0,0 +1,52 @@
+use macroquad::prelude::*;
+
+use crate::cell::Cell;
+use crate::layout::Layout;
+use crate::notation::format_cells;
+use crate::theme;
+
+use super::{prune_equal_pairs, Constraint};
+
+
+// Two regions of the same shape holding the same digits in corresponding cells
+#[derive(Debug)]
+pub struct CloneRegions {
+    pairs: Vec<(usize, usize)>,
+    cells: Vec<usize>,
+}
+
+impl CloneRegions {
+    pub fn new(first: Vec<usize>, second: Vec<usize>) -> Self {
+        let cells = first.iter().chain(&second).copied().collect();
+        Self { pairs: first.into_iter().zip(second).collect(), cells }
+    }
+}
+
+impl Constraint for CloneRegions {
+    fn affected_cells(&self) -> &[usize] {
+        &self.cells
+    }
+
+    fn prune(&self, cells: &mut [Cell]) -> Result<bool, ()> {
+        prune_equal_pairs(cells, &self.pairs)
+    }
+
+    fn draw(&self, layout: &Layout) {
+        let style = &theme::current().constraints;
+        let inset = style.clone_inset * layout.cell_size;
+        for &idx in &self.cells {
+            let position = layout.cell_position(idx);
+            draw_rectangle(
+                position.x + inset,
+                position.y + inset,
+                layout.cell_size - 2. * inset,
+                layout.cell_size - 2. * inset,
+                style.clone_color);
+        }
+    }
+
+    fn rule(&self, side: usize) -> String {
+        let (first, second): (Vec<usize>, Vec<usize>) = self.pairs.iter().copied().unzip();
+        format!("clone {} / {}", format_cells(&first, side), format_cells(&second, side))
+    }
+}
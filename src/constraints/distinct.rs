@@ -0,0 +1,61 @@
+use crate::cell::Cell;
+use crate::layout::Layout;
+
+use super::Constraint;
+
+
+// Cells holding distinct digits: rows, columns and boxes, plus the extra houses and
+// pairs of the flag variants
+#[derive(Debug)]
+pub struct Distinct {
+    cells: Vec<usize>,
+}
+
+impl Distinct {
+    pub fn new(cells: Vec<usize>) -> Self {
+        Self { cells }
+    }
+}
+
+impl Constraint for Distinct {
+    fn affected_cells(&self) -> &[usize] {
+        &self.cells
+    }
+
+    // Remove collapsed digits from the other cells, until removing them collapses no more
+    fn prune(&self, cells: &mut [Cell]) -> Result<bool, ()> {
+        let mut changed = false;
+        let mut removed = 0;
+        loop {
+            let mut placed = 0;
+            for &idx in &self.cells {
+                if let [value] = cells[idx].possible_values[..] {
+                    // Left without candidates, as the clashing cell
+                    if placed & 1 << value != 0 {
+                        cells[idx].possible_values.clear();
+                        return Err(());
+                    }
+                    placed |= 1 << value;
+                }
+            }
+            let new = placed & !removed;
+            if new == 0 {
+                return Ok(changed);
+            }
+            for &idx in &self.cells {
+                if cells[idx].possible_values.len() > 1 {
+                    changed |= cells[idx].retain_possibilities(|v| new & 1 << v == 0)?;
+                }
+            }
+            removed = placed;
+        }
+    }
+
+    // Houses are drawn by the grid
+    fn draw(&self, _layout: &Layout) {}
+
+    // Built from the grid and its flags, so never written on its own
+    fn rule(&self, _side: usize) -> String {
+        String::new()
+    }
+}
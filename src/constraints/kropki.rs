@@ -0,0 +1,123 @@
+use macroquad::prelude::*;
+
+use crate::cell::Cell;
+use crate::layout::Layout;
+use crate::notation::format_cells;
+use crate::theme;
+
+use super::{prune_relation, Constraint};
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dot {
+    // Consecutive digits
+    White,
+    // One digit double the other
+    Black,
+}
+
+impl Dot {
+    fn allows(self, a: u8, b: u8) -> bool {
+        match self {
+            Dot::White => a.abs_diff(b) == 1,
+            Dot::Black => a == 2 * b || b == 2 * a,
+        }
+    }
+}
+
+// Dots between orthogonal neighbours. With the negative convention all dots are
+// given, so neighbours without a dot are neither consecutive nor in a 1:2 ratio.
+#[derive(Debug)]
+pub struct Kropki {
+    side: usize,
+    dots: Vec<(Dot, usize, usize)>,
+    negative: bool,
+    cells: Vec<usize>,
+}
+
+impl Kropki {
+    pub fn new(side: usize) -> Self {
+        Self { side, dots: Vec::new(), negative: false, cells: Vec::new() }
+    }
+
+    pub fn add_dot(&mut self, dot: Dot, a: usize, b: usize) {
+        self.dots.push((dot, a.min(b), a.max(b)));
+        for idx in [a, b] {
+            if !self.cells.contains(&idx) {
+                self.cells.push(idx);
+            }
+        }
+    }
+
+    pub fn set_negative(&mut self) {
+        self.negative = true;
+        self.cells = (0..self.side * self.side).collect();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dots.is_empty() && !self.negative
+    }
+
+    fn has_dot(&self, a: usize, b: usize) -> bool {
+        self.dots.iter().any(|&(_, x, y)| (x, y) == (a, b))
+    }
+}
+
+impl Constraint for Kropki {
+    fn affected_cells(&self) -> &[usize] {
+        &self.cells
+    }
+
+    fn prune(&self, cells: &mut [Cell]) -> Result<bool, ()> {
+        let mut changed = false;
+        for &(dot, a, b) in &self.dots {
+            changed |= prune_relation(cells, a, b, |x, y| dot.allows(x, y))?;
+        }
+
+        if self.negative {
+            let side = self.side;
+            for a in 0..side * side {
+                let right = (a % side != side - 1).then_some(a + 1);
+                let below = (a / side != side - 1).then_some(a + side);
+                for b in right.into_iter().chain(below).filter(|&b| !self.has_dot(a, b)) {
+                    changed |= prune_relation(cells, a, b, |x, y| {
+                        !Dot::White.allows(x, y) && !Dot::Black.allows(x, y)
+                    })?;
+                }
+            }
+        }
+        Ok(changed)
+    }
+
+    fn draw(&self, layout: &Layout) {
+        let style = &theme::current().constraints;
+        let radius = style.kropki_radius * layout.cell_size;
+        for &(dot, a, b) in &self.dots {
+            let center = (layout.cell_position(a) + layout.cell_position(b)) / 2.
+                + Vec2::splat(layout.cell_size / 2.);
+            let fill = match dot {
+                Dot::White => style.kropki_white_color,
+                Dot::Black => style.kropki_black_color,
+            };
+            draw_circle(center.x, center.y, radius, fill);
+            draw_circle_lines(center.x, center.y, radius, 1., style.kropki_outline_color);
+        }
+    }
+
+    fn rule(&self, side: usize) -> String {
+        let mut rules: Vec<String> = self.dots
+            .iter()
+            .map(|&(dot, a, b)| {
+                let color = match dot {
+                    Dot::White => "white",
+                    Dot::Black => "black",
+                };
+                format!("kropki {} {}", color, format_cells(&[a, b], side))
+            })
+            .collect();
+        if self.negative {
+            rules.push("kropki-negative".to_string());
+        }
+        rules.join("\n")
+    }
+}
@@ -0,0 +1,212 @@
+use std::fmt::Debug;
+
+use macroquad::prelude::*;
+
+use crate::cell::Cell;
+use crate::layout::{Edge, Layout, Margins};
+use crate::text;
+use crate::theme;
+
+mod arrow;
+mod cage;
+mod class_line;
+mod clone_regions;
+mod distinct;
+mod kropki;
+mod non_consecutive;
+mod palindrome;
+mod parity;
+mod quadruple;
+mod relation;
+mod sandwich;
+mod skyscraper;
+mod thermometer;
+mod x_sum;
+
+pub use arrow::Arrow;
+pub use cage::Cage;
+pub use class_line::{ClassLine, LineClasses};
+pub use clone_regions::CloneRegions;
+pub use distinct::Distinct;
+pub use kropki::{Dot, Kropki};
+pub use non_consecutive::NonConsecutive;
+pub use palindrome::Palindrome;
+pub use parity::{Parity, ParityCells};
+pub use quadruple::Quadruple;
+pub use relation::Relation;
+pub use sandwich::Sandwich;
+pub use skyscraper::Skyscraper;
+pub use thermometer::Thermometer;
+pub use x_sum::XSum;
+
+
+pub trait Constraint: Debug {
+    // Cells whose candidates `prune` may restrict
+    fn affected_cells(&self) -> &[usize];
+
+    // Remove candidates that can't be part of any valid placement, returns whether any was removed
+    fn prune(&self, cells: &mut [Cell]) -> Result<bool, ()>;
+
+    // `prune` for a grid keeping the totals of the previous call on its cells, for the
+    // constraints working from sums to only fold in the cells that changed since
+    fn prune_with(&self, cells: &mut [Cell], _aggregate: &mut Aggregate) -> Result<bool, ()> {
+        self.prune(cells)
+    }
+
+    fn margins(&self) -> Margins {
+        Margins::default()
+    }
+
+    fn draw(&self, layout: &Layout);
+
+    // Variant file rule describing the constraint, the canonical form used to save it
+    fn rule(&self, side: usize) -> String;
+}
+
+// Running totals over the candidates of a line of cells, updated only for the cells whose
+// candidates changed since the last update. Grids keep one per constraint between prunes
+#[derive(Debug, Default)]
+pub struct Aggregate {
+    // Candidates of each cell when last folded into the totals
+    masks: Vec<u32>,
+    placed_sum: u32,
+    unfilled: usize,
+    min_sum: u32,
+    max_sum: u32,
+    // Pruning from the folded candidates removed nothing
+    settled: bool,
+}
+
+impl Aggregate {
+    // Fold in the cells whose candidates changed, returns whether any did
+    fn update(&mut self, cells: &[Cell], line: &[usize]) -> bool {
+        if self.masks.len() != line.len() {
+            self.masks.resize(line.len(), 0);
+            self.invalidate();
+        }
+
+        let mut changed = false;
+        for (slot, &idx) in line.iter().enumerate() {
+            let mask = cells[idx].mask();
+            if mask != self.masks[slot] {
+                self.fold(self.masks[slot], false);
+                self.fold(mask, true);
+                self.masks[slot] = mask;
+                changed = true;
+            }
+        }
+        self.settled &= !changed;
+        changed
+    }
+
+    fn fold(&mut self, mask: u32, add: bool) {
+        if mask == 0 {
+            return;
+        }
+        let (min, max) = (mask.trailing_zeros(), 31 - mask.leading_zeros());
+        let (placed, unfilled) = if mask.count_ones() == 1 { (min, 0) } else { (0, 1) };
+        if add {
+            self.min_sum += min;
+            self.max_sum += max;
+            self.placed_sum += placed;
+            self.unfilled += unfilled;
+        } else {
+            self.min_sum -= min;
+            self.max_sum -= max;
+            self.placed_sum -= placed;
+            self.unfilled -= unfilled;
+        }
+    }
+
+    // Smallest and largest digits left in the cell in `slot`, none for an empty cell
+    fn bounds(&self, slot: usize) -> (u32, u32) {
+        match self.masks[slot] {
+            0 => (0, 0),
+            mask => (mask.trailing_zeros(), 31 - mask.leading_zeros()),
+        }
+    }
+
+    // Smallest and largest sums of the line without the cell in `slot`
+    fn others(&self, slot: usize) -> (u32, u32) {
+        let (min, max) = self.bounds(slot);
+        (self.min_sum - min, self.max_sum - max)
+    }
+
+    // Forget the totals, the next update folding in every cell again
+    pub fn invalidate(&mut self) {
+        self.masks.fill(0);
+        *self = Aggregate { masks: std::mem::take(&mut self.masks), ..Aggregate::default() };
+    }
+}
+
+// Iterate over the values set in a candidate mask
+fn mask_values(mask: u32) -> impl Iterator<Item = u32> {
+    (0..32).filter(move |v| mask & 1 << v != 0)
+}
+
+// Restrict each cell of `line` to the values of its support mask
+fn apply_supports(cells: &mut [Cell], line: &[usize], supports: &[u32]) -> Result<bool, ()> {
+    let mut changed = false;
+    for (&idx, &support) in line.iter().zip(supports) {
+        changed |= cells[idx].retain_possibilities(|v| support & 1 << v != 0)?;
+    }
+    Ok(changed)
+}
+
+// Keep the candidates of `a` and `b` having a partner in the other cell they are `related` to
+fn prune_relation(
+    cells: &mut [Cell],
+    a: usize,
+    b: usize,
+    related: impl Fn(u8, u8) -> bool,
+) -> Result<bool, ()> {
+    let (values_a, values_b) = (cells[a].possible_values.clone(), cells[b].possible_values.clone());
+    let mut changed = cells[a].retain_possibilities(|x| values_b.iter().any(|&y| related(x, y)))?;
+    changed |= cells[b].retain_possibilities(|y| values_a.iter().any(|&x| related(x, y)))?;
+    Ok(changed)
+}
+
+// Both cells of each pair hold the same digit, so they share their candidates
+fn prune_equal_pairs(cells: &mut [Cell], pairs: &[(usize, usize)]) -> Result<bool, ()> {
+    let mut changed = false;
+    for &(a, b) in pairs {
+        let common = cells[a].mask() & cells[b].mask();
+        changed |= cells[a].retain_possibilities(|v| common & 1 << v != 0)?;
+        changed |= cells[b].retain_possibilities(|v| common & 1 << v != 0)?;
+    }
+    Ok(changed)
+}
+
+// Line through the centers of consecutive cells, with rounded joints
+fn draw_cell_path(layout: &Layout, cells: &[usize], thickness: f32, color: Color) {
+    let center = |idx: usize| layout.cell_position(idx) + Vec2::splat(layout.cell_size / 2.);
+    for (&a, &b) in cells.iter().zip(cells.iter().skip(1)) {
+        let (a, b) = (center(a), center(b));
+        draw_line(a.x, a.y, b.x, b.y, thickness, color);
+    }
+    for &idx in cells {
+        let c = center(idx);
+        draw_circle(c.x, c.y, thickness / 2., color);
+    }
+}
+
+// Rule with a single clue in front of `line`, `variant::serialize` merges those of an edge
+fn outside_clue_rule(name: &str, side: usize, edge: Edge, line: usize, clue: u32) -> String {
+    let clues: Vec<String> = (0..side)
+        .map(|l| if l == line { clue.to_string() } else { ".".to_string() })
+        .collect();
+    format!("{} {} {}", name, edge.name(), clues.join(" "))
+}
+
+fn draw_outside_clue(layout: &Layout, edge: Edge, line: usize, text: &str) {
+    let style = &theme::current().constraints;
+    let position = layout.outside_cell_position(edge, line, 0);
+    let font_size = style.clue_font_size * layout.scale();
+    let dimensions = text::measure(text, font_size);
+    text::draw(
+        text,
+        position.x + (layout.cell_size - dimensions.width) / 2.,
+        position.y + (layout.cell_size + dimensions.offset_y) / 2.,
+        font_size,
+        style.clue_color);
+}
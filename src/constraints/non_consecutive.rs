@@ -0,0 +1,34 @@
+use crate::cell::Cell;
+use crate::layout::Layout;
+
+use super::{prune_relation, Constraint};
+
+
+// Orthogonally adjacent cells can't hold consecutive digits
+#[derive(Debug)]
+pub struct NonConsecutive {
+    cells: [usize; 2],
+}
+
+impl NonConsecutive {
+    pub fn new(a: usize, b: usize) -> Self {
+        Self { cells: [a, b] }
+    }
+}
+
+impl Constraint for NonConsecutive {
+    fn affected_cells(&self) -> &[usize] {
+        &self.cells
+    }
+
+    fn prune(&self, cells: &mut [Cell]) -> Result<bool, ()> {
+        prune_relation(cells, self.cells[0], self.cells[1], |a, b| a.abs_diff(b) != 1)
+    }
+
+    fn draw(&self, _layout: &Layout) {}
+
+    // Built from the `non-consecutive` flag
+    fn rule(&self, _side: usize) -> String {
+        String::new()
+    }
+}
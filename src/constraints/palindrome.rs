@@ -0,0 +1,47 @@
+use macroquad::prelude::*;
+
+use crate::cell::Cell;
+use crate::layout::Layout;
+use crate::notation::format_cells;
+use crate::theme;
+
+use super::{draw_cell_path, prune_equal_pairs, Constraint};
+
+
+// A line reading the same digits from both ends
+#[derive(Debug)]
+pub struct Palindrome {
+    cells: Vec<usize>,
+    pairs: Vec<(usize, usize)>,
+}
+
+impl Palindrome {
+    pub fn new(cells: Vec<usize>) -> Self {
+        let pairs = cells
+            .iter()
+            .zip(cells.iter().rev())
+            .take(cells.len() / 2)
+            .map(|(&a, &b)| (a, b))
+            .collect();
+        Self { cells, pairs }
+    }
+}
+
+impl Constraint for Palindrome {
+    fn affected_cells(&self) -> &[usize] {
+        &self.cells
+    }
+
+    fn prune(&self, cells: &mut [Cell]) -> Result<bool, ()> {
+        prune_equal_pairs(cells, &self.pairs)
+    }
+
+    fn draw(&self, layout: &Layout) {
+        let style = &theme::current().constraints;
+        draw_cell_path(layout, &self.cells, style.palindrome_thickness * layout.cell_size, style.palindrome_color);
+    }
+
+    fn rule(&self, side: usize) -> String {
+        format!("palindrome {}", format_cells(&self.cells, side))
+    }
+}
@@ -0,0 +1,70 @@
+use macroquad::prelude::*;
+
+use crate::cell::Cell;
+use crate::layout::Layout;
+use crate::notation::format_cells;
+use crate::theme;
+
+use super::Constraint;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Parity {
+    // Drawn as a circle
+    Odd,
+    // Drawn as a square
+    Even,
+}
+
+// Every cell holds a digit of the given parity
+#[derive(Debug)]
+pub struct ParityCells {
+    parity: Parity,
+    cells: Vec<usize>,
+}
+
+impl ParityCells {
+    pub fn new(parity: Parity, cells: Vec<usize>) -> Self {
+        Self { parity, cells }
+    }
+}
+
+impl Constraint for ParityCells {
+    fn affected_cells(&self) -> &[usize] {
+        &self.cells
+    }
+
+    fn prune(&self, cells: &mut [Cell]) -> Result<bool, ()> {
+        let remainder = match self.parity {
+            Parity::Odd => 1,
+            Parity::Even => 0,
+        };
+        let mut changed = false;
+        for &idx in &self.cells {
+            changed |= cells[idx].retain_possibilities(|v| v % 2 == remainder)?;
+        }
+        Ok(changed)
+    }
+
+    fn draw(&self, layout: &Layout) {
+        let style = &theme::current().constraints;
+        let size = style.parity_size * layout.cell_size;
+        for &idx in &self.cells {
+            let center = layout.cell_position(idx) + Vec2::splat(layout.cell_size / 2.);
+            match self.parity {
+                Parity::Odd => draw_circle(center.x, center.y, size / 2., style.parity_color),
+                Parity::Even => {
+                    draw_rectangle(center.x - size / 2., center.y - size / 2., size, size, style.parity_color)
+                }
+            }
+        }
+    }
+
+    fn rule(&self, side: usize) -> String {
+        let name = match self.parity {
+            Parity::Odd => "odd",
+            Parity::Even => "even",
+        };
+        format!("{} {}", name, format_cells(&self.cells, side))
+    }
+}
@@ -0,0 +1,106 @@
+use macroquad::prelude::*;
+
+use crate::cell::Cell;
+use crate::layout::Layout;
+use crate::notation::format_cells;
+use crate::text;
+use crate::theme;
+
+use super::{apply_supports, mask_values, Constraint};
+
+
+// Every listed digit appears among the four cells around a grid intersection
+#[derive(Debug)]
+pub struct Quadruple {
+    cells: Vec<usize>,
+    digits: Vec<u8>,
+}
+
+impl Quadruple {
+    // `top_left` is the cell up and left of the intersection
+    pub fn new(side: usize, top_left: usize, digits: Vec<u8>) -> Self {
+        let cells = vec![top_left, top_left + 1, top_left + side, top_left + side + 1];
+        Self { cells, digits }
+    }
+
+    fn is_satisfied(&self, values: &[u32]) -> bool {
+        self.digits.iter().all(|&digit| {
+            let wanted = self.digits.iter().filter(|&&d| d == digit).count();
+            values.iter().filter(|&&v| v == digit as u32).count() >= wanted
+        })
+    }
+
+    // Try every assignment of the four cells, orthogonal neighbours being in the same house
+    fn explore(&self, candidates: &[u32], values: &mut Vec<u32>, supports: &mut [u32]) -> bool {
+        let pos = values.len();
+        if pos == candidates.len() {
+            return self.is_satisfied(values);
+        }
+
+        let mut feasible = false;
+        for v in mask_values(candidates[pos]) {
+            let clashes = match pos {
+                1 => values[0] == v,
+                2 => values[0] == v,
+                3 => values[1] == v || values[2] == v,
+                _ => false,
+            };
+            if clashes {
+                continue;
+            }
+            values.push(v);
+            if self.explore(candidates, values, supports) {
+                supports[pos] |= 1 << v;
+                feasible = true;
+            }
+            values.pop();
+        }
+        feasible
+    }
+}
+
+impl Constraint for Quadruple {
+    fn affected_cells(&self) -> &[usize] {
+        &self.cells
+    }
+
+    fn prune(&self, cells: &mut [Cell]) -> Result<bool, ()> {
+        let candidates: Vec<u32> = self.cells.iter().map(|&idx| cells[idx].mask()).collect();
+        let mut supports = vec![0; candidates.len()];
+        if !self.explore(&candidates, &mut Vec::new(), &mut supports) {
+            return Err(());
+        }
+        apply_supports(cells, &self.cells, &supports)
+    }
+
+    fn draw(&self, layout: &Layout) {
+        let style = &theme::current().constraints;
+        let center = layout.cell_position(self.cells[0]) + Vec2::splat(layout.cell_size);
+        let radius = style.quadruple_radius * layout.cell_size;
+        draw_circle(center.x, center.y, radius, style.quadruple_color);
+        draw_circle_lines(center.x, center.y, radius, 1., style.quadruple_outline_color);
+
+        // Up to four digits in a 2x2 arrangement inside the circle
+        let font_size = style.quadruple_font_size * layout.scale();
+        for (i, digit) in self.digits.iter().enumerate() {
+            let text = digit.to_string();
+            let dimensions = text::measure(&text, font_size);
+            let offset = vec2(
+                if i % 2 == 0 { -radius / 2. } else { radius / 2. },
+                if i < 2 { -radius / 2. } else { radius / 2. },
+            );
+            let offset = if self.digits.len() == 1 { Vec2::ZERO } else { offset };
+            text::draw(
+                &text,
+                center.x + offset.x - dimensions.width / 2.,
+                center.y + offset.y + dimensions.offset_y / 2.,
+                font_size,
+                style.quadruple_digit_color);
+        }
+    }
+
+    fn rule(&self, side: usize) -> String {
+        let digits: Vec<String> = self.digits.iter().map(u8::to_string).collect();
+        format!("quadruple {} {}", format_cells(&self.cells[..1], side), digits.join(" "))
+    }
+}
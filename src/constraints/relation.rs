@@ -0,0 +1,66 @@
+use macroquad::prelude::*;
+
+use crate::cell::Cell;
+use crate::expression::Expression;
+use crate::layout::Layout;
+use crate::notation::format_cells;
+use crate::text;
+use crate::theme;
+
+use super::{draw_cell_path, prune_relation, Constraint};
+
+
+// Each pair of consecutive cells along the line satisfies a custom expression of `a` and `b`
+#[derive(Debug)]
+pub struct Relation {
+    expression: Expression,
+    cells: Vec<usize>,
+    // Values of `b` related to each value of `a`, as masks
+    related: Vec<u32>,
+}
+
+impl Relation {
+    pub fn new(expression: Expression, side: usize, cells: Vec<usize>) -> Self {
+        let related = (0..=side as u8)
+            .map(|a| {
+                (1..=side as u8).filter(|&b| expression.holds(a, b)).fold(0, |mask, b| mask | 1 << b)
+            })
+            .collect();
+        Self { expression, cells, related }
+    }
+}
+
+impl Constraint for Relation {
+    fn affected_cells(&self) -> &[usize] {
+        &self.cells
+    }
+
+    fn prune(&self, cells: &mut [Cell]) -> Result<bool, ()> {
+        let mut changed = false;
+        for pair in self.cells.windows(2) {
+            changed |= prune_relation(cells, pair[0], pair[1], |a, b| {
+                self.related[a as usize] & 1 << b != 0
+            })?;
+        }
+        Ok(changed)
+    }
+
+    fn draw(&self, layout: &Layout) {
+        let style = &theme::current().constraints;
+        draw_cell_path(layout, &self.cells, style.relation_thickness * layout.cell_size, style.relation_color);
+
+        // Expression written along the bottom of the first cell
+        let position = layout.cell_position(self.cells[0]);
+        let font_size = style.relation_font_size * layout.scale();
+        text::draw(
+            self.expression.source(),
+            position.x + 2. * layout.scale(),
+            position.y + layout.cell_size - 3. * layout.scale(),
+            font_size,
+            style.relation_color);
+    }
+
+    fn rule(&self, side: usize) -> String {
+        format!("relation {} : {}", format_cells(&self.cells, side), self.expression.source())
+    }
+}
@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+
+use crate::cell::Cell;
+use crate::layout::{Edge, Layout, Margins};
+
+use super::{apply_supports, draw_outside_clue, mask_values, outside_clue_rule, Aggregate, Constraint};
+
+
+// Digits between the lowest and the highest digit of the line sum to `clue`
+#[derive(Debug)]
+pub struct Sandwich {
+    edge: Edge,
+    line: usize,
+    clue: u32,
+    side: u32,
+    cells: Vec<usize>,
+}
+
+impl Sandwich {
+    pub fn new(side: usize, edge: Edge, line: usize, clue: u32) -> Self {
+        Self { edge, line, clue, side: side as u32, cells: edge.line_cells(side, line) }
+    }
+
+    fn crusts(&self, used: u32) -> u32 {
+        (used >> 1 & 1) + (used >> self.side & 1)
+    }
+
+    // Whether the line can be completed from the `used` digits with `sum` between crusts so far,
+    // recording in `supports` the digits of each position that lead to a completion
+    fn explore(
+        &self,
+        candidates: &[u32],
+        used: u32,
+        sum: u32,
+        memo: &mut HashMap<(u32, u32), bool>,
+        supports: &mut [u32],
+    ) -> bool {
+        let pos = used.count_ones() as usize;
+        if pos == candidates.len() {
+            return sum == self.clue;
+        }
+        if let Some(&feasible) = memo.get(&(used, sum)) {
+            return feasible;
+        }
+
+        let mut feasible = false;
+        for v in mask_values(candidates[pos] & !used) {
+            let inside = self.crusts(used) == 1 && v != 1 && v != self.side;
+            let sum = if inside { sum + v } else { sum };
+            if sum <= self.clue && self.explore(candidates, used | 1 << v, sum, memo, supports) {
+                supports[pos] |= 1 << v;
+                feasible = true;
+            }
+        }
+        memo.insert((used, sum), feasible);
+        feasible
+    }
+}
+
+impl Constraint for Sandwich {
+    fn affected_cells(&self) -> &[usize] {
+        &self.cells
+    }
+
+    fn prune(&self, cells: &mut [Cell]) -> Result<bool, ()> {
+        let candidates: Vec<u32> = self.cells.iter().map(|&idx| cells[idx].mask()).collect();
+        let mut supports = vec![0; candidates.len()];
+        if !self.explore(&candidates, 0, 0, &mut HashMap::new(), &mut supports) {
+            return Err(());
+        }
+        apply_supports(cells, &self.cells, &supports)
+    }
+
+    // The search only runs again once the line changed since it last removed nothing
+    fn prune_with(&self, cells: &mut [Cell], aggregate: &mut Aggregate) -> Result<bool, ()> {
+        if !aggregate.update(cells, &self.cells) && aggregate.settled {
+            return Ok(false);
+        }
+        let changed = self.prune(cells)?;
+        aggregate.settled = !changed;
+        Ok(changed)
+    }
+
+    fn margins(&self) -> Margins {
+        Margins::edge(self.edge, 1.)
+    }
+
+    fn draw(&self, layout: &Layout) {
+        draw_outside_clue(layout, self.edge, self.line, &self.clue.to_string());
+    }
+
+    fn rule(&self, side: usize) -> String {
+        outside_clue_rule("sandwich", side, self.edge, self.line, self.clue)
+    }
+}
@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+
+use crate::cell::Cell;
+use crate::layout::{Edge, Layout, Margins};
+
+use super::{apply_supports, draw_outside_clue, mask_values, outside_clue_rule, Constraint};
+
+
+// Exactly `clue` digits of the line are visible from the edge, a digit hiding all lower ones behind it
+#[derive(Debug)]
+pub struct Skyscraper {
+    edge: Edge,
+    line: usize,
+    clue: u32,
+    cells: Vec<usize>,
+}
+
+impl Skyscraper {
+    pub fn new(side: usize, edge: Edge, line: usize, clue: u32) -> Self {
+        Self { edge, line, clue, cells: edge.line_cells(side, line) }
+    }
+
+    // Whether the line can be completed from the `used` digits with `visible` seen so far,
+    // recording in `supports` the digits of each position that lead to a completion
+    fn explore(
+        &self,
+        candidates: &[u32],
+        used: u32,
+        visible: u32,
+        memo: &mut HashMap<(u32, u32), bool>,
+        supports: &mut [u32],
+    ) -> bool {
+        let pos = used.count_ones() as usize;
+        if pos == candidates.len() {
+            return visible == self.clue;
+        }
+        if let Some(&feasible) = memo.get(&(used, visible)) {
+            return feasible;
+        }
+
+        let highest = 31u32.saturating_sub(used.leading_zeros());
+        let mut feasible = false;
+        for v in mask_values(candidates[pos] & !used) {
+            let visible = visible + (v > highest) as u32;
+            if visible <= self.clue && self.explore(candidates, used | 1 << v, visible, memo, supports) {
+                supports[pos] |= 1 << v;
+                feasible = true;
+            }
+        }
+        memo.insert((used, visible), feasible);
+        feasible
+    }
+}
+
+impl Constraint for Skyscraper {
+    fn affected_cells(&self) -> &[usize] {
+        &self.cells
+    }
+
+    fn prune(&self, cells: &mut [Cell]) -> Result<bool, ()> {
+        let candidates: Vec<u32> = self.cells.iter().map(|&idx| cells[idx].mask()).collect();
+        let mut supports = vec![0; candidates.len()];
+        if !self.explore(&candidates, 0, 0, &mut HashMap::new(), &mut supports) {
+            return Err(());
+        }
+        apply_supports(cells, &self.cells, &supports)
+    }
+
+    fn margins(&self) -> Margins {
+        Margins::edge(self.edge, 1.)
+    }
+
+    fn draw(&self, layout: &Layout) {
+        draw_outside_clue(layout, self.edge, self.line, &self.clue.to_string());
+    }
+
+    fn rule(&self, side: usize) -> String {
+        outside_clue_rule("skyscraper", side, self.edge, self.line, self.clue)
+    }
+}
@@ -0,0 +1,66 @@
+use macroquad::prelude::*;
+
+use crate::cell::Cell;
+use crate::layout::Layout;
+use crate::notation::format_cells;
+use crate::theme;
+
+use super::{draw_cell_path, Constraint};
+
+
+// Digits strictly increase from the bulb, the first cell
+#[derive(Debug)]
+pub struct Thermometer {
+    cells: Vec<usize>,
+}
+
+impl Thermometer {
+    pub fn new(cells: Vec<usize>) -> Self {
+        Self { cells }
+    }
+}
+
+impl Constraint for Thermometer {
+    fn affected_cells(&self) -> &[usize] {
+        &self.cells
+    }
+
+    fn prune(&self, cells: &mut [Cell]) -> Result<bool, ()> {
+        // Each cell is above the lowest digit before it and below the highest after it
+        let mut low = 0;
+        let lows: Vec<u8> = self.cells
+            .iter()
+            .map(|&idx| {
+                low = cells[idx].possible_values[0].max(low + 1);
+                low
+            })
+            .collect();
+        let mut high = u8::MAX;
+        let mut highs: Vec<u8> = self.cells
+            .iter()
+            .rev()
+            .map(|&idx| {
+                high = (*cells[idx].possible_values.last().unwrap()).min(high.saturating_sub(1));
+                high
+            })
+            .collect();
+        highs.reverse();
+
+        let mut changed = false;
+        for ((&idx, low), high) in self.cells.iter().zip(lows).zip(highs) {
+            changed |= cells[idx].retain_possibilities(|v| (low..=high).contains(&v))?;
+        }
+        Ok(changed)
+    }
+
+    fn draw(&self, layout: &Layout) {
+        let style = &theme::current().constraints;
+        draw_cell_path(layout, &self.cells, style.thermometer_thickness * layout.cell_size, style.thermometer_color);
+        let bulb = layout.cell_position(self.cells[0]) + Vec2::splat(layout.cell_size / 2.);
+        draw_circle(bulb.x, bulb.y, style.bulb_radius * layout.cell_size, style.thermometer_color);
+    }
+
+    fn rule(&self, side: usize) -> String {
+        format!("thermo {}", format_cells(&self.cells, side))
+    }
+}
@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+
+use crate::cell::Cell;
+use crate::layout::{Edge, Layout, Margins};
+
+use super::{apply_supports, draw_outside_clue, mask_values, outside_clue_rule, Constraint};
+
+
+// The first X digits from the edge sum to `clue`, X being the first digit
+#[derive(Debug)]
+pub struct XSum {
+    edge: Edge,
+    line: usize,
+    clue: u32,
+    cells: Vec<usize>,
+}
+
+impl XSum {
+    pub fn new(side: usize, edge: Edge, line: usize, clue: u32) -> Self {
+        Self { edge, line, clue, cells: edge.line_cells(side, line) }
+    }
+
+    // Whether the first `length` digits can be completed from `used` to sum to the clue,
+    // recording in `supports` the digits of each position that lead to a completion
+    fn explore(
+        &self,
+        candidates: &[u32],
+        length: usize,
+        used: u32,
+        sum: u32,
+        memo: &mut HashMap<u32, bool>,
+        supports: &mut [u32],
+    ) -> bool {
+        let pos = used.count_ones() as usize;
+        if pos == length {
+            return sum == self.clue;
+        }
+        if let Some(&feasible) = memo.get(&used) {
+            return feasible;
+        }
+
+        let mut feasible = false;
+        for v in mask_values(candidates[pos] & !used) {
+            if sum + v <= self.clue && self.explore(candidates, length, used | 1 << v, sum + v, memo, supports) {
+                supports[pos] |= 1 << v;
+                feasible = true;
+            }
+        }
+        memo.insert(used, feasible);
+        feasible
+    }
+}
+
+impl Constraint for XSum {
+    fn affected_cells(&self) -> &[usize] {
+        &self.cells
+    }
+
+    fn prune(&self, cells: &mut [Cell]) -> Result<bool, ()> {
+        let candidates: Vec<u32> = self.cells.iter().map(|&idx| cells[idx].mask()).collect();
+        let mut supports = vec![0; candidates.len()];
+
+        for x in mask_values(candidates[0]) {
+            let length = x as usize;
+            if length > candidates.len() {
+                continue;
+            }
+            if self.explore(&candidates, length, 1 << x, x, &mut HashMap::new(), &mut supports) {
+                supports[0] |= 1 << x;
+                // Digits past the first X are free as far as this clue is concerned
+                for pos in length..candidates.len() {
+                    supports[pos] |= candidates[pos];
+                }
+            }
+        }
+        if supports[0] == 0 {
+            return Err(());
+        }
+        apply_supports(cells, &self.cells, &supports)
+    }
+
+    fn margins(&self) -> Margins {
+        Margins::edge(self.edge, 1.)
+    }
+
+    fn draw(&self, layout: &Layout) {
+        draw_outside_clue(layout, self.edge, self.line, &self.clue.to_string());
+    }
+
+    fn rule(&self, side: usize) -> String {
+        outside_clue_rule("xsum", side, self.edge, self.line, self.clue)
+    }
+}
@@ -0,0 +1,14 @@
+// Why propagating a placement failed: where a constraint found no way to place its
+// digits, and the eliminations that led there
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Contradiction {
+    // Cell the constraint left without candidates, none when it fails as a whole,
+    // like a cage no digits add up to
+    pub cell: Option<usize>,
+    // Candidates the cell had left before the constraint removed them
+    pub removed: Vec<u8>,
+    // Variant file rule of the constraint, none for the houses and flag variants
+    pub rule: Option<String>,
+    // Cells that lost candidates on the way, in order, from the one collapsed if any
+    pub path: Vec<usize>,
+}
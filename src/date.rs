@@ -0,0 +1,56 @@
+use std::fmt;
+
+
+// Calendar day in UTC
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Date {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    // Civil date from days since 1970-01-01, counting years from March so leap days come last
+    pub fn from_days(days: i64) -> Self {
+        let shifted = days + 719_468;
+        let era = shifted.div_euclid(146_097);
+        let day_of_era = shifted.rem_euclid(146_097);
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_from_march = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+        let month = if month_from_march < 10 { month_from_march + 3 } else { month_from_march - 9 };
+        let year = year_of_era + era * 400 + (month <= 2) as i64;
+        Self { year, month: month as u32, day: day as u32 }
+    }
+
+    pub fn today() -> Self {
+        Self::from_days(now().div_euclid(86400))
+    }
+
+    // Same for everyone on the same day, and readable: 20240131
+    pub fn seed(&self) -> u64 {
+        (self.year * 10000) as u64 + (self.month * 100 + self.day) as u64
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+// Seconds since 1970-01-01 UTC, from the browser's clock on the web
+pub fn now() -> i64 {
+    macroquad::miniquad::date::now() as i64
+}
+
+// UTC date and time as 20240131-235959, for file names sorting in order
+pub fn timestamp() -> String {
+    let seconds = now();
+    let (date, time) = (Date::from_days(seconds.div_euclid(86400)), seconds.rem_euclid(86400));
+    format!(
+        "{:04}{:02}{:02}-{:02}{:02}{:02}",
+        date.year, date.month, date.day, time / 3600, time / 60 % 60, time % 60
+    )
+}
@@ -0,0 +1,359 @@
+use macroquad::prelude::*;
+
+use sudoku::grid::{BoardSize, Grid, Variants};
+use sudoku::layout::Layout;
+use sudoku::notation::{format_cells, Coord};
+use sudoku::text;
+use sudoku::theme;
+use sudoku::variant;
+
+use crate::input;
+use crate::keys::{self, Action};
+use crate::language::{tr, Text};
+
+
+const TOOLS: [(Action, Tool); 7] = [
+    (Action::ToolRegions, Tool::Regions),
+    (Action::ToolThermo, Tool::Thermo),
+    (Action::ToolArrow, Tool::Arrow),
+    (Action::ToolCage, Tool::Cage),
+    (Action::ToolPalindrome, Tool::Palindrome),
+    (Action::ToolRelation, Tool::Relation),
+    (Action::ToolRule, Tool::Rule),
+];
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tool {
+    Regions,
+    Thermo,
+    Arrow,
+    Cage,
+    Palindrome,
+    Relation,
+    // Any constraint rule of a variant file, typed in full
+    Rule,
+}
+
+impl Tool {
+    fn name(self) -> Text {
+        match self {
+            Tool::Regions => Text::ToolRegions,
+            Tool::Thermo => Text::ToolThermo,
+            Tool::Arrow => Text::ToolArrow,
+            Tool::Cage => Text::ToolCage,
+            Tool::Palindrome => Text::ToolPalindrome,
+            Tool::Relation => Text::ToolRelation,
+            Tool::Rule => Text::ToolRule,
+        }
+    }
+
+    // Text asked for once the cells are drawn, if any
+    fn prompt(self) -> Option<Text> {
+        match self {
+            Tool::Cage => Some(Text::CageSum),
+            Tool::Relation => Some(Text::RelationPrompt),
+            Tool::Rule => Some(Text::RulePrompt),
+            _ => None,
+        }
+    }
+
+    // Cages may be a single cell, lines need at least two, typed rules list their own
+    fn min_cells(self) -> usize {
+        match self {
+            Tool::Cage => 1,
+            Tool::Rule => 0,
+            _ => 2,
+        }
+    }
+
+    // Variant file rule for the cells drawn and the text typed, see `variant`
+    fn rule(self, cells: &[usize], text: &str, side: usize) -> String {
+        let cells = format_cells(cells, side);
+        match self {
+            Tool::Regions => String::new(),
+            Tool::Thermo => format!("thermo {}", cells),
+            Tool::Arrow => format!("arrow {}", cells),
+            Tool::Cage => format!("cage {} {}", text.trim(), cells),
+            Tool::Palindrome => format!("palindrome {}", cells),
+            Tool::Relation => format!("relation {} : {}", cells, text),
+            Tool::Rule => text.to_string(),
+        }
+    }
+}
+
+// Paints jigsaw regions and draws line and cage constraints with the mouse:
+// dragging from a cell grows its region over the cells crossed, the other tools
+// follow the cells crossed, going back over the previous cell undoes the last one.
+// Constraints are built from their variant file rule, so the editor only makes
+// those a file can hold. The rule tool takes any of them typed in full, clicking
+// cells to write them
+pub struct Editor {
+    size: BoardSize,
+    variants: Variants,
+    save_path: String,
+    // Board drawn behind the editor, rebuilt whenever the variants change
+    grid: Grid,
+    tool: Tool,
+    // Region painted by the current drag
+    brush: Option<usize>,
+    path: Vec<usize>,
+    // Cells drawn with a tool asking for text, waiting for it to be typed
+    pending: Option<Vec<usize>>,
+    input: String,
+    message: String,
+}
+
+impl Editor {
+    pub fn new(size: BoardSize, variants: Variants, save_path: String) -> Self {
+        Self {
+            size,
+            grid: Grid::new(size, variants.clone()),
+            variants,
+            save_path,
+            tool: Tool::Regions,
+            brush: None,
+            path: Vec::new(),
+            pending: None,
+            input: String::new(),
+            message: String::new(),
+        }
+    }
+
+    // Carry on from rules changed outside the editor
+    pub fn set_variants(&mut self, variants: Variants) {
+        self.variants = variants;
+        self.changed();
+    }
+
+    // Edit until the variants are handed to the solver
+    pub async fn run(&mut self) -> Variants {
+        loop {
+            clear_background(theme::current().board.background_color);
+            let layout = Layout::new(self.size, self.grid.margins());
+            // No solve to keep while editing
+            if is_quit_requested() {
+                std::process::exit(0);
+            }
+
+            // Alt+Enter isn't also taken as Enter
+            if input::toggle_fullscreen() {
+                while get_char_pressed().is_some() {}
+            } else if self.pending.is_some() {
+                self.type_text(&layout);
+            } else {
+                while get_char_pressed().is_some() {}
+                if keys::pressed(Action::EditorSolve) {
+                    match self.check() {
+                        Ok(()) => return self.variants.clone(),
+                        Err(err) => self.message = err,
+                    }
+                }
+                self.handle_keys();
+                self.handle_mouse(&layout);
+            }
+
+            self.draw(&layout);
+            next_frame().await;
+        }
+    }
+
+    fn changed(&mut self) {
+        self.grid = Grid::new(self.size, self.variants.clone());
+    }
+
+    fn check(&self) -> Result<(), String> {
+        match &self.variants.regions {
+            Some(regions) => variant::check_regions(regions, self.size.side()),
+            None => Ok(()),
+        }
+    }
+
+    fn region_of(&self, idx: usize) -> usize {
+        match &self.variants.regions {
+            Some(regions) => regions[idx],
+            None => Coord::from_index(idx, self.size.side()).box_index(self.size),
+        }
+    }
+
+    fn handle_keys(&mut self) {
+        for (action, tool) in TOOLS {
+            if keys::pressed(action) {
+                self.tool = tool;
+                if tool == Tool::Rule {
+                    self.message.clear();
+                    self.finish_path(Vec::new());
+                }
+            }
+        }
+        if keys::pressed(Action::EditorUndo) && self.variants.constraints.pop().is_some() {
+            self.changed();
+        }
+        if keys::pressed(Action::ClearRegions) && self.variants.regions.take().is_some() {
+            self.changed();
+        }
+        if keys::pressed(Action::EditorSave) {
+            self.message = match self.check().and_then(|()| {
+                variant::save(&self.save_path, &self.variants, self.size)
+            }) {
+                Ok(()) => tr(Text::SavedTo, &[&self.save_path]),
+                Err(err) => err,
+            };
+        }
+    }
+
+    fn handle_mouse(&mut self, layout: &Layout) {
+        let cell = layout.cell_at(mouse_position().into());
+
+        if is_mouse_button_pressed(MouseButton::Left) {
+            if let Some(idx) = cell {
+                self.message.clear();
+                match self.tool {
+                    Tool::Regions => self.brush = Some(self.region_of(idx)),
+                    Tool::Rule => self.finish_path(Vec::new()),
+                    _ => self.path = vec![idx],
+                }
+            }
+        } else if is_mouse_button_down(MouseButton::Left) {
+            if let Some(idx) = cell {
+                self.drag_over(idx);
+            }
+        } else {
+            self.brush = None;
+            if !self.path.is_empty() {
+                let path = std::mem::take(&mut self.path);
+                self.finish_path(path);
+            }
+        }
+    }
+
+    fn drag_over(&mut self, idx: usize) {
+        if let Some(brush) = self.brush {
+            if self.region_of(idx) != brush {
+                let side = self.size.side();
+                let size = self.size;
+                let regions = self.variants.regions.get_or_insert_with(|| {
+                    (0..size.cell_count()).map(|idx| Coord::from_index(idx, side).box_index(size)).collect()
+                });
+                regions[idx] = brush;
+                self.changed();
+            }
+            return;
+        }
+
+        let Some(&last) = self.path.last() else { return };
+        let (a, b) = (Coord::from_index(last, self.size.side()), Coord::from_index(idx, self.size.side()));
+        if self.path.len() > 1 && self.path[self.path.len() - 2] == idx {
+            self.path.pop();
+        } else if a.row.abs_diff(b.row) <= 1 && a.col.abs_diff(b.col) <= 1 && !self.path.contains(&idx) {
+            self.path.push(idx);
+        }
+    }
+
+    fn finish_path(&mut self, path: Vec<usize>) {
+        if path.len() < self.tool.min_cells() {
+            return;
+        }
+        if self.tool.prompt().is_some() {
+            self.pending = Some(path);
+            self.input.clear();
+            return;
+        }
+        if let Err(err) = self.add_rule(&path) {
+            self.message = err;
+        }
+    }
+
+    fn type_text(&mut self, layout: &Layout) {
+        while let Some(c) = get_char_pressed() {
+            if !c.is_control() {
+                self.input.push(c);
+            }
+        }
+        if self.tool == Tool::Rule && is_mouse_button_pressed(MouseButton::Left) {
+            if let Some(idx) = layout.cell_at(mouse_position().into()) {
+                let separator = if self.input.ends_with(' ') || self.input.is_empty() { "" } else { " " };
+                self.input = format!("{}{}{} ", self.input, separator, format_cells(&[idx], self.size.side()));
+            }
+        }
+        if is_key_pressed(KeyCode::Backspace) {
+            self.input.pop();
+        }
+        if keys::pressed(Action::Back) {
+            self.pending = None;
+            self.message.clear();
+        }
+        if is_key_pressed(KeyCode::Enter) {
+            let cells = self.pending.clone().unwrap_or_default();
+            match self.add_rule(&cells) {
+                Ok(()) => {
+                    self.pending = None;
+                    self.message.clear();
+                }
+                Err(err) => self.message = err,
+            }
+        }
+    }
+
+    // Add the constraints of the tool's rule, checked as when loading a variant file
+    fn add_rule(&mut self, cells: &[usize]) -> Result<(), String> {
+        let rule = self.tool.rule(cells, &self.input, self.size.side());
+        self.variants.constraints.extend(variant::parse_constraints(&rule, self.size)?);
+        self.changed();
+        Ok(())
+    }
+
+    fn draw(&self, layout: &Layout) {
+        let theme = theme::current();
+        if self.tool == Tool::Regions {
+            let side = self.size.side();
+            for idx in 0..self.size.cell_count() {
+                let hue = self.region_of(idx) as f32 / side as f32;
+                let mut color = macroquad::color::hsl_to_rgb(hue, 0.6, 0.5);
+                color.a = theme.editor.region_alpha;
+                let p = layout.cell_position(idx);
+                draw_rectangle(p.x, p.y, layout.cell_size, layout.cell_size, color);
+            }
+        }
+        self.grid.draw_board(layout);
+
+        for &idx in self.pending.iter().flatten().chain(&self.path) {
+            let p = layout.cell_position(idx);
+            draw_rectangle(p.x, p.y, layout.cell_size, layout.cell_size, theme.editor.path_color);
+        }
+
+        let tools: Vec<String> = TOOLS
+            .iter()
+            .map(|&(action, tool)| {
+                let marker = if tool == self.tool { "*" } else { "" };
+                format!("[{}] {}{}", keys::label(action), tr(tool.name(), &[]), marker)
+            })
+            .collect();
+        let lines = [
+            tools.join("  "),
+            tr(Text::EditorHelp, &[
+                &keys::label(Action::EditorUndo),
+                &keys::label(Action::ClearRegions),
+                &keys::label(Action::EditorSave),
+                &keys::label(Action::EditorSolve),
+                &input::fullscreen_help(),
+            ]),
+        ];
+        for (i, line) in lines.iter().enumerate() {
+            text::draw(line, 0., theme.text.font_size * (i + 1) as f32, theme.text.font_size, theme.text.color);
+        }
+
+        let mut status = Vec::new();
+        if let Some(prompt) = self.tool.prompt().filter(|_| self.pending.is_some()) {
+            let prompt = tr(Text::EditorPrompt, &[&tr(prompt, &[]), &self.input, &keys::label(Action::Back)]);
+            status.push((prompt, theme.text.color));
+        }
+        if !self.message.is_empty() {
+            status.push((self.message.clone(), theme.text.message_color));
+        }
+        for (i, (line, color)) in status.iter().rev().enumerate() {
+            let y = screen_height() - theme.text.font_size * i as f32 - theme.text.font_size / 2.;
+            text::draw(line, 0., y, theme.text.font_size, *color);
+        }
+    }
+}
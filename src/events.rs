@@ -0,0 +1,80 @@
+use std::sync::mpsc::{self, Receiver, Sender};
+
+
+// What happens in a solver step, reported to the observers of the grid, see
+// `Grid::subscribe`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    // The cell picked by the step and the digit it got
+    CellCollapsed { idx: usize, value: u8 },
+    // Candidate removed from another cell by the constraints
+    CandidateEliminated { idx: usize, value: u8 },
+    // Propagating the collapse of the cell broke a constraint, the solve is stuck
+    Contradiction { idx: usize },
+    // After a contradiction, backjumping ruled out the digit of the cell collapsed by a
+    // decision it went back past, see `Grid::backjumping`
+    Backjump { idx: usize, value: u8 },
+    // Every cell has a single candidate left
+    Solved,
+}
+
+pub type Observer = Box<dyn FnMut(&Event)>;
+
+// Observer of a grid, to stop telling it, see `Grid::unsubscribe`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ObserverId(pub(crate) usize);
+
+// Observer sending every event down a channel, and the end to receive them from
+pub fn channel() -> (Observer, Receiver<Event>) {
+    let (sender, receiver) = mpsc::channel();
+    (forward(sender), receiver)
+}
+
+// Observer sending every event to a channel shared with other grids
+pub fn forward(sender: Sender<Event>) -> Observer {
+    Box::new(move |event: &Event| {
+        // Nobody listening anymore
+        let _ = sender.send(*event);
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::{BoardSize, Grid, Variants};
+    use crate::notation::format_cells;
+
+    // Every step reports its collapse and the candidates it cost, the last attempt ending
+    // on the solve
+    #[test]
+    fn steps_are_reported() {
+        let mut grid = Grid::new(BoardSize::default(), Variants::default());
+        grid.seed(0x5eed);
+        let (observer, receiver) = channel();
+        grid.subscribe(observer);
+        while !grid.is_resolve() {
+            if grid.step().is_err() {
+                grid.reset();
+            }
+        }
+        let solution: Vec<u8> = grid.cells.iter().map(|cell| cell.possible_values[0]).collect();
+
+        let events: Vec<Event> = receiver.try_iter().collect();
+        assert_eq!(events.last(), Some(&Event::Solved));
+        // The grid starts over after each contradiction
+        let restart = events.iter().rposition(|event| matches!(event, Event::Contradiction { .. }));
+        let last_attempt = &events[restart.map_or(0, |i| i + 1)..];
+        let mut eliminated = 0;
+        for event in last_attempt {
+            match *event {
+                Event::CellCollapsed { idx, value } => {
+                    assert_eq!(value, solution[idx], "{} reported collapsed to {}", format_cells(&[idx], 9), value);
+                }
+                Event::CandidateEliminated { .. } => eliminated += 1,
+                _ => {}
+            }
+        }
+        assert_eq!(eliminated, grid.eliminations);
+    }
+}
@@ -0,0 +1,400 @@
+// Tiny expression language relating two digits `a` and `b`, e.g. `a + b == 10` or
+// `abs(a - b) >= 4`. Only integer arithmetic, comparisons, `&&`, `||`, `!` and the
+// `abs`, `min` and `max` functions are available, with bounded size and nesting.
+// Comparisons evaluate to 1 or 0, and a nonzero result means the digits are related.
+
+const MAX_LENGTH: usize = 256;
+const MAX_DEPTH: usize = 32;
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token {
+    Number(i64),
+    A,
+    B,
+    Function(Function),
+    Operator(Operator),
+    Not,
+    Open,
+    Close,
+    Comma,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Function {
+    Abs,
+    Min,
+    Max,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operator {
+    Or,
+    And,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+impl Operator {
+    fn precedence(self) -> u8 {
+        match self {
+            Operator::Or => 1,
+            Operator::And => 2,
+            Operator::Equal | Operator::NotEqual => 3,
+            Operator::Less | Operator::LessEqual | Operator::Greater | Operator::GreaterEqual => 4,
+            Operator::Add | Operator::Sub => 5,
+            Operator::Mul | Operator::Div | Operator::Rem => 6,
+        }
+    }
+
+    fn symbol(self) -> &'static str {
+        match self {
+            Operator::Or => "||",
+            Operator::And => "&&",
+            Operator::Equal => "==",
+            Operator::NotEqual => "!=",
+            Operator::Less => "<",
+            Operator::LessEqual => "<=",
+            Operator::Greater => ">",
+            Operator::GreaterEqual => ">=",
+            Operator::Add => "+",
+            Operator::Sub => "-",
+            Operator::Mul => "*",
+            Operator::Div => "/",
+            Operator::Rem => "%",
+        }
+    }
+
+    fn apply(self, x: i64, y: i64) -> Option<i64> {
+        match self {
+            Operator::Or => Some((x != 0 || y != 0) as i64),
+            Operator::And => Some((x != 0 && y != 0) as i64),
+            Operator::Equal => Some((x == y) as i64),
+            Operator::NotEqual => Some((x != y) as i64),
+            Operator::Less => Some((x < y) as i64),
+            Operator::LessEqual => Some((x <= y) as i64),
+            Operator::Greater => Some((x > y) as i64),
+            Operator::GreaterEqual => Some((x >= y) as i64),
+            Operator::Add => x.checked_add(y),
+            Operator::Sub => x.checked_sub(y),
+            Operator::Mul => x.checked_mul(y),
+            Operator::Div => x.checked_div(y),
+            Operator::Rem => x.checked_rem(y),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Number(i64),
+    A,
+    B,
+    Negate(Box<Node>),
+    Not(Box<Node>),
+    Binary(Operator, Box<Node>, Box<Node>),
+    Call(Function, Vec<Node>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expression {
+    source: String,
+    root: Node,
+}
+
+impl Expression {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        if source.len() > MAX_LENGTH {
+            return Err(format!("expression longer than {} characters", MAX_LENGTH));
+        }
+        let tokens = tokenize(source)?;
+        let mut parser = Parser { tokens: &tokens, pos: 0, depth: 0 };
+        let root = parser.expression(0)?;
+        if parser.pos != tokens.len() {
+            return Err(format!("unexpected '{}'", token_name(tokens[parser.pos])));
+        }
+        Ok(Self { source: source.trim().to_string(), root })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    // Whether the expression holds for these digits, errors such as a division by zero don't
+    pub fn holds(&self, a: u8, b: u8) -> bool {
+        evaluate(&self.root, a as i64, b as i64).is_some_and(|v| v != 0)
+    }
+}
+
+fn evaluate(node: &Node, a: i64, b: i64) -> Option<i64> {
+    match node {
+        Node::Number(n) => Some(*n),
+        Node::A => Some(a),
+        Node::B => Some(b),
+        Node::Negate(node) => evaluate(node, a, b)?.checked_neg(),
+        Node::Not(node) => Some((evaluate(node, a, b)? == 0) as i64),
+        Node::Binary(op, x, y) => op.apply(evaluate(x, a, b)?, evaluate(y, a, b)?),
+        Node::Call(function, args) => {
+            let values = args.iter().map(|arg| evaluate(arg, a, b)).collect::<Option<Vec<_>>>()?;
+            match function {
+                Function::Abs => values[0].checked_abs(),
+                Function::Min => values.into_iter().min(),
+                Function::Max => values.into_iter().max(),
+            }
+        }
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        let (token, width) = match (c, next) {
+            (c, _) if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            (c, _) if c.is_ascii_digit() => {
+                let digits: String = chars[i..].iter().take_while(|c| c.is_ascii_digit()).collect();
+                let n = digits.parse().map_err(|_| format!("number '{}' too large", digits))?;
+                (Token::Number(n), digits.len())
+            }
+            (c, _) if c.is_ascii_alphabetic() => {
+                let word: String =
+                    chars[i..].iter().take_while(|c| c.is_ascii_alphabetic()).collect();
+                let token = match word.as_str() {
+                    "a" => Token::A,
+                    "b" => Token::B,
+                    "abs" => Token::Function(Function::Abs),
+                    "min" => Token::Function(Function::Min),
+                    "max" => Token::Function(Function::Max),
+                    _ => return Err(format!("unknown name '{}'", word)),
+                };
+                (token, word.len())
+            }
+            ('|', Some('|')) => (Token::Operator(Operator::Or), 2),
+            ('&', Some('&')) => (Token::Operator(Operator::And), 2),
+            ('=', Some('=')) => (Token::Operator(Operator::Equal), 2),
+            ('!', Some('=')) => (Token::Operator(Operator::NotEqual), 2),
+            ('<', Some('=')) => (Token::Operator(Operator::LessEqual), 2),
+            ('>', Some('=')) => (Token::Operator(Operator::GreaterEqual), 2),
+            ('<', _) => (Token::Operator(Operator::Less), 1),
+            ('>', _) => (Token::Operator(Operator::Greater), 1),
+            ('+', _) => (Token::Operator(Operator::Add), 1),
+            ('-', _) => (Token::Operator(Operator::Sub), 1),
+            ('*', _) => (Token::Operator(Operator::Mul), 1),
+            ('/', _) => (Token::Operator(Operator::Div), 1),
+            ('%', _) => (Token::Operator(Operator::Rem), 1),
+            ('!', _) => (Token::Not, 1),
+            ('(', _) => (Token::Open, 1),
+            (')', _) => (Token::Close, 1),
+            (',', _) => (Token::Comma, 1),
+            _ => return Err(format!("unexpected character '{}'", c)),
+        };
+        tokens.push(token);
+        i += width;
+    }
+    Ok(tokens)
+}
+
+fn token_name(token: Token) -> String {
+    match token {
+        Token::Number(n) => n.to_string(),
+        Token::A => "a".to_string(),
+        Token::B => "b".to_string(),
+        Token::Function(function) => format!("{:?}", function).to_lowercase(),
+        Token::Operator(op) => op.symbol().to_string(),
+        Token::Not => "!".to_string(),
+        Token::Open => "(".to_string(),
+        Token::Close => ")".to_string(),
+        Token::Comma => ",".to_string(),
+    }
+}
+
+// Precedence climbing parser, nesting is bounded so hostile input can't overflow the stack
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).copied();
+        self.pos += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => {
+                Err(format!("expected '{}', got '{}'", token_name(expected), token_name(token)))
+            }
+            None => Err(format!("expected '{}' at the end", token_name(expected))),
+        }
+    }
+
+    fn expression(&mut self, min_precedence: u8) -> Result<Node, String> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err("expression nested too deeply".to_string());
+        }
+        let mut left = self.unary()?;
+        while let Some(&Token::Operator(op)) = self.tokens.get(self.pos) {
+            if op.precedence() <= min_precedence {
+                break;
+            }
+            self.pos += 1;
+            let right = self.expression(op.precedence())?;
+            left = Node::Binary(op, Box::new(left), Box::new(right));
+        }
+        self.depth -= 1;
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Node, String> {
+        match self.next() {
+            Some(Token::Number(n)) => Ok(Node::Number(n)),
+            Some(Token::A) => Ok(Node::A),
+            Some(Token::B) => Ok(Node::B),
+            Some(Token::Operator(Operator::Sub)) => Ok(Node::Negate(Box::new(self.operand()?))),
+            Some(Token::Not) => Ok(Node::Not(Box::new(self.operand()?))),
+            Some(Token::Open) => {
+                let node = self.expression(0)?;
+                self.expect(Token::Close)?;
+                Ok(node)
+            }
+            Some(Token::Function(function)) => {
+                self.expect(Token::Open)?;
+                let mut args = vec![self.expression(0)?];
+                while self.tokens.get(self.pos) == Some(&Token::Comma) {
+                    self.pos += 1;
+                    args.push(self.expression(0)?);
+                }
+                self.expect(Token::Close)?;
+                let arity_ok = match function {
+                    Function::Abs => args.len() == 1,
+                    Function::Min | Function::Max => args.len() >= 2,
+                };
+                if !arity_ok {
+                    let name = token_name(Token::Function(function));
+                    return Err(format!("wrong number of arguments to {}", name));
+                }
+                Ok(Node::Call(function, args))
+            }
+            Some(token) => Err(format!("unexpected '{}'", token_name(token))),
+            None => Err("unexpected end of expression".to_string()),
+        }
+    }
+
+    // Operand of a prefix operator, itself possibly prefixed
+    fn operand(&mut self) -> Result<Node, String> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err("expression nested too deeply".to_string());
+        }
+        let node = self.unary();
+        self.depth -= 1;
+        node
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(source: &str, a: i64, b: i64) -> Option<i64> {
+        evaluate(&Expression::parse(source).unwrap().root, a, b)
+    }
+
+    #[test]
+    fn operators_evaluate() {
+        let cases = [
+            ("a || b", 0, 3, 1),
+            ("a || b", 0, 0, 0),
+            ("a && b", 2, 3, 1),
+            ("a && b", 2, 0, 0),
+            ("a == b", 4, 4, 1),
+            ("a != b", 4, 4, 0),
+            ("a < b", 3, 4, 1),
+            ("a <= b", 4, 4, 1),
+            ("a > b", 4, 4, 0),
+            ("a >= b", 4, 5, 0),
+            ("a + b", 4, 5, 9),
+            ("a - b", 4, 5, -1),
+            ("a * b", 4, 5, 20),
+            ("a / b", 9, 2, 4),
+            ("a % b", 9, 2, 1),
+            ("-a", 4, 0, -4),
+            ("!a", 0, 0, 1),
+            ("!a", 7, 0, 0),
+            ("abs(a - b)", 2, 9, 7),
+            ("min(a, b, 3)", 5, 4, 3),
+            ("max(a, b)", 5, 4, 5),
+        ];
+        for (source, a, b, expected) in cases {
+            assert_eq!(value(source, a, b), Some(expected), "{} with a={} b={}", source, a, b);
+        }
+    }
+
+    #[test]
+    fn operators_bind_by_precedence() {
+        assert_eq!(value("1 + 2 * 3", 0, 0), Some(7));
+        assert_eq!(value("(1 + 2) * 3", 0, 0), Some(9));
+        assert_eq!(value("10 - 3 - 2", 0, 0), Some(5));
+        assert_eq!(value("12 / 3 / 2", 0, 0), Some(2));
+        assert_eq!(value("a + b == 10", 3, 7), Some(1));
+        assert_eq!(value("1 < 2 == 1", 0, 0), Some(1));
+        assert_eq!(value("0 && 0 || 1", 0, 0), Some(1));
+        assert_eq!(value("1 || 0 && 0", 0, 0), Some(1));
+        assert_eq!(value("-a * b", 2, 3), Some(-6));
+        assert_eq!(value("!a == 0", 0, 0), Some(0));
+    }
+
+    // Arithmetic errors make the digits unrelated rather than panicking
+    #[test]
+    fn failed_arithmetic_does_not_hold() {
+        assert!(!Expression::parse("a / (b - b) == 0").unwrap().holds(1, 2));
+        assert!(!Expression::parse("a % 0 == 0").unwrap().holds(1, 2));
+        assert!(!Expression::parse("a * 9223372036854775807 > 0").unwrap().holds(2, 1));
+        assert!(Expression::parse("abs(a - b) >= 4").unwrap().holds(1, 5));
+    }
+
+    #[test]
+    fn size_and_nesting_are_bounded() {
+        let longest = format!("a{}", " ".repeat(MAX_LENGTH - 1));
+        assert!(Expression::parse(&longest).is_ok());
+        assert!(Expression::parse(&format!("{} ", longest)).unwrap_err().contains("longer"));
+
+        // The whole expression counts as one level
+        let nested = |depth: usize| format!("{}a{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(Expression::parse(&nested(MAX_DEPTH - 1)).is_ok());
+        assert!(Expression::parse(&nested(MAX_DEPTH)).unwrap_err().contains("nested"));
+        assert!(Expression::parse(&format!("{}a", "-".repeat(MAX_DEPTH))).unwrap_err().contains("nested"));
+        assert!(Expression::parse(&format!("{}a{}", "abs(".repeat(MAX_DEPTH), ")".repeat(MAX_DEPTH))).is_err());
+    }
+
+    #[test]
+    fn malformed_input_is_rejected() {
+        let malformed = [
+            "", "a +", "+ a", "(a", "a)", "a b", "a & b", "a = b", "c", "foo(a)", "abs", "abs(a, b)", "min(a)",
+            "max(", "max(a,)", ",", "a $ b", "99999999999999999999", "é",
+        ];
+        for source in malformed {
+            assert!(Expression::parse(source).is_err(), "'{}' parsed", source);
+        }
+    }
+}
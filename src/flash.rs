@@ -0,0 +1,53 @@
+use macroquad::prelude::*;
+
+use sudoku::grid::Grid;
+use sudoku::layout::Layout;
+use sudoku::theme;
+
+
+const FLASH_SECONDS: f64 = 0.6;
+
+
+// Houses lighting up as their last cell is filled in correctly, by the solver or
+// the player
+#[derive(Default)]
+pub struct Flash {
+    // Of each house, none until the first update so houses complete from the
+    // start don't flash
+    complete: Option<Vec<bool>>,
+    // House and the time it was completed
+    flashing: Vec<(Vec<usize>, f64)>,
+}
+
+impl Flash {
+    pub fn update(&mut self, grid: &Grid, filled: &[bool], time: f64) {
+        let houses = grid.houses();
+        let complete: Vec<bool> = houses.iter().map(|house| house.iter().all(|&idx| filled[idx])).collect();
+        if let Some(before) = self.complete.as_ref().filter(|before| before.len() == complete.len()) {
+            for (i, house) in houses.iter().enumerate() {
+                if complete[i] && !before[i] {
+                    self.flashing.push((house.to_vec(), time));
+                }
+            }
+        }
+        self.complete = Some(complete);
+        self.flashing.retain(|&(_, since)| time - since < FLASH_SECONDS);
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    // Fading out from the moment the house was completed
+    pub fn draw(&self, layout: &Layout, time: f64) {
+        let color = theme::current().wave.flash_color;
+        for (house, since) in &self.flashing {
+            let fade = 1. - ((time - since) / FLASH_SECONDS) as f32;
+            let color = Color { a: color.a * fade, ..color };
+            for &idx in house {
+                let p = layout.cell_position(idx);
+                draw_rectangle(p.x, p.y, layout.cell_size, layout.cell_size, color);
+            }
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use macroquad::prelude::*;
+
+use sudoku::grid::Grid;
+use sudoku::layout::Layout;
+use sudoku::{text, theme};
+
+use crate::wave::Wave;
+
+
+pub const DEFAULT_RESOLUTION: (u32, u32) = (1920, 1080);
+pub const DEFAULT_FPS: f64 = 60.;
+
+// The solved grid stays on screen this long at the end
+const HOLD_SECONDS: f64 = 2.;
+// Gives up past this many steps, the rules likely having no solution
+const MAX_STEPS: usize = 200_000;
+
+
+// Resolutions are given as 1920x1080
+pub fn parse_resolution(text: &str) -> Option<(u32, u32)> {
+    let (width, height) = text.split_once('x')?;
+    let resolution = (width.trim().parse().ok()?, height.trim().parse().ok()?);
+    (resolution.0 > 0 && resolution.1 > 0).then_some(resolution)
+}
+
+fn save(path: &str, image: &Image) -> Result<(), String> {
+    let (width, height) = (image.width as usize, image.height as usize);
+    // Texture rows come bottom up
+    let rows: Vec<u8> = image.bytes.chunks(width * 4).rev().flatten().copied().collect();
+    image::save_buffer(path, &rows, width as u32, height as u32, image::ColorType::Rgba8)
+        .map_err(|err| format!("{}: {}", path, err))
+}
+
+// Renders the solve offscreen at a fixed frame rate rather than following the clock,
+// one solver step every tick and the wave animated in between, and writes every
+// frame to dir/frame-000000.png onwards. Frames only depend on the grid's seed.
+// Returns the number of frames written
+pub async fn dump(dir: &str, mut grid: Grid, resolution: (u32, u32), fps: f64, tick_seconds: f64) -> Result<usize, String> {
+    std::fs::create_dir_all(dir).map_err(|err| format!("{}: {}", dir, err))?;
+
+    let (width, height) = (resolution.0 as f32, resolution.1 as f32);
+    let target = render_target(resolution.0, resolution.1);
+    target.texture.set_filter(FilterMode::Linear);
+    let mut camera = Camera2D::from_display_rect(Rect::new(0., 0., width, height));
+    camera.render_target = Some(target);
+
+    let ticks = (tick_seconds * fps).round().max(1.) as usize;
+    let hold = (HOLD_SECONDS * fps).round() as usize;
+    let mut wave: Option<Wave> = None;
+    let mut steps = 0;
+    // Frame the last step's animation ends on
+    let mut solved: Option<usize> = None;
+    let mut frame = 0;
+    while solved.is_none_or(|solved| frame < solved + hold) {
+        let time = frame as f64 / fps;
+        if frame % ticks == 0 && solved.is_none() {
+            if grid.is_resolve() {
+                solved = Some(frame);
+            } else if steps == MAX_STEPS {
+                return Err(format!("no solution after {} steps", MAX_STEPS));
+            } else {
+                steps += 1;
+                let before = grid.cells.clone();
+                wave = None;
+                match grid.step() {
+                    Ok(()) => wave = grid.last_collapse().map(|idx| Wave::new(idx, before, time)),
+                    Err(_) => grid.reset(),
+                }
+                if grid.is_resolve() {
+                    solved = Some(frame + ticks);
+                }
+            }
+        }
+
+        let theme = theme::current();
+        let layout = Layout::fit(grid.size, grid.margins(), width, height);
+        set_camera(&camera);
+        clear_background(theme.board.background_color);
+        match &wave {
+            Some(wave) => wave.draw(&grid, &layout, time),
+            None => grid.draw(&layout),
+        }
+        set_default_camera();
+
+        // Preview in the window, scaled to fit
+        clear_background(theme.board.background_color);
+        let scale = (screen_width() / width).min(screen_height() / height);
+        let size = vec2(width, height) * scale;
+        let params = DrawTextureParams { dest_size: Some(size), flip_y: true, ..Default::default() };
+        let origin = (vec2(screen_width(), screen_height()) - size) / 2.;
+        draw_texture_ex(target.texture, origin.x, origin.y, WHITE, params);
+        let progress = format!("Frame {}, step {}", frame, steps);
+        text::draw(&progress, 0., theme.text.font_size, theme.text.font_size, theme.text.color);
+        next_frame().await;
+
+        save(&format!("{}/frame-{:06}.png", dir, frame), &target.texture.get_texture_data())?;
+        frame += 1;
+    }
+    Ok(frame)
+}
@@ -0,0 +1,543 @@
+use macroquad::prelude::*;
+
+use sudoku::grid::{BoardSize, Grid, Variants};
+use sudoku::layout::{Layout, Margins};
+use sudoku::library::Difficulty;
+use sudoku::{library, state, text, theme};
+
+use crate::analytics;
+use crate::autosave;
+use crate::browser::{Browser, Choice};
+use crate::clipboard;
+use crate::date::{self, Date};
+use crate::flash::Flash;
+use crate::heatmap;
+use crate::input;
+use crate::keys::{self, Action};
+use crate::language::{self, tr, Text};
+use crate::locking::Locking;
+use crate::metrics;
+use crate::notice::Notice;
+use crate::play::Play;
+use crate::records;
+use crate::recording::Recording;
+use crate::screen::{Phase, Screen};
+use crate::screenshot;
+use crate::sounds::Sounds;
+use crate::teacher::Teacher;
+use crate::toggles::{self, Toggles};
+use crate::trace;
+use crate::victory::Victory;
+use crate::volatility::Volatility;
+use crate::watchdog::Watchdog;
+use crate::wave::Wave;
+
+
+// Solve until the editor or the title screen is asked for, the player taking over the cells
+// left open in play mode. Every solve gets its own seed so it can be replayed. The daily grid
+// is labeled with its date until another one is drawn or the rules change. Puzzles loaded from
+// the library replace the givens. Leaving for the title screen or closing the window keeps the
+// game for the next launch. Returns the rules as last toggled and the screen to go to
+pub async fn run(
+    start: (Grid, Option<Play>, bool),
+    givens: &mut Vec<Option<u8>>,
+    seed: u64,
+    daily: Option<Date>,
+    tick_seconds: f64,
+    watchdog_timeout: f64,
+    sounds: &mut Sounds,
+) -> (Variants, Screen) {
+    let mut game = Game::new(start, seed, daily, tick_seconds, watchdog_timeout);
+    loop {
+        clear_background(theme::current().board.background_color);
+        let layout = Layout::new(game.grid.size, game.margins());
+        if is_quit_requested() {
+            game.autosave();
+            std::process::exit(0);
+        }
+        // Escape first dismisses the watchdog
+        if keys::pressed(Action::Menu) && (game.phase.play().is_some() || !game.watchdog.is_stalled(&game.grid, get_time())) {
+            game.autosave();
+            game.stop_recording();
+            return (game.grid.variants, Screen::Title);
+        }
+
+        // The solver waits while the library is open
+        if game.browser.is_some() {
+            game.browse(&layout, givens);
+            next_frame().await;
+            continue;
+        }
+
+        let stepped = game.step(sounds);
+        game.handle_keys(givens, sounds);
+        game.restart();
+        if keys::pressed(Action::Edit) {
+            game.stop_recording();
+            return (game.grid.variants, Screen::Editor);
+        }
+        game.apply_changes(&layout);
+        game.update_phase(sounds);
+
+        if game.phase.play().is_some() {
+            game.play(&layout, sounds);
+        } else {
+            game.draw_solve(&layout);
+        }
+        game.draw_help();
+        if game.phase.play().is_none() {
+            game.watchdog.update(&game.grid, get_time());
+        }
+        game.capture(stepped);
+
+        next_frame().await;
+    }
+}
+
+// Everything a solve on the board keeps from one frame to the next
+struct Game {
+    grid: Grid,
+    phase: Phase,
+    seed: u64,
+    daily: Option<Date>,
+    tick_seconds: f64,
+    tick: f64,
+    wave: Option<Wave>,
+    watchdog: Watchdog,
+    heatmap: bool,
+    toggles: Toggles,
+    locking: Locking,
+    notice: Option<Notice>,
+    flash: Flash,
+    volatility: Volatility,
+    show_volatility: bool,
+    recording: Option<Recording>,
+    browser: Option<Browser>,
+    teacher: Option<Teacher>,
+    // Puzzle just loaded, and whether to play it
+    loaded: Option<bool>,
+    // Of the library puzzle on the board, for its best time
+    difficulty: Option<Difficulty>,
+    solve_start: f64,
+    restarts: usize,
+}
+
+impl Game {
+    fn new((mut grid, play, browse): (Grid, Option<Play>, bool), seed: u64, daily: Option<Date>, tick_seconds: f64, watchdog_timeout: f64) -> Self {
+        trace::follow(&mut grid);
+        trace::seed(seed);
+        Self {
+            phase: play.map_or(Phase::Solving, |play| Phase::Playing(Box::new(play))),
+            seed,
+            daily,
+            tick_seconds,
+            tick: get_time(),
+            wave: None,
+            watchdog: Watchdog::new(watchdog_timeout, get_time()),
+            heatmap: false,
+            toggles: Toggles::default(),
+            locking: Locking::default(),
+            notice: None,
+            flash: Flash::default(),
+            volatility: Volatility::new(grid.cells.len()),
+            show_volatility: false,
+            recording: None,
+            browser: browse.then(Browser::new),
+            teacher: None,
+            loaded: None,
+            difficulty: None,
+            solve_start: get_time(),
+            restarts: 0,
+            grid,
+        }
+    }
+
+    fn margins(&self) -> Margins {
+        match (self.phase.play(), &self.teacher) {
+            (Some(_), _) => Play::margins(&self.grid),
+            (None, Some(_)) => Teacher::margins(self.grid.margins()),
+            (None, None) => self.grid.margins(),
+        }
+    }
+
+    fn autosave(&self) {
+        if let Err(err) = autosave::save(&self.grid, self.phase.play()) {
+            eprintln!("Couldn't autosave {}", err);
+        }
+    }
+
+    fn stop_recording(&mut self) {
+        if let Some(recording) = self.recording.take() {
+            finish_recording(recording);
+        }
+    }
+
+    // The library over the board until a puzzle is picked or it's closed
+    fn browse(&mut self, layout: &Layout, givens: &mut Vec<Option<u8>>) {
+        let Some(open) = &mut self.browser else {
+            return;
+        };
+        let choice = open.update();
+        self.grid.draw(layout);
+        open.draw();
+        if let Some(Choice::Solve(i) | Choice::Play(i)) = choice {
+            let entry = &library::PUZZLES[i];
+            let puzzle = Some(self.grid.size)
+                .filter(|&size| size == BoardSize::default())
+                .and_then(|size| Grid::with_givens(size, self.grid.variants.clone(), entry.givens()).ok());
+            let message = match puzzle {
+                Some(puzzle) => {
+                    self.load(puzzle, givens);
+                    self.loaded = Some(matches!(choice, Some(Choice::Play(_))));
+                    self.difficulty = Some(entry.difficulty);
+                    tr(Text::Loaded, &[&entry.name])
+                }
+                None => tr(Text::DoesntFit, &[&entry.name]),
+            };
+            self.notice = Some(Notice::new(message, get_time()));
+        }
+        if choice.is_some() {
+            self.browser = None;
+        }
+    }
+
+    // New puzzle on the board, solved with the same settings
+    fn load(&mut self, mut puzzle: Grid, givens: &mut Vec<Option<u8>>) {
+        puzzle.backjumping = self.grid.backjumping;
+        self.grid = puzzle;
+        trace::follow(&mut self.grid);
+        if let Some(teacher) = &mut self.teacher {
+            teacher.follow(&mut self.grid);
+        }
+        *givens = self.grid.givens().to_vec();
+        self.daily = None;
+    }
+
+    // A solver step once the tick is over. Returns whether one was taken
+    fn step(&mut self, sounds: &mut Sounds) -> bool {
+        if self.phase.play().is_some() || get_time() - self.tick <= self.tick_seconds {
+            return false;
+        }
+        self.tick = get_time();
+        if self.grid.is_resolve() {
+            return false;
+        }
+        let before = self.grid.cells.clone();
+        self.wave = None;
+        let result = self.grid.step();
+        trace::step(&self.grid);
+        if let Some(teacher) = &mut self.teacher {
+            teacher.step(&self.grid, &before);
+        }
+        self.volatility.record(&before, &self.grid.cells);
+        self.watchdog.record(&self.grid, result.as_ref().err(), self.tick);
+        match result {
+            Ok(()) => {
+                self.wave = self.grid.last_collapse().map(|idx| Wave::new(idx, before, self.tick));
+                sounds.collapse();
+            }
+            // Reset grid in case of unresolvable cell
+            Err(_) => {
+                sounds.contradiction();
+                let failed = self.grid.cells.clone();
+                self.grid.reset();
+                self.volatility.record(&failed, &self.grid.cells);
+                self.restarts += 1;
+            }
+        }
+        true
+    }
+
+    fn handle_keys(&mut self, givens: &mut Vec<Option<u8>>, sounds: &mut Sounds) {
+        if keys::pressed(Action::Play) {
+            self.phase = match self.phase {
+                Phase::Playing(_) => Phase::Solving,
+                _ => Phase::Playing(Box::new(Play::new(&self.grid))),
+            };
+            self.flash.reset();
+            self.watchdog.reset(get_time());
+        }
+        if keys::pressed(Action::Browse) {
+            self.browser = Some(Browser::new());
+        }
+        if keys::pressed(Action::Heatmap) {
+            self.heatmap = !self.heatmap;
+        }
+        if keys::pressed(Action::Teach) {
+            self.teacher = match self.teacher.take() {
+                Some(teacher) => {
+                    teacher.leave(&mut self.grid);
+                    None
+                }
+                None => Some(Teacher::new(&mut self.grid)),
+            };
+        }
+        if keys::pressed(Action::Volatility) && !clipboard::modifier_down() {
+            self.show_volatility = !self.show_volatility;
+        }
+        input::toggle_fullscreen();
+        if keys::pressed(Action::Theme) {
+            theme::swap();
+        }
+        if keys::pressed(Action::Palette) && !clipboard::modifier_down() {
+            let palette = theme::current().palette.name.next();
+            theme::set_palette(palette);
+            self.notice = Some(Notice::new(tr(Text::PaletteName, &[&palette.name()]), get_time()));
+        }
+        sounds.update();
+        if clipboard::copy_pressed() {
+            clipboard::copy(&self.grid);
+            self.notice = Some(Notice::new(tr(Text::Copied, &[]), get_time()));
+        }
+        if clipboard::paste_pressed() {
+            let pasted = clipboard::paste(self.grid.size).and_then(|pasted| {
+                Grid::with_givens(self.grid.size, self.grid.variants.clone(), pasted)
+                    .map_err(|contradiction| language::contradiction(&contradiction, self.grid.size.side()))
+            });
+            let message = match pasted {
+                Ok(puzzle) => {
+                    self.load(puzzle, givens);
+                    self.loaded = Some(false);
+                    self.difficulty = None;
+                    tr(Text::Pasted, &[])
+                }
+                Err(err) => tr(Text::PasteFailed, &[&err]),
+            };
+            self.notice = Some(Notice::new(message, get_time()));
+        }
+    }
+
+    // Over from the start on a new grid, the same one again or a puzzle just loaded
+    fn restart(&mut self) {
+        let new_grid = keys::pressed(Action::NewGrid);
+        if !new_grid && !keys::pressed(Action::Replay) && self.loaded.is_none() {
+            return;
+        }
+        if new_grid {
+            self.seed = ::rand::random();
+            self.daily = None;
+            self.difficulty = None;
+        }
+        self.grid.seed(self.seed);
+        self.grid.reset();
+        trace::seed(self.seed);
+        if let Some(teacher) = &mut self.teacher {
+            teacher.clear();
+        }
+        self.phase = Phase::Solving;
+        self.wave = None;
+        self.flash.reset();
+        self.volatility.clear();
+        self.solve_start = get_time();
+        self.restarts = 0;
+        self.watchdog.reset(get_time());
+        if self.loaded.take() == Some(true) {
+            self.phase = Phase::Playing(Box::new(Play::puzzle(&self.grid, self.difficulty)));
+        }
+    }
+
+    // Rules toggled or digits locked in
+    fn apply_changes(&mut self, layout: &Layout) {
+        let mut changes = self.toggles.update(&mut self.grid);
+        if self.phase.play().is_none() {
+            changes = changes.or_else(|| self.locking.update(&mut self.grid, layout));
+        }
+        let Some(message) = changes else {
+            return;
+        };
+        self.wave = None;
+        self.daily = None;
+        self.difficulty = None;
+        self.watchdog.reset(get_time());
+        if let Some(play) = self.phase.play_mut() {
+            play.refresh(&self.grid);
+            play.difficulty = None;
+        }
+        self.notice = Some(Notice::new(message, get_time()));
+    }
+
+    fn update_phase(&mut self, sounds: &mut Sounds) {
+        match self.phase {
+            Phase::Solved(_) if !self.grid.is_resolve() => self.phase = Phase::Solving,
+            Phase::Solving if self.grid.is_resolve() => {
+                let seconds = get_time() - self.solve_start;
+                self.phase = Phase::Solved(Victory::new(seconds, self.restarts, get_time()));
+                analytics::record(self.seed, self.restarts);
+                metrics::record(&self.grid, self.seed, seconds, self.restarts);
+                sounds.solved();
+            }
+            _ => {}
+        }
+    }
+
+    // The player's turn, drawn on the board
+    fn play(&mut self, layout: &Layout, sounds: &mut Sounds) {
+        let Phase::Playing(play) = &mut self.phase else {
+            return;
+        };
+        let grid = &self.grid;
+        let mut notice = None;
+        if keys::pressed(Action::Check) {
+            let message = match play.check() {
+                0 => tr(Text::NoMistakes, &[]),
+                wrong => tr(Text::WrongDigits, &[&wrong]),
+            };
+            notice = Some(message);
+        }
+        if keys::pressed(Action::Hint) {
+            notice = Some(play.hint(grid).map_or(tr(Text::NoHint, &[]), |hint| language::hint(hint, grid.size.side())));
+        }
+        if keys::pressed(Action::InputMode) {
+            input::set_digit_first(!input::digit_first());
+            notice = Some(tr(if input::digit_first() { Text::DigitFirst } else { Text::CellFirst }, &[]));
+        }
+        if keys::pressed(Action::Notes) {
+            play.toggle_notes(grid);
+        }
+        if play.update(grid, layout) {
+            sounds.solved();
+            notice = Some(finish_play(play));
+        }
+        play.draw(grid, layout);
+        self.flash.update(grid, &play.correct(), get_time());
+        self.flash.draw(layout, get_time());
+        if let Some(message) = notice {
+            self.notice = Some(Notice::new(message, get_time()));
+        }
+    }
+
+    // The solver's board, with how the solve went once it's over
+    fn draw_solve(&mut self, layout: &Layout) {
+        let grid = &self.grid;
+        #[cfg(feature = "audit")]
+        let before = (grid.cells.clone(), grid.eliminations);
+        match &self.wave {
+            Some(wave) => wave.draw(grid, layout, get_time()),
+            None => grid.draw(layout),
+        }
+        if self.heatmap {
+            heatmap::draw(grid, layout);
+        }
+        if let Some(teacher) = &self.teacher {
+            teacher.draw(layout, grid.margins());
+        }
+        self.locking.draw(grid, layout);
+        let resolved: Vec<bool> = grid.cells.iter().map(|cell| cell.possible_values.len() == 1).collect();
+        self.flash.update(grid, &resolved, get_time());
+        self.flash.draw(layout, get_time());
+        #[cfg(feature = "audit")]
+        assert!(before == (grid.cells.clone(), grid.eliminations), "Drawing changed the grid");
+        match &self.phase {
+            Phase::Solved(_) if self.show_volatility => self.volatility.draw(layout, grid.size.side()),
+            Phase::Solved(victory) => {
+                let keys = tr(Text::VictoryKeys, &[&keys::label(Action::NewGrid), &keys::label(Action::Replay), &keys::label(Action::Volatility)]);
+                victory.draw(grid, layout, get_time(), &keys);
+            }
+            _ => {}
+        }
+    }
+
+    // Keys along the top, the latest notice and the date of a daily grid
+    fn draw_help(&self) {
+        let theme = theme::current();
+        text::draw(
+            &tr(Text::HelpKeys, &[
+                &keys::label(Action::NewGrid), &keys::label(Action::Replay), &keys::label(Action::Browse),
+                &keys::label(Action::Edit), &keys::label(Action::Play), &keys::label(Action::Heatmap),
+                &keys::label(Action::Menu),
+            ]),
+            0., theme.text.font_size,
+            theme.text.font_size,
+            theme.text.color
+        );
+        let help = tr(Text::HelpMore, &[
+            &toggles::help(),
+            &keys::label(Action::Mute),
+            &keys::label(Action::Theme),
+            &keys::label(Action::Palette),
+            &input::fullscreen_help(),
+            &keys::label(Action::Screenshot),
+            &keys::label(Action::Record),
+            &keys::label(Action::SaveState),
+            &keys::label(Action::Copy),
+            &keys::label(Action::Paste),
+            &keys::label(Action::Undo),
+            &keys::label(Action::Redo),
+            &keys::label(Action::Check),
+            &keys::label(Action::Notes),
+            &keys::label(Action::Hint),
+            &keys::label(Action::InputMode),
+            &keys::label(Action::Teach),
+        ]);
+        text::draw(&help, 0., 2. * theme.text.font_size, theme.text.font_size, theme.text.color);
+        if let Some(notice) = &self.notice {
+            notice.draw(get_time());
+        }
+        if let Some(date) = self.daily {
+            let label = tr(Text::Daily, &[&date]);
+            let x = screen_width() - text::measure(&label, theme.text.font_size).width - theme.text.font_size / 2.;
+            let y = screen_height() - theme.text.font_size / 2.;
+            text::draw(&label, x, y, theme.text.font_size, theme.text.color);
+        }
+    }
+
+    // Saved solve, screenshot and recorded frame, taken once everything is drawn, the
+    // notices showing from the next frame
+    fn capture(&mut self, mut stepped: bool) {
+        if keys::pressed(Action::SaveState) {
+            let path = screenshot::free_path(&format!("sudoku-{}", date::timestamp()), "json");
+            let message = match state::save(&path, &self.grid) {
+                Ok(()) => tr(Text::SavedSolve, &[&path]),
+                Err(err) => tr(Text::SaveSolveFailed, &[&err]),
+            };
+            self.notice = Some(Notice::new(message, get_time()));
+        }
+        if keys::pressed(Action::Screenshot) {
+            let message = match screenshot::capture() {
+                Ok(path) => tr(Text::Saved, &[&path]),
+                Err(err) => tr(Text::ScreenshotFailed, &[&err]),
+            };
+            self.notice = Some(Notice::new(message, get_time()));
+        }
+        if keys::pressed(Action::Record) {
+            match self.recording.take() {
+                Some(recording) => self.notice = Some(Notice::new(finish_recording(recording), get_time())),
+                None => {
+                    self.recording = Some(Recording::new(self.tick_seconds));
+                    stepped = true;
+                    let message = tr(Text::RecordingStarted, &[&keys::label(Action::Record)]);
+                    self.notice = Some(Notice::new(message, get_time()));
+                }
+            }
+        }
+        if let Some(active) = self.recording.as_mut().filter(|_| stepped) {
+            let result = active.capture();
+            if let Err(err) = result {
+                self.recording = None;
+                self.notice = Some(Notice::new(tr(Text::RecordingFailed, &[&err]), get_time()));
+            } else if self.grid.is_resolve() {
+                self.notice = Some(Notice::new(finish_recording(self.recording.take().unwrap()), get_time()));
+            }
+        }
+    }
+}
+
+// Keeps the time of a library puzzle if it's the best of its difficulty
+fn finish_play(play: &Play) -> String {
+    let time = records::format_time(play.seconds);
+    match play.difficulty.map(|difficulty| (difficulty, records::record(difficulty, play.seconds))) {
+        Some((difficulty, Ok(true))) => tr(Text::NewBest, &[&time, &play.mistakes, &language::difficulty(difficulty)]),
+        Some((_, Err(err))) => {
+            eprintln!("Couldn't save the best time {}", err);
+            tr(Text::PuzzleSolved, &[&time, &play.mistakes])
+        }
+        _ => tr(Text::PuzzleSolved, &[&time, &play.mistakes]),
+    }
+}
+
+fn finish_recording(recording: Recording) -> String {
+    match recording.finish() {
+        Ok(path) => tr(Text::Saved, &[&path]),
+        Err(err) => tr(Text::RecordingFailed, &[&err]),
+    }
+}
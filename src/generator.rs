@@ -0,0 +1,196 @@
+use ::rand::rngs::StdRng;
+use ::rand::seq::SliceRandom;
+use ::rand::{Rng, SeedableRng};
+
+use crate::grid::{BoardSize, Grid, Variants};
+use crate::hints;
+use crate::library::Difficulty;
+use crate::symmetry::Symmetry;
+
+
+// Puzzles generated looking for one on target before giving up
+pub const MAX_ATTEMPTS: usize = 200;
+
+// What a generated puzzle is to be like, anything goes for what's left out
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Target {
+    pub symmetry: Symmetry,
+    pub clues: Option<usize>,
+    pub difficulty: Option<Difficulty>,
+}
+
+impl Target {
+    fn describe(self) -> String {
+        let clues = self.clues.map(|clues| format!("{} clues", clues));
+        let difficulty = self.difficulty.map(|difficulty| format!("rated {}", difficulty.name()));
+        clues.into_iter().chain(difficulty).collect::<Vec<_>>().join(" ")
+    }
+}
+
+// Puzzle on target, generated from the seed then from seeds drawn from it. Givens of the
+// solution are put back in a seeded order, symmetric ones together, for puzzles with
+// fewer clues than asked for, which makes them easier at times
+pub fn generate_target(size: BoardSize, variants: &Variants, seed: u64, target: Target) -> Result<Vec<Option<u8>>, String> {
+    let mut seeds = StdRng::seed_from_u64(seed);
+    for attempt in 0..MAX_ATTEMPTS {
+        let seed = if attempt == 0 { seed } else { seeds.gen() };
+        let mut givens = generate(size, variants, seed, target.symmetry);
+        if let Some(clues) = target.clues {
+            let Some(filled) = fill(size, variants, givens, seed, target.symmetry, clues) else {
+                continue;
+            };
+            givens = filled;
+        }
+        let on_target = target.difficulty.is_none_or(|difficulty| {
+            Grid::with_givens(size, variants.clone(), givens.clone())
+                .is_ok_and(|grid| hints::grade(&grid, &givens) == difficulty)
+        });
+        if on_target {
+            return Ok(givens);
+        }
+    }
+    Err(format!("no puzzle with {} after {} attempts", target.describe(), MAX_ATTEMPTS))
+}
+
+// Givens put back from the solution up to exactly the count of clues, none when the
+// puzzle has more or the symmetry can't come to it
+fn fill(
+    size: BoardSize,
+    variants: &Variants,
+    mut givens: Vec<Option<u8>>,
+    seed: u64,
+    symmetry: Symmetry,
+    clues: usize,
+) -> Option<Vec<Option<u8>>> {
+    let solution = Grid::with_givens(size, variants.clone(), givens.clone()).ok()?.solution()?;
+    let mut order: Vec<usize> = (0..givens.len()).filter(|&idx| givens[idx].is_none()).collect();
+    order.shuffle(&mut StdRng::seed_from_u64(seed));
+    let mut count = givens.iter().flatten().count();
+    for orbit in symmetry.orbits(&order, size.side()) {
+        if count + orbit.len() <= clues {
+            count += orbit.len();
+            for idx in orbit {
+                givens[idx] = Some(solution[idx]);
+            }
+        }
+    }
+    (count == clues).then_some(givens)
+}
+
+
+// Puzzle with a single solution: a grid solved from the seed, then its digits taken away
+// in a seeded order, those the symmetry maps onto each other together. Minimal without a
+// symmetry, see `minimize`, else no pair of givens can go
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(size, variants)))]
+pub fn generate(size: BoardSize, variants: &Variants, seed: u64, symmetry: Symmetry) -> Vec<Option<u8>> {
+    let mut grid = Grid::new(size, variants.clone());
+    grid.seed(seed);
+    while !grid.is_resolve() {
+        if grid.step().is_err() {
+            grid.reset();
+        }
+    }
+
+    let givens: Vec<Option<u8>> = grid.cells.iter().map(|cell| Some(cell.possible_values[0])).collect();
+    let mut order: Vec<usize> = (0..givens.len()).collect();
+    order.shuffle(&mut StdRng::seed_from_u64(seed));
+    remove(size, variants, givens, symmetry.orbits(&order, size.side()))
+}
+
+fn is_unique(size: BoardSize, variants: &Variants, givens: &[Option<u8>]) -> bool {
+    Grid::with_givens(size, variants.clone(), givens.to_vec()).is_ok_and(|mut grid| grid.count_solutions(2) == 1)
+}
+
+// Givens of a puzzle with a single solution taken away in the order of the cells, as
+// long as the ones left allow no other solution. One pass is enough for the result to
+// be minimal: a given that had to stay only has to more as others go
+pub fn minimize(size: BoardSize, variants: &Variants, givens: Vec<Option<u8>>, order: Vec<usize>) -> Vec<Option<u8>> {
+    remove(size, variants, givens, order.into_iter().map(|idx| vec![idx]))
+}
+
+// Each group of givens taken away at once, put back if another solution opens up
+fn remove(
+    size: BoardSize,
+    variants: &Variants,
+    mut givens: Vec<Option<u8>>,
+    groups: impl IntoIterator<Item = Vec<usize>>,
+) -> Vec<Option<u8>> {
+    for group in groups {
+        let values: Vec<Option<u8>> = group.iter().map(|&idx| givens[idx].take()).collect();
+        if values.iter().all(Option::is_none) {
+            continue;
+        }
+        if !is_unique(size, variants, &givens) {
+            for (&idx, value) in group.iter().zip(values) {
+                givens[idx] = value;
+            }
+        }
+    }
+    givens
+}
+
+// Givens the puzzle can do without, each on its own still leaving a single solution
+pub fn redundant_clues(size: BoardSize, variants: &Variants, givens: &[Option<u8>]) -> Vec<usize> {
+    let mut without = givens.to_vec();
+    (0..givens.len())
+        .filter(|&idx| {
+            let Some(value) = without[idx].take() else {
+                return false;
+            };
+            let redundant = is_unique(size, variants, &without);
+            without[idx] = Some(value);
+            redundant
+        })
+        .collect()
+}
+
+// A puzzle with a single solution that every given is needed for
+pub fn is_minimal(size: BoardSize, variants: &Variants, givens: &[Option<u8>]) -> bool {
+    is_unique(size, variants, givens) && redundant_clues(size, variants, givens).is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEED: u64 = 0x5eed;
+
+    #[test]
+    fn generated_puzzles_are_minimal() {
+        let (size, variants) = (BoardSize::default(), Variants::default());
+        assert!(is_minimal(size, &variants, &generate(size, &variants, SEED, Symmetry::None)));
+    }
+
+    // Brought down to a minimal puzzle with the same solution
+    #[test]
+    fn minimized_puzzles_keep_their_solution() {
+        let (size, variants) = (BoardSize::default(), Variants::default());
+        let solution = Grid::new(size, variants.clone()).solution().unwrap();
+        let full: Vec<Option<u8>> = solution.iter().copied().map(Some).collect();
+        assert!(!is_minimal(size, &variants, &full));
+        let minimized = minimize(size, &variants, full, (0..size.cell_count()).collect());
+        assert!(is_minimal(size, &variants, &minimized));
+        let mut grid = Grid::with_givens(size, variants, minimized).unwrap();
+        assert_eq!(grid.solution(), Some(solution));
+    }
+
+    #[test]
+    fn puzzles_meet_their_target() {
+        let size = BoardSize::default();
+        let target = Target { symmetry: Symmetry::Rotational, clues: Some(30), difficulty: Some(Difficulty::Easy) };
+        let givens = generate_target(size, &Variants::default(), SEED, target).unwrap();
+        assert_eq!(givens.iter().flatten().count(), 30);
+        assert!(Symmetry::Rotational.holds(&givens, size.side()));
+        let mut grid = Grid::with_givens(size, Variants::default(), givens.clone()).unwrap();
+        assert_eq!(hints::grade(&grid, &givens), Difficulty::Easy);
+        assert_eq!(grid.count_solutions(2), 1);
+    }
+
+    // A full grid takes no technique at all
+    #[test]
+    fn targets_out_of_reach_are_rejected() {
+        let small = BoardSize::from_side(4).unwrap();
+        let target = Target { clues: Some(small.cell_count()), difficulty: Some(Difficulty::Expert), ..Target::default() };
+        assert!(generate_target(small, &Variants::default(), SEED, target).is_err());
+    }
+}
@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+
+const MAX_CODE: u16 = 4096;
+const MIN_CODE_SIZE: u8 = 8;
+
+
+// Writes an endlessly looping GIF89a, every frame bringing its own 256 color table
+pub struct Encoder<W: Write> {
+    out: W,
+}
+
+impl<W: Write> Encoder<W> {
+    pub fn new(mut out: W, width: u16, height: u16) -> io::Result<Self> {
+        out.write_all(b"GIF89a")?;
+        out.write_all(&width.to_le_bytes())?;
+        out.write_all(&height.to_le_bytes())?;
+        // No global color table, background 0, square pixels
+        out.write_all(&[0, 0, 0])?;
+        // Netscape extension, looping forever
+        out.write_all(&[0x21, 0xff, 11])?;
+        out.write_all(b"NETSCAPE2.0")?;
+        out.write_all(&[3, 1, 0, 0, 0])?;
+        Ok(Self { out })
+    }
+
+    // Frame covering the whole image, shown for delay hundredths of a second.
+    // Palette holds the RGB triplets indices refer to
+    pub fn frame(&mut self, width: u16, height: u16, palette: &[u8], indices: &[u8], delay: u16) -> io::Result<()> {
+        let out = &mut self.out;
+        out.write_all(&[0x21, 0xf9, 4, 0])?;
+        out.write_all(&delay.to_le_bytes())?;
+        out.write_all(&[0, 0])?;
+
+        out.write_all(&[0x2c, 0, 0, 0, 0])?;
+        out.write_all(&width.to_le_bytes())?;
+        out.write_all(&height.to_le_bytes())?;
+        // Local color table of 2^(7+1) entries
+        out.write_all(&[0x80 | 7])?;
+        let mut table = palette.to_vec();
+        table.resize(256 * 3, 0);
+        out.write_all(&table)?;
+
+        out.write_all(&[MIN_CODE_SIZE])?;
+        for block in compress(indices).chunks(255) {
+            out.write_all(&[block.len() as u8])?;
+            out.write_all(block)?;
+        }
+        out.write_all(&[0])
+    }
+
+    pub fn finish(mut self) -> io::Result<W> {
+        self.out.write_all(&[0x3b])?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+// Codes packed from the least significant bit
+#[derive(Default)]
+struct Bits {
+    bytes: Vec<u8>,
+    bits: u32,
+    pending: u32,
+}
+
+impl Bits {
+    fn push(&mut self, code: u16, size: u32) {
+        self.bits |= (code as u32) << self.pending;
+        self.pending += size;
+        while self.pending >= 8 {
+            self.bytes.push(self.bits as u8);
+            self.bits >>= 8;
+            self.pending -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.pending > 0 {
+            self.bytes.push(self.bits as u8);
+        }
+        self.bytes
+    }
+}
+
+// Variable width LZW as GIF wants it, the table starting over once all 12 bit
+// codes are taken
+fn compress(indices: &[u8]) -> Vec<u8> {
+    let clear = 1 << MIN_CODE_SIZE;
+    let end = clear + 1;
+    let mut bits = Bits::default();
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut next = end + 1;
+    let mut size = MIN_CODE_SIZE as u32 + 1;
+    bits.push(clear, size);
+
+    let Some((&first, rest)) = indices.split_first() else {
+        bits.push(end, size);
+        return bits.finish();
+    };
+    let mut prefix = first as u16;
+    for &index in rest {
+        if let Some(&code) = table.get(&(prefix, index)) {
+            prefix = code;
+            continue;
+        }
+        bits.push(prefix, size);
+        if next == MAX_CODE {
+            bits.push(clear, size);
+            table.clear();
+            next = end + 1;
+            size = MIN_CODE_SIZE as u32 + 1;
+        } else {
+            table.insert((prefix, index), next);
+            next += 1;
+            if next > 1 << size && size < 12 {
+                size += 1;
+            }
+        }
+        prefix = index as u16;
+    }
+    bits.push(prefix, size);
+    bits.push(end, size);
+    bits.finish()
+}
@@ -0,0 +1,1455 @@
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use ::rand::prelude::IteratorRandom;
+use ::rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+use macroquad::prelude::*;
+
+use crate::cell::Cell;
+use crate::constraints::{Aggregate, Constraint, Distinct, NonConsecutive};
+use crate::contradiction::Contradiction;
+use crate::events::{Event, Observer, ObserverId};
+use crate::heuristic::Heuristic;
+use crate::layout::{Layout, Margins, CELL_SIZE};
+#[cfg(feature = "audit")]
+use crate::notation::format_cells;
+use crate::notation::Coord;
+use crate::snapshot::GridSnapshot;
+use crate::text;
+use crate::theme;
+use crate::units::{self, CellView, House, HouseKind, UnitTables};
+
+
+const BIG_NUM_BASELINE: f32 = 40.;
+const SMALL_NUM_OFFSET: (f32, f32) = (5., 18.);
+
+const MAX_SIDE: usize = 25;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoardSize {
+    pub box_width: usize,
+    pub box_height: usize,
+}
+
+impl Default for BoardSize {
+    fn default() -> Self {
+        Self { box_width: 3, box_height: 3 }
+    }
+}
+
+impl BoardSize {
+    // Boxes are as square as possible, wider than tall (6 -> 3x2, 12 -> 4x3)
+    pub fn from_side(side: usize) -> Option<Self> {
+        if side > MAX_SIDE {
+            return None;
+        }
+        (2..=side)
+            .take_while(|h| h * h <= side)
+            .filter(|&h| side.is_multiple_of(h))
+            .last()
+            .map(|box_height| Self { box_width: side / box_height, box_height })
+    }
+
+    pub fn side(&self) -> usize {
+        self.box_width * self.box_height
+    }
+
+    pub fn cell_count(&self) -> usize {
+        self.side() * self.side()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Variants {
+    pub diagonal: bool,
+    pub windoku: bool,
+    pub anti_knight: bool,
+    pub anti_king: bool,
+    pub non_consecutive: bool,
+    // Cells at the same position in their box form an extra house
+    pub disjoint_groups: bool,
+    // Jigsaw region of each cell, replacing the boxes
+    pub regions: Option<Vec<usize>>,
+    pub constraints: Vec<Rc<dyn Constraint>>,
+}
+
+pub struct Grid {
+    pub size: BoardSize,
+    pub variants: Variants,
+    pub cells: Vec<Cell>,
+    pub eliminations: usize,
+    // Cells picked by the following steps
+    pub heuristic: Heuristic,
+    // Contradictions take the solve back to the collapse behind them rather than over
+    // from the start, see `backjump`
+    pub backjumping: bool,
+    units: Rc<UnitTables>,
+    rules: Rc<Rules>,
+    // The generator behind rand's StdRng, whose position can be saved, see `state`
+    pub(crate) rng: ChaCha12Rng,
+    // Cells collapsed since the last reset and the digit each got, in order
+    pub(crate) collapses: Vec<(usize, u8)>,
+    pub(crate) last_collapse: Option<usize>,
+    // Collapses since the last reset when backjumping, and the state before each
+    pub(crate) decisions: Vec<Decision>,
+    // Collapses each cell's candidates follow from, by their place in `decisions`
+    pub(crate) reasons: Vec<Levels>,
+    observers: Vec<(ObserverId, Observer)>,
+    next_observer: usize,
+    scratch: Scratch,
+}
+
+pub(crate) struct Decision {
+    pub(crate) before: GridSnapshot,
+    pub(crate) reasons: Vec<Levels>,
+    pub(crate) idx: usize,
+    pub(crate) value: u8,
+}
+
+// Set of decision levels, a bit for each
+#[derive(Clone, Default)]
+pub(crate) struct Levels(Vec<u64>);
+
+impl Levels {
+    fn single(level: usize) -> Self {
+        let mut levels = Self::default();
+        levels.insert(level);
+        levels
+    }
+
+    pub(crate) fn insert(&mut self, level: usize) {
+        if self.0.len() <= level / 64 {
+            self.0.resize(level / 64 + 1, 0);
+        }
+        self.0[level / 64] |= 1 << (level % 64);
+    }
+
+    // Levels in the set, lowest first
+    pub(crate) fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.0.iter().enumerate().flat_map(|(i, &word)| (0..64).filter(move |bit| word & 1 << bit != 0).map(move |bit| i * 64 + bit))
+    }
+
+    fn union(&mut self, other: &Levels) {
+        if self.0.len() < other.0.len() {
+            self.0.resize(other.0.len(), 0);
+        }
+        self.0.iter_mut().zip(&other.0).for_each(|(word, other)| *word |= other);
+    }
+
+    fn remove(&mut self, level: usize) {
+        if let Some(word) = self.0.get_mut(level / 64) {
+            *word &= !(1 << (level % 64));
+        }
+    }
+
+    fn last(&self) -> Option<usize> {
+        let (i, word) = self.0.iter().enumerate().rev().find(|(_, word)| **word != 0)?;
+        Some(i * 64 + 63 - word.leading_zeros() as usize)
+    }
+
+    fn clear(&mut self) {
+        self.0.clear();
+    }
+}
+
+// Every constraint of the grid, the cheap built-in ones first, along with the
+// constraints watching each cell and the candidates they leave before any collapse
+#[derive(Default)]
+struct Rules {
+    constraints: Vec<Rc<dyn Constraint>>,
+    builtin: usize,
+    watchers: Vec<Vec<usize>>,
+    givens: Vec<Option<u8>>,
+    start: Vec<Cell>,
+}
+
+// Constraints waiting to prune, each queued at most once
+#[derive(Default)]
+struct Worklist {
+    builtin: usize,
+    queued: Vec<bool>,
+    cheap: VecDeque<usize>,
+    costly: VecDeque<usize>,
+}
+
+impl Worklist {
+    // Empty, sized for the constraints of the rules
+    fn prepare(&mut self, rules: &Rules) {
+        self.builtin = rules.builtin;
+        self.cheap.clear();
+        self.costly.clear();
+        self.queued.clear();
+        self.queued.resize(rules.constraints.len(), false);
+    }
+
+    fn push(&mut self, id: usize) {
+        if !self.queued[id] {
+            self.queued[id] = true;
+            if id < self.builtin {
+                self.cheap.push_back(id);
+            } else {
+                self.costly.push_back(id);
+            }
+        }
+    }
+
+    fn pop(&mut self) -> Option<usize> {
+        let id = self.cheap.pop_front().or_else(|| self.costly.pop_front())?;
+        self.queued[id] = false;
+        Some(id)
+    }
+}
+
+// Room propagation works in, kept from one call to the next so steps don't allocate
+#[derive(Default)]
+struct Scratch {
+    worklist: Worklist,
+    // Candidates of the cells of the constraint pruning, from before it does
+    masks: Vec<u32>,
+    // Cells as they lost candidates, repeats included
+    path: Vec<usize>,
+    // Decision levels the candidates of the constraint pruning follow from when
+    // backjumping, those of the contradiction after one
+    conflict: Levels,
+    // Totals of the sum constraints from their previous prune, by constraint
+    aggregates: Vec<Aggregate>,
+}
+
+impl Scratch {
+    // The candidates changed other than by propagating, fold every cell in again
+    fn invalidate(&mut self) {
+        self.aggregates.iter_mut().for_each(Aggregate::invalidate);
+    }
+}
+
+impl Grid {
+    pub fn new(size: BoardSize, variants: Variants) -> Self {
+        Self::build(size, variants, vec![None; size.cell_count()]).0
+    }
+
+    // Puzzle starting from given digits, fails if they break a constraint
+    pub fn with_givens(size: BoardSize, variants: Variants, givens: Vec<Option<u8>>) -> Result<Self, Contradiction> {
+        let (grid, consistent) = Self::build(size, variants, givens);
+        consistent.map(|()| grid)
+    }
+
+    // Grid along with whether the rules and givens are consistent before any collapse
+    fn build(size: BoardSize, variants: Variants, givens: Vec<Option<u8>>) -> (Self, Result<(), Contradiction>) {
+        let mut fresh = vec![Cell::new(size.side()); size.cell_count()];
+        for (cell, given) in fresh.iter_mut().zip(&givens) {
+            if let Some(value) = given {
+                cell.possible_values = vec![*value];
+            }
+        }
+        let units = UnitTables::get(size, variants.regions.as_deref());
+        let mut grid = Self {
+            size,
+            variants,
+            cells: fresh.clone(),
+            eliminations: 0,
+            heuristic: Heuristic::default(),
+            backjumping: false,
+            units,
+            rules: Rc::default(),
+            rng: ChaCha12Rng::from_entropy(),
+            collapses: Vec::new(),
+            last_collapse: None,
+            decisions: Vec::new(),
+            reasons: vec![Levels::default(); size.cell_count()],
+            observers: Vec::new(),
+            next_observer: 0,
+            scratch: Scratch::default(),
+        };
+
+        let constraints = grid.build_constraints();
+        let mut watchers = vec![Vec::new(); size.cell_count()];
+        for (id, constraint) in constraints.iter().enumerate() {
+            for &idx in constraint.affected_cells() {
+                watchers[idx].push(id);
+            }
+        }
+        let all = 0..constraints.len();
+        let builtin = constraints.len() - grid.variants.constraints.len();
+        grid.rules = Rc::new(Rules { constraints, builtin, watchers, givens, start: fresh });
+
+        // Constraints can restrict candidates before anything is collapsed
+        let consistent = grid.apply_constraints(all);
+        if consistent.is_ok() {
+            Rc::get_mut(&mut grid.rules).unwrap().start = grid.cells.clone();
+        }
+        grid.reset();
+        (grid, consistent)
+    }
+
+    // Have the observer told of every following step, see `events`
+    pub fn subscribe(&mut self, observer: Observer) -> ObserverId {
+        let id = ObserverId(self.next_observer);
+        self.next_observer += 1;
+        self.observers.push((id, observer));
+        id
+    }
+
+    // No longer tell the observer, dropping it
+    pub fn unsubscribe(&mut self, id: ObserverId) {
+        self.observers.retain(|(observer, _)| *observer != id);
+    }
+
+    fn notify(&mut self, event: Event) {
+        for (_, observer) in &mut self.observers {
+            observer(&event);
+        }
+    }
+
+    // Make the following solves reproducible
+    pub fn seed(&mut self, seed: u64) {
+        self.rng = ChaCha12Rng::seed_from_u64(seed);
+    }
+
+    pub fn givens(&self) -> &[Option<u8>] {
+        &self.rules.givens
+    }
+
+    pub fn is_given(&self, idx: usize) -> bool {
+        self.rules.givens[idx].is_some()
+    }
+
+    pub fn reset(&mut self) {
+        self.cells.clone_from(&self.rules.start);
+        self.eliminations = 0;
+        self.collapses.clear();
+        self.last_collapse = None;
+        self.decisions.clear();
+        self.reasons.iter_mut().for_each(Levels::clear);
+        self.scratch.invalidate();
+    }
+
+    // Switch to other rules keeping the progress made: the collapses are replayed in order
+    // over the candidates the new rules leave, dropping those they no longer allow, which
+    // are returned. Fails, leaving the grid unchanged, if the givens break the new rules
+    pub fn set_variants(&mut self, variants: Variants) -> Result<Vec<usize>, Contradiction> {
+        self.rebuild(variants, self.rules.givens.clone())
+    }
+
+    // Place or clear a given, keeping the progress made like `set_variants`
+    pub fn set_given(&mut self, idx: usize, value: Option<u8>) -> Result<Vec<usize>, Contradiction> {
+        let mut givens = self.rules.givens.clone();
+        givens[idx] = value;
+        self.rebuild(self.variants.clone(), givens)
+    }
+
+    fn rebuild(&mut self, variants: Variants, givens: Vec<Option<u8>>) -> Result<Vec<usize>, Contradiction> {
+        let (mut grid, consistent) = Self::build(self.size, variants, givens);
+        consistent?;
+
+        let mut dropped = Vec::new();
+        let rules = Rc::clone(&grid.rules);
+        for &(idx, value) in self.collapses.iter().filter(|(idx, _)| rules.givens[*idx].is_none()) {
+            let before = grid.snapshot();
+            if grid.cells[idx].possible_values.contains(&value) {
+                grid.cells[idx].possible_values = vec![value];
+                if grid.apply_watchers(idx).is_ok() {
+                    grid.collapses.push((idx, value));
+                    continue;
+                }
+            }
+            grid.restore(&before);
+            dropped.push(idx);
+        }
+
+        grid.eliminations = self.eliminations;
+        grid.heuristic = self.heuristic;
+        grid.backjumping = self.backjumping;
+        grid.rng = self.rng.clone();
+        grid.observers = std::mem::take(&mut self.observers);
+        grid.next_observer = self.next_observer;
+        *self = grid;
+        Ok(dropped)
+    }
+
+    // Cells collapsed since the last reset and the digit each got, in order
+    pub fn collapses(&self) -> &[(usize, u8)] {
+        &self.collapses
+    }
+
+    // Cell collapsed by the latest step
+    pub fn last_collapse(&self) -> Option<usize> {
+        self.last_collapse
+    }
+
+    pub fn snapshot(&self) -> GridSnapshot {
+        GridSnapshot::new(self.size, &self.cells, self.eliminations, &self.collapses, self.last_collapse, &self.rng)
+    }
+
+    // Back to the state a snapshot of this grid, or one with the same rules, was taken
+    // in, the following steps collapsing the same cells again
+    pub fn restore(&mut self, snapshot: &GridSnapshot) {
+        snapshot.restore_cells(&mut self.cells);
+        self.eliminations = snapshot.eliminations;
+        self.collapses = snapshot.collapses.to_vec();
+        self.last_collapse = snapshot.last_collapse;
+        self.rng = snapshot.rng.clone();
+        self.scratch.invalidate();
+    }
+
+    // FNV-1a hash of the candidates, the same on every machine and build, to tell solver
+    // states apart in logs
+    pub fn fingerprint(&self) -> u64 {
+        self.cells.iter().flat_map(|cell| cell.mask().to_le_bytes()).fold(0xcbf29ce484222325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        })
+    }
+
+    pub fn is_resolve(&self) -> bool {
+        !self.cells.iter().any(|c| c.possible_values.len() > 1)
+    }
+
+    // Every cell keeps a candidate and the digits placed so far break no constraint
+    pub fn is_valid(&self) -> bool {
+        let values: Vec<Option<u8>> = self.cells
+            .iter()
+            .map(|cell| match cell.possible_values[..] {
+                [value] => Some(value),
+                _ => None,
+            })
+            .collect();
+        self.cells.iter().all(|cell| !cell.possible_values.is_empty()) && !self.conflicts(&values).contains(&true)
+    }
+
+    // A finished solve keeping to every rule and given
+    pub fn is_complete_and_valid(&self) -> bool {
+        self.is_resolve()
+            && self.is_valid()
+            && self.givens().iter().zip(&self.cells).all(|(given, cell)| given.is_none_or(|value| cell.possible_values == [value]))
+    }
+
+    fn get_lowest_entropy_cell_idx(&mut self) -> usize {
+        let min = self.cells
+            .iter()
+            .fold(self.size.side(), |min, c| {
+                if c.possible_values.len() == 1 {
+                    min
+                } else {
+                    c.possible_values.len().min(min)
+                }
+            });
+
+        self.cells
+            .iter()
+            .enumerate()
+            .filter_map(|(i, c)|
+                if c.possible_values.len() == min {
+                    Some(i)
+                } else {
+                    None
+                })
+                .choose(&mut self.rng)
+                .unwrap()
+    }
+
+    fn pick_cell_idx(&mut self) -> usize {
+        let mut open = (0..self.cells.len()).filter(|&idx| self.cells[idx].possible_values.len() > 1);
+        match self.heuristic {
+            Heuristic::MinEntropy => self.get_lowest_entropy_cell_idx(),
+            Heuristic::Scan => open.next().unwrap(),
+            Heuristic::Random => open.choose(&mut self.rng).unwrap(),
+        }
+    }
+
+    // Collapse the cell the heuristic picks and propagate the result through the constraints.
+    // Errs on a contradiction, the solve having to start over, unless backjumping gets
+    // past it. Backjumping waits for the next reset when collapses were made without it
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "collapse", level = "trace", skip_all, fields(cell, value)))]
+    pub fn step(&mut self) -> Result<(), Contradiction> {
+        let cell_idx = self.pick_cell_idx();
+        let tracked = self.backjumping && self.decisions.len() == self.collapses.len();
+        let decided = tracked.then(|| (self.snapshot(), self.reasons.clone()));
+        let value = self.cells[cell_idx].collapse(&mut self.rng);
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("cell", cell_idx).record("value", value);
+        if let Some((before, reasons)) = decided {
+            self.reasons[cell_idx] = Levels::single(self.decisions.len());
+            self.decisions.push(Decision { before, reasons, idx: cell_idx, value });
+        }
+        self.collapses.push((cell_idx, value));
+        self.last_collapse = Some(cell_idx);
+        // Only kept for the observers to compare against
+        let before = (!self.observers.is_empty()).then(|| self.cells.clone());
+        let result = self.apply_watchers(cell_idx).map_err(|mut contradiction| {
+            contradiction.path.insert(0, cell_idx);
+            contradiction
+        });
+        let (result, ruled_out) = match result {
+            Err(contradiction) if tracked => match self.backjump(contradiction) {
+                Ok(ruled_out) => (Ok(()), Some(ruled_out)),
+                Err(contradiction) => (Err(contradiction), None),
+            },
+            result => (result, None),
+        };
+        if let Some(before) = before {
+            let mut events = vec![Event::CellCollapsed { idx: cell_idx, value }];
+            match &ruled_out {
+                // Candidates came back, only the digits ruled out are worth telling
+                Some(ruled_out) => {
+                    events.push(Event::Contradiction { idx: cell_idx });
+                    events.extend(ruled_out.iter().map(|&(idx, value)| Event::Backjump { idx, value }));
+                }
+                None => {
+                    for (idx, (before, after)) in before.iter().zip(&self.cells).enumerate() {
+                        let eliminated = before.possible_values.iter().filter(|value| !after.possible_values.contains(value));
+                        events.extend(eliminated.map(|&value| Event::CandidateEliminated { idx, value }));
+                    }
+                }
+            }
+            match result {
+                Err(_) => events.push(Event::Contradiction { idx: cell_idx }),
+                Ok(()) if self.is_resolve() => events.push(Event::Solved),
+                Ok(()) => {}
+            }
+            events.into_iter().for_each(|event| self.notify(event));
+        }
+        #[cfg(feature = "audit")]
+        if result.is_ok() {
+            if let Err(err) = self.audit() {
+                let coord = Coord::from_index(cell_idx, self.size.side());
+                panic!("Audit failed after collapsing {}: {}", coord, err);
+            }
+        }
+        result
+    }
+
+    // Conflict-directed backjumping: the contradiction follows from the collapses behind
+    // the candidates it rests on, the latest of which took a digit it can't have. Undoes
+    // the collapses made since the one before that, which played no part, and rules the
+    // digit out there, as following from the others. Ruling it out can fail in turn,
+    // going further back. Errs once no collapse is behind the contradiction. Returns the
+    // digits ruled out, in order
+    fn backjump(&mut self, mut contradiction: Contradiction) -> Result<Vec<(usize, u8)>, Contradiction> {
+        // Work done and random draws carry on from where the solve got
+        let (mut eliminations, rng) = (self.eliminations, self.rng.clone());
+        let mut ruled_out = Vec::new();
+        let result = loop {
+            let mut conflict = std::mem::take(&mut self.scratch.conflict);
+            let Some(level) = conflict.last() else {
+                break Err(contradiction);
+            };
+            conflict.remove(level);
+            let (idx, value) = (self.decisions[level].idx, self.decisions[level].value);
+            let back = conflict.last().map_or(0, |level| level + 1);
+            let Decision { before, reasons, .. } = &self.decisions[back];
+            let (before, reasons) = (before.clone(), reasons.clone());
+            self.restore(&before);
+            self.reasons = reasons;
+            self.eliminations = eliminations;
+            self.decisions.truncate(back);
+            self.last_collapse = None;
+            // It still has the digit along with others, the collapse having had a choice
+            self.cells[idx].possible_values.retain(|&v| v != value);
+            self.reasons[idx].union(&conflict);
+            ruled_out.push((idx, value));
+            let result = self.apply_watchers(idx);
+            eliminations = self.eliminations;
+            match result {
+                Ok(()) => break Ok(ruled_out),
+                Err(next) => contradiction = next,
+            }
+        };
+        self.rng = rng;
+        result
+    }
+
+    // Solutions left from the current candidates, counted up to the limit. Searches depth
+    // first from the cells with the fewest candidates and leaves the grid as it was
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip(self)))]
+    pub fn count_solutions(&mut self, limit: usize) -> usize {
+        let saved = self.snapshot();
+        let count = self.search(limit);
+        self.restore(&saved);
+        count
+    }
+
+    // First solution found the same way, digits in cell order, the grid left as it was
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn solution(&mut self) -> Option<Vec<u8>> {
+        let saved = self.snapshot();
+        let solution = self.search_first();
+        self.restore(&saved);
+        solution
+    }
+
+    fn search_first(&mut self) -> Option<Vec<u8>> {
+        let open = (0..self.cells.len()).filter(|&idx| self.cells[idx].possible_values.len() > 1);
+        let Some(idx) = open.min_by_key(|&idx| self.cells[idx].possible_values.len()) else {
+            return Some(self.cells.iter().map(|cell| cell.possible_values[0]).collect());
+        };
+        for value in self.cells[idx].possible_values.clone() {
+            let before = self.snapshot();
+            self.cells[idx].possible_values = vec![value];
+            let solution = match self.apply_watchers(idx) {
+                Ok(()) => self.search_first(),
+                Err(_) => None,
+            };
+            self.restore(&before);
+            if solution.is_some() {
+                return solution;
+            }
+        }
+        None
+    }
+
+    fn search(&mut self, limit: usize) -> usize {
+        let open = (0..self.cells.len()).filter(|&idx| self.cells[idx].possible_values.len() > 1);
+        let Some(idx) = open.min_by_key(|&idx| self.cells[idx].possible_values.len()) else {
+            return 1;
+        };
+        let mut count = 0;
+        for value in self.cells[idx].possible_values.clone() {
+            let before = self.snapshot();
+            self.cells[idx].possible_values = vec![value];
+            if self.apply_watchers(idx).is_ok() {
+                count += self.search(limit - count);
+            }
+            self.restore(&before);
+            if count >= limit {
+                break;
+            }
+        }
+        count
+    }
+
+    // Every constraint is at its fixpoint: pruning again neither removes candidates nor
+    // finds a contradiction, so candidates agree with the placed digits everywhere
+    #[cfg(feature = "audit")]
+    pub fn audit(&self) -> Result<(), String> {
+        let side = self.size.side();
+        for constraint in &self.rules.constraints {
+            let describe = || match constraint.rule(side) {
+                rule if rule.is_empty() => {
+                    format!("distinct {}", format_cells(constraint.affected_cells(), side))
+                }
+                rule => rule,
+            };
+            let mut cells = self.cells.clone();
+            match constraint.prune(&mut cells) {
+                Err(()) => return Err(format!("'{}' is contradicted", describe())),
+                Ok(_) if cells != self.cells => {
+                    return Err(format!("'{}' can still prune", describe()));
+                }
+                Ok(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    // Groups of cells holding every digit once: rows, columns, boxes or regions, and the
+    // diagonals and windows of the variants
+    pub fn houses(&self) -> Vec<&[usize]> {
+        let side = self.size.side();
+        self.rules.constraints
+            .iter()
+            .filter(|constraint| constraint.affected_cells().len() == side && constraint.rule(side).is_empty())
+            .map(|constraint| constraint.affected_cells())
+            .collect()
+    }
+
+    // Digits each constraint still allows in every cell, from the digit of each filled cell,
+    // the way players note candidates: every constraint prunes on its own, none building on
+    // what the others removed. Constraints the filled cells already break are left out
+    pub fn pencil_marks(&self, values: &[Option<u8>]) -> Vec<Vec<u8>> {
+        let unknown = Cell::new(self.size.side());
+        let cells: Vec<Cell> = values
+            .iter()
+            .map(|value| match value {
+                Some(value) => Cell { possible_values: vec![*value] },
+                None => unknown.clone(),
+            })
+            .collect();
+        let mut marks: Vec<Vec<u8>> = cells.iter().map(|cell| cell.possible_values.clone()).collect();
+        for constraint in &self.rules.constraints {
+            let mut pruned = cells.clone();
+            if constraint.prune(&mut pruned).is_err() {
+                continue;
+            }
+            for &idx in constraint.affected_cells() {
+                marks[idx].retain(|value| pruned[idx].possible_values.contains(value));
+            }
+        }
+        marks
+    }
+
+    // Filled cells breaking a constraint, from the digit of each filled cell: those wrong on
+    // their own, those clashing two by two, or every filled cell of a constraint only broken
+    // as a whole, like a cage adding up to the wrong sum
+    pub fn conflicts(&self, values: &[Option<u8>]) -> Vec<bool> {
+        let unknown = Cell::new(self.size.side());
+        let breaks = |constraint: &Rc<dyn Constraint>, filled: &[usize]| {
+            let mut cells = vec![unknown.clone(); values.len()];
+            for &idx in filled {
+                cells[idx] = Cell { possible_values: vec![values[idx].unwrap()] };
+            }
+            constraint.prune(&mut cells).is_err()
+        };
+
+        let mut conflicts = vec![false; values.len()];
+        for constraint in &self.rules.constraints {
+            let mut filled: Vec<usize> = constraint.affected_cells()
+                .iter()
+                .copied()
+                .filter(|&idx| values[idx].is_some())
+                .collect();
+            if filled.is_empty() || !breaks(constraint, &filled) {
+                continue;
+            }
+
+            let mut found = false;
+            filled.retain(|&idx| {
+                let wrong = breaks(constraint, &[idx]);
+                conflicts[idx] |= wrong;
+                found |= wrong;
+                !wrong
+            });
+            for (i, &a) in filled.iter().enumerate() {
+                for &b in &filled[i + 1..] {
+                    if breaks(constraint, &[a, b]) {
+                        conflicts[a] = true;
+                        conflicts[b] = true;
+                        found = true;
+                    }
+                }
+            }
+            if !found {
+                for &idx in &filled {
+                    conflicts[idx] = true;
+                }
+            }
+        }
+        conflicts
+    }
+
+    // Houses, extra houses and pairs of the flag variants, then the variant constraints
+    fn build_constraints(&self) -> Vec<Rc<dyn Constraint>> {
+        let side = self.size.side();
+        let mut houses: Vec<Vec<usize>> = self.units().map(|house| house.indices().to_vec()).collect();
+        if self.variants.diagonal {
+            houses.push((0..side).map(|i| i * side + i).collect());
+            houses.push((0..side).map(|i| i * side + side - 1 - i).collect());
+        }
+        if self.variants.windoku {
+            houses.extend(self.group_cells(|idx| self.window_of(idx)));
+        }
+        if self.variants.disjoint_groups {
+            let BoardSize { box_width, box_height } = self.size;
+            houses.extend(self.group_cells(|idx| {
+                let coord = Coord::from_index(idx, side);
+                Some((coord.row % box_height, coord.col % box_width))
+            }));
+        }
+
+        let mut constraints: Vec<Rc<dyn Constraint>> = houses
+            .into_iter()
+            .map(|cells| Rc::new(Distinct::new(cells)) as Rc<dyn Constraint>)
+            .collect();
+        for idx in 0..self.size.cell_count() {
+            let after = |peer: &usize| *peer > idx;
+            if self.variants.anti_knight {
+                for peer in self.iter_knight_moves(idx).filter(after) {
+                    constraints.push(Rc::new(Distinct::new(vec![idx, peer])));
+                }
+            }
+            if self.variants.anti_king {
+                for peer in self.iter_diagonal_neighbours(idx).filter(after) {
+                    constraints.push(Rc::new(Distinct::new(vec![idx, peer])));
+                }
+            }
+            if self.variants.non_consecutive {
+                for peer in self.iter_orthogonal_neighbours(idx).filter(after) {
+                    constraints.push(Rc::new(NonConsecutive::new(idx, peer)));
+                }
+            }
+        }
+        constraints.extend(self.variants.constraints.iter().cloned());
+        constraints
+    }
+
+    // Prune with the given constraints, queuing again those watching cells that lost
+    // candidates. Built-in rules go first so the costly variant constraints only run
+    // once the cheap eliminations are done.
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "propagate", level = "trace", skip_all))]
+    fn apply_constraints(&mut self, ids: impl IntoIterator<Item = usize>) -> Result<(), Contradiction> {
+        let rules = Rc::clone(&self.rules);
+        let Scratch { mut worklist, mut masks, mut path, mut conflict, mut aggregates } = std::mem::take(&mut self.scratch);
+        worklist.prepare(&rules);
+        aggregates.resize_with(rules.constraints.len(), Aggregate::default);
+        ids.into_iter().for_each(|id| worklist.push(id));
+        path.clear();
+
+        let mut result = Ok(());
+        while let Some(id) = worklist.pop() {
+            #[cfg(feature = "tracing")]
+            let _span = tracing::trace_span!("prune", id, builtin = id < rules.builtin).entered();
+            let constraint = &rules.constraints[id];
+            masks.clear();
+            masks.extend(constraint.affected_cells().iter().map(|&idx| self.cells[idx].mask()));
+            if self.backjumping {
+                conflict.clear();
+                constraint.affected_cells().iter().for_each(|&idx| conflict.union(&self.reasons[idx]));
+            }
+            let changed = match constraint.prune_with(&mut self.cells, &mut aggregates[id]) {
+                Ok(changed) => changed,
+                Err(()) => {
+                    result = Err(self.contradiction(constraint.as_ref(), &masks, path.clone()));
+                    break;
+                }
+            };
+            if changed {
+                for (&idx, mask) in constraint.affected_cells().iter().zip(&masks) {
+                    let (count, remaining) = (mask.count_ones() as usize, self.cells[idx].possible_values.len());
+                    if remaining < count {
+                        self.eliminations += count - remaining;
+                        path.push(idx);
+                        if self.backjumping {
+                            self.reasons[idx].union(&conflict);
+                        }
+                        for &watcher in &rules.watchers[idx] {
+                            worklist.push(watcher);
+                        }
+                    }
+                }
+            }
+        }
+        self.scratch = Scratch { worklist, masks, path, conflict, aggregates };
+        result
+    }
+
+    // Prune with the constraints watching the cell, after it changed
+    fn apply_watchers(&mut self, idx: usize) -> Result<(), Contradiction> {
+        let rules = Rc::clone(&self.rules);
+        self.apply_constraints(rules.watchers[idx].iter().copied())
+    }
+
+    // The constraint failing from the candidates of its cells before it pruned
+    fn contradiction(&self, constraint: &dyn Constraint, masks: &[u32], mut path: Vec<usize>) -> Contradiction {
+        let emptied = constraint.affected_cells().iter().zip(masks).find(|(&idx, _)| self.cells[idx].possible_values.is_empty());
+        let rule = constraint.rule(self.size.side());
+        let mut seen = vec![false; self.cells.len()];
+        path.retain(|&idx| !std::mem::replace(&mut seen[idx], true));
+        Contradiction {
+            cell: emptied.map(|(&idx, _)| idx),
+            removed: emptied.map_or(Vec::new(), |(_, &mask)| (1..=self.size.side() as u8).filter(|v| mask & 1 << v != 0).collect()),
+            rule: (!rule.is_empty()).then_some(rule),
+            path,
+        }
+    }
+
+    pub fn cell(&self, idx: usize) -> CellView<'_> {
+        units::view(&self.cells, self.size.side(), idx)
+    }
+
+    pub fn row(&self, row: usize) -> House<'_> {
+        House::new(HouseKind::Row(row), self.size.side(), &self.cells, &self.units.rows[row])
+    }
+
+    pub fn col(&self, col: usize) -> House<'_> {
+        House::new(HouseKind::Col(col), self.size.side(), &self.cells, &self.units.cols[col])
+    }
+
+    // Box numbered as `Coord::box_index`, or jigsaw region
+    pub fn box_at(&self, region: usize) -> House<'_> {
+        House::new(HouseKind::Box(region), self.size.side(), &self.cells, &self.units.boxes[region])
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = House<'_>> {
+        (0..self.size.side()).map(|row| self.row(row))
+    }
+
+    pub fn cols(&self) -> impl Iterator<Item = House<'_>> {
+        (0..self.size.side()).map(|col| self.col(col))
+    }
+
+    pub fn boxes(&self) -> impl Iterator<Item = House<'_>> {
+        (0..self.size.side()).map(|region| self.box_at(region))
+    }
+
+    // Rows, columns and boxes
+    pub fn units(&self) -> impl Iterator<Item = House<'_>> {
+        self.rows().chain(self.cols()).chain(self.boxes())
+    }
+
+    // Other cells sharing a row, column or box with idx
+    pub fn peers(&self, idx: usize) -> impl Iterator<Item = CellView<'_>> {
+        self.units.peers[idx].iter().map(move |&peer| self.cell(peer))
+    }
+
+    // Jigsaw region if any, box otherwise
+    pub fn region_of(&self, idx: usize) -> usize {
+        if let Some(regions) = &self.variants.regions {
+            return regions[idx];
+        }
+        Coord::from_index(idx, self.size.side()).box_index(self.size)
+    }
+
+    // Windows are box shaped regions one cell in from the boxes, a box width/height apart
+    fn window_of(&self, idx: usize) -> Option<(usize, usize)> {
+        let BoardSize { box_width, box_height } = self.size;
+        let side = self.size.side();
+        let window_start = |pos: usize, len: usize| {
+            let start = 1 + (pos.checked_sub(1)? / (len + 1)) * (len + 1);
+            (pos < start + len && start + len < side).then_some(start)
+        };
+        Some((window_start(idx / side, box_height)?, window_start(idx % side, box_width)?))
+    }
+
+    // Cells grouped by key, leaving out those without one
+    fn group_cells<K: PartialEq>(&self, key: impl Fn(usize) -> Option<K>) -> Vec<Vec<usize>> {
+        let mut groups: Vec<(K, Vec<usize>)> = Vec::new();
+        for idx in 0..self.size.cell_count() {
+            let Some(key) = key(idx) else { continue };
+            match groups.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, cells)) => cells.push(idx),
+                None => groups.push((key, vec![idx])),
+            }
+        }
+        groups.into_iter().map(|(_, cells)| cells).collect()
+    }
+
+    fn iter_knight_moves(&self, idx: usize) -> impl Iterator<Item = usize> {
+        self.iter_offsets(idx, &[(-2, -1), (-2, 1), (-1, -2), (-1, 2), (1, -2), (1, 2), (2, -1), (2, 1)])
+    }
+
+    // Orthogonal neighbours already share a row or column, so a king's move only adds these
+    fn iter_diagonal_neighbours(&self, idx: usize) -> impl Iterator<Item = usize> {
+        self.iter_offsets(idx, &[(-1, -1), (-1, 1), (1, -1), (1, 1)])
+    }
+
+    fn iter_orthogonal_neighbours(&self, idx: usize) -> impl Iterator<Item = usize> {
+        self.iter_offsets(idx, &[(-1, 0), (1, 0), (0, -1), (0, 1)])
+    }
+
+    fn iter_offsets(&self, idx: usize, offsets: &'static [(isize, isize)]) -> impl Iterator<Item = usize> {
+        let side = self.size.side() as isize;
+        let (row, col) = ((idx as isize) / side, (idx as isize) % side);
+        offsets
+            .iter()
+            .map(move |(dr, dc)| (row + dr, col + dc))
+            .filter(move |&(r, c)| (0..side).contains(&r) && (0..side).contains(&c))
+            .map(move |(r, c)| (r * side + c) as usize)
+    }
+
+    pub fn margins(&self) -> Margins {
+        self.variants.constraints
+            .iter()
+            .fold(Margins::default(), |margins, c| margins.max(c.margins()))
+    }
+
+    pub fn draw(&self, layout: &Layout) {
+        self.draw_cells(layout, &self.cells);
+    }
+
+    // Board showing other candidates than the current ones, like those of an animation frame
+    pub fn draw_cells(&self, layout: &Layout, cells: &[Cell]) {
+        self.draw_board(layout);
+        self.draw_values(layout, cells);
+    }
+
+    // Lines, regions and variant markings, without any digit
+    pub fn draw_board(&self, layout: &Layout) {
+        let board = &theme::current().board;
+        // Hairlines stay visible on small boards
+        let small_thickness = (board.small_lines_thickness * layout.scale()).max(1.);
+        let big_thickness = (board.big_lines_thickness * layout.scale()).max(1.);
+        let side = self.size.side();
+        let cell_size = layout.cell_size;
+        let grid_position = (layout.origin.x, layout.origin.y);
+        let grid_extent = layout.extent();
+
+        if self.variants.windoku {
+            for idx in (0..self.size.cell_count()).filter(|&idx| self.window_of(idx).is_some()) {
+                let position = layout.cell_position(idx);
+                draw_rectangle(position.x, position.y, cell_size, cell_size, board.window_color);
+            }
+        }
+
+        if self.variants.diagonal {
+            let (x, y) = grid_position;
+            draw_line(x, y, x + grid_extent, y + grid_extent, small_thickness, board.diagonal_color);
+            draw_line(x + grid_extent, y, x, y + grid_extent, small_thickness, board.diagonal_color);
+        }
+
+        let jigsaw = self.variants.regions.is_some();
+        for i in 0..=side {
+            let thickness = |box_len: usize| if i % box_len == 0 && (!jigsaw || i % side == 0) {
+                big_thickness
+            } else {
+                small_thickness
+            };
+            draw_line(
+                grid_position.0,
+                grid_position.1 + i as f32 * cell_size,
+                grid_position.0 + grid_extent,
+                grid_position.1 + i as f32 * cell_size,
+                thickness(self.size.box_height),
+                board.grid_color);
+            draw_line(
+                grid_position.0 + i as f32 * cell_size,
+                grid_position.1,
+                grid_position.0 + i as f32 * cell_size,
+                grid_position.1 + grid_extent,
+                thickness(self.size.box_width),
+                board.grid_color
+            );
+        }
+
+        if jigsaw {
+            for idx in 0..self.size.cell_count() {
+                let position = layout.cell_position(idx);
+                if idx % side != side - 1 && self.region_of(idx) != self.region_of(idx + 1) {
+                    let x = position.x + cell_size;
+                    draw_line(x, position.y, x, position.y + cell_size, big_thickness, board.grid_color);
+                }
+                if idx / side != side - 1 && self.region_of(idx) != self.region_of(idx + side) {
+                    let y = position.y + cell_size;
+                    draw_line(position.x, y, position.x + cell_size, y, big_thickness, board.grid_color);
+                }
+            }
+        }
+
+        for constraint in &self.variants.constraints {
+            constraint.draw(layout);
+        }
+    }
+
+    fn draw_values(&self, layout: &Layout, cells: &[Cell]) {
+        let digits = &theme::current().digits;
+        for (idx, cell) in cells.iter().enumerate() {
+            let values = &cell.possible_values;
+            if self.is_given(idx) {
+                self.draw_given(layout, idx);
+            } else if values.len() == 1 {
+                self.draw_digit(layout, idx, values[0], digits.solved_color);
+            } else {
+                for (i, &v) in values.iter().enumerate() {
+                    self.draw_small_digit(layout, idx, i, v, digits.candidate_color);
+                }
+            }
+        }
+    }
+
+    // Pencil marks, each digit in its own place whichever others are left
+    pub fn draw_marks(&self, layout: &Layout, idx: usize, values: &[u8]) {
+        for &v in values {
+            self.draw_mark(layout, idx, v, theme::current().digits.candidate_color);
+        }
+    }
+
+    pub fn draw_mark(&self, layout: &Layout, idx: usize, value: u8, color: Color) {
+        self.draw_small_digit(layout, idx, value as usize - 1, value, color);
+    }
+
+    // Small digits are laid out like the boxes: box_width columns, box_height rows
+    fn draw_small_digit(&self, layout: &Layout, idx: usize, place: usize, value: u8, color: Color) {
+        let digits = &theme::current().digits;
+        let scale = layout.scale();
+        let columns = self.size.box_width;
+        let small_step = layout.cell_size / (columns as f32 + 0.5);
+        let small_font_size = digits.small_font_size * small_step / (CELL_SIZE / 3.5);
+        let cell_position = layout.cell_position(idx);
+        text::draw(
+            &value.to_string(),
+            cell_position.x + (place % columns) as f32 * small_step + SMALL_NUM_OFFSET.0 * scale,
+            cell_position.y + (place / columns) as f32 * small_step + SMALL_NUM_OFFSET.1 * scale,
+            small_font_size,
+            color);
+    }
+
+    pub fn draw_digit(&self, layout: &Layout, idx: usize, value: u8, color: Color) {
+        self.draw_big_text(layout, idx, &value.to_string(), color, 0.);
+    }
+
+    // Givens stand out from the solved digits, brighter and bolder
+    pub fn draw_given(&self, layout: &Layout, idx: usize) {
+        if let Some(value) = self.rules.givens[idx] {
+            let digits = &theme::current().digits;
+            self.draw_big_text(layout, idx, &value.to_string(), digits.given_color, digits.given_weight);
+        }
+    }
+
+    // Text centered in a cell, drawn again `weight` pixels to the right to thicken it
+    fn draw_big_text(&self, layout: &Layout, idx: usize, text: &str, color: Color, weight: f32) {
+        let position = layout.cell_position(idx);
+        let font_size = theme::current().digits.big_font_size * layout.scale();
+        let width = text::measure(text, font_size).width;
+        let x = position.x + (layout.cell_size - width) / 2.;
+        let y = position.y + BIG_NUM_BASELINE * layout.scale();
+        text::draw(text, x, y, font_size, color);
+        if weight > 0. {
+            text::draw(text, x + weight * layout.scale(), y, font_size, color);
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use ::rand::seq::SliceRandom;
+    use ::rand::Rng;
+
+    use std::collections::BTreeSet;
+
+    use super::*;
+    use crate::events;
+    use crate::puzzle;
+    use crate::state;
+    use crate::variant;
+
+    const SEEDS: u64 = 5;
+    // Random partial grids propagated for each variant
+    const CASES: usize = 200;
+    const MAX_RESTARTS: usize = 10_000;
+    const VARIANTS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/variants");
+
+    // Steps until solved, checking the grid stays valid along the way
+    fn solve(grid: &mut Grid, seed: u64) {
+        grid.seed(seed);
+        grid.reset();
+        let mut restarts = 0;
+        while !grid.is_resolve() {
+            if grid.step().is_err() {
+                restarts += 1;
+                assert!(restarts < MAX_RESTARTS, "no solution with seed {}", seed);
+                grid.reset();
+                continue;
+            }
+            assert!(grid.is_valid(), "invalid grid after a step with seed {}", seed);
+        }
+        assert!(grid.is_complete_and_valid(), "invalid solution with seed {}", seed);
+    }
+
+    #[test]
+    fn solves_of_every_size_are_valid() {
+        for side in [4, 6, 9, 12] {
+            let mut grid = Grid::new(BoardSize::from_side(side).unwrap(), Variants::default());
+            (0..SEEDS).for_each(|seed| solve(&mut grid, seed));
+        }
+    }
+
+    #[test]
+    fn solves_of_every_heuristic_are_valid() {
+        for heuristic in Heuristic::ALL {
+            let mut grid = Grid::new(BoardSize::default(), Variants::default());
+            grid.heuristic = heuristic;
+            (0..SEEDS).for_each(|seed| solve(&mut grid, seed));
+        }
+    }
+
+    // Audited after every step with the audit feature, restores included
+    #[test]
+    fn backjumping_solves_are_valid() {
+        let killer = variant::load(&format!("{}/killer.txt", VARIANTS), BoardSize::default()).unwrap();
+        let grids = [
+            Grid::new(BoardSize::default(), Variants::default()),
+            Grid::new(BoardSize::default(), Variants { windoku: true, ..Variants::default() }),
+            Grid::new(BoardSize::default(), killer),
+        ];
+        for mut grid in grids {
+            grid.backjumping = true;
+            (0..SEEDS).for_each(|seed| solve(&mut grid, seed));
+        }
+    }
+
+    // Saved solves keep the decisions to go back to, restored ones backjumping the same
+    #[test]
+    fn restored_solves_backjump_the_same() {
+        let killer = variant::load(&format!("{}/killer.txt", VARIANTS), BoardSize::default()).unwrap();
+        let mut grid = Grid::new(BoardSize::default(), killer);
+        grid.backjumping = true;
+        grid.seed(0);
+        // Saved a few steps after the first backjump, reasons then resting on several decisions
+        let mut after = None;
+        while after.is_none_or(|steps| steps < 3) {
+            let made = grid.collapses().len();
+            grid.step().unwrap();
+            after = after.map(|steps| steps + 1).or((grid.collapses().len() <= made).then_some(0));
+        }
+        assert!(!grid.is_resolve());
+        let saved = state::serialize(&grid);
+        let mut restored = state::parse(&saved).unwrap();
+        restored.backjumping = true;
+        assert_eq!(state::serialize(&restored), saved);
+        while !grid.is_resolve() {
+            assert_eq!(restored.step().is_ok(), grid.step().is_ok());
+            assert_eq!(restored.snapshot().to_cells(), grid.snapshot().to_cells());
+            assert_eq!((&restored.collapses, restored.last_collapse), (&grid.collapses, grid.last_collapse));
+            assert_eq!(restored.eliminations, grid.eliminations);
+        }
+        assert!(restored.is_complete_and_valid());
+    }
+
+    // Every solution left from the candidates
+    fn solutions(grid: &mut Grid) -> Vec<Vec<u8>> {
+        let Some(idx) = (0..grid.cells.len()).find(|&idx| grid.cells[idx].possible_values.len() > 1) else {
+            return vec![grid.cells.iter().map(|cell| cell.possible_values[0]).collect()];
+        };
+        let mut found = Vec::new();
+        for value in grid.cells[idx].possible_values.clone() {
+            let before = grid.snapshot();
+            grid.cells[idx].possible_values = vec![value];
+            if grid.apply_watchers(idx).is_ok() {
+                found.extend(solutions(grid));
+            }
+            grid.restore(&before);
+        }
+        found
+    }
+
+    // Backjumping only rules out digits the collapses left behind rule out, so solves
+    // with it never fail on a puzzle with solutions and meet the same ones as without
+    #[test]
+    fn backjumping_keeps_every_solution() {
+        const RUNS: u64 = 500;
+        let size = BoardSize::default();
+        // The classic puzzle short of its first five givens, and killer cages with the
+        // middle rows their two solutions share
+        let classic = puzzle::parse(".............95....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79", size).unwrap();
+        let killer = variant::load(&format!("{}/killer.txt", VARIANTS), size).unwrap();
+        let middle = puzzle::parse("..................781542639512783496874629153693154278967415382..................", size).unwrap();
+        let grids = [
+            Grid::with_givens(size, Variants::default(), classic).unwrap(),
+            Grid::with_givens(size, killer, middle).unwrap(),
+        ];
+        for mut grid in grids {
+            let all: BTreeSet<Vec<u8>> = solutions(&mut grid).into_iter().collect();
+            assert!(all.len() > 1);
+            assert_eq!(grid.count_solutions(usize::MAX), all.len());
+            let mut met = [BTreeSet::new(), BTreeSet::new()];
+            for backjumping in [false, true] {
+                grid.backjumping = backjumping;
+                for seed in 0..RUNS {
+                    grid.seed(seed);
+                    grid.reset();
+                    while !grid.is_resolve() {
+                        if grid.step().is_err() {
+                            assert!(!backjumping, "backjumping failed with seed {}", seed);
+                            grid.reset();
+                            continue;
+                        }
+                        let agree = |solution: &&Vec<u8>| grid.collapses().iter().all(|&(idx, value)| solution[idx] == value);
+                        for solution in all.iter().filter(agree) {
+                            let kept = grid.cells.iter().zip(solution).all(|(cell, value)| cell.possible_values.contains(value));
+                            assert!(kept, "solution ruled out with seed {}", seed);
+                        }
+                    }
+                    met[backjumping as usize].insert(grid.cells.iter().map(|cell| cell.possible_values[0]).collect());
+                }
+            }
+            assert_eq!(met[0], all);
+            assert_eq!(met[1], all);
+        }
+    }
+
+    #[test]
+    fn solves_of_flag_variants_are_valid() {
+        // Non-consecutive grids take thousands of restarts, too slow to solve here
+        let flags: [fn(&mut Variants); 5] = [
+            |variants| variants.diagonal = true,
+            |variants| variants.windoku = true,
+            |variants| variants.anti_knight = true,
+            |variants| variants.anti_king = true,
+            |variants| variants.disjoint_groups = true,
+        ];
+        for set in flags {
+            let mut variants = Variants::default();
+            set(&mut variants);
+            let mut grid = Grid::new(BoardSize::default(), variants);
+            (0..SEEDS).for_each(|seed| solve(&mut grid, seed));
+        }
+    }
+
+    #[test]
+    fn solves_of_variant_files_are_valid() {
+        // Quick enough to solve with the plain solver
+        for name in ["thermo", "kropki", "arrows", "odd-even", "relations"] {
+            let path = format!("{}/{}.txt", VARIANTS, name);
+            let mut grid = Grid::new(BoardSize::default(), variant::load(&path, BoardSize::default()).unwrap());
+            solve(&mut grid, 0);
+        }
+    }
+
+    // Pruning leaves nothing behind in the shared rules for the next grid or call
+    #[test]
+    fn cage_pencil_marks_are_stable() {
+        let size = BoardSize::default();
+        let grid = Grid::new(size, variant::parse("cage 3 r1c1 r1c2", size).unwrap());
+        let values = vec![None; size.cell_count()];
+        let first = grid.pencil_marks(&values);
+        assert_eq!(first[0], [1, 2]);
+        assert_eq!(grid.pencil_marks(&values), first);
+    }
+
+    #[test]
+    fn solves_keep_the_givens() {
+        let size = BoardSize::default();
+        let givens = puzzle::parse("53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79", size).unwrap();
+        let mut grid = Grid::with_givens(size, Variants::default(), givens).unwrap();
+        (0..SEEDS).for_each(|seed| solve(&mut grid, seed));
+    }
+
+    // Variant rules with candidates to prune, for the propagation cases. New constraints
+    // belong here once they have an example file
+    fn propagation_variants() -> Vec<Variants> {
+        let mut all = vec![
+            Variants::default(),
+            Variants { diagonal: true, anti_king: true, ..Variants::default() },
+            Variants { windoku: true, ..Variants::default() },
+            Variants { non_consecutive: true, ..Variants::default() },
+            Variants { anti_knight: true, disjoint_groups: true, ..Variants::default() },
+        ];
+        for name in ["thermo", "kropki", "arrows", "odd-even", "relations", "killer"] {
+            let path = format!("{}/{}.txt", VARIANTS, name);
+            all.push(variant::load(&path, BoardSize::default()).unwrap());
+        }
+        all
+    }
+
+    // Propagates the candidates with the constraints queued in a random order, checking
+    // candidates only ever shrink, no cell is emptied without a contradiction and the
+    // constraints end at their fixpoint. Returns whether propagation succeeded
+    fn propagate(grid: &mut Grid, rng: &mut ChaCha12Rng) -> bool {
+        let before = grid.cells.clone();
+        let mut ids: Vec<usize> = (0..grid.rules.constraints.len()).collect();
+        ids.shuffle(rng);
+        let result = grid.apply_constraints(ids);
+        for (idx, (before, after)) in before.iter().zip(&grid.cells).enumerate() {
+            assert!(after.mask() & !before.mask() == 0, "candidates of cell {} grew", idx);
+        }
+        if result.is_err() {
+            return false;
+        }
+        assert!(grid.cells.iter().all(|cell| !cell.possible_values.is_empty()), "cell emptied without a contradiction");
+        for constraint in &grid.rules.constraints {
+            let mut cells = grid.cells.clone();
+            assert_eq!(constraint.prune(&mut cells), Ok(false), "{:?} left before its fixpoint", constraint);
+        }
+        true
+    }
+
+    #[test]
+    fn propagation_keeps_its_invariants() {
+        let mut rng = ChaCha12Rng::seed_from_u64(0x5eed);
+        for variants in propagation_variants() {
+            let mut grid = Grid::new(BoardSize::default(), variants);
+            let solution = grid.solution().expect("the variant has a solution");
+            for _ in 0..CASES {
+                // Part of the solution and eliminations it allows must never contradict,
+                // nor lose a digit of the solution
+                grid.reset();
+                let side = grid.size.side() as u8;
+                for (cell, &digit) in grid.cells.iter_mut().zip(&solution) {
+                    match rng.gen_range(0..4) {
+                        0 => cell.possible_values = vec![digit],
+                        1 => {
+                            let removed = rng.gen_range(1..=side);
+                            cell.possible_values.retain(|&value| value == digit || value != removed);
+                        }
+                        _ => {}
+                    }
+                }
+                assert!(propagate(&mut grid, &mut rng), "contradiction on part of a solution");
+                for (idx, cell) in grid.cells.iter().enumerate() {
+                    assert!(cell.possible_values.contains(&solution[idx]), "digit {} of cell {} was eliminated", solution[idx], idx);
+                }
+
+                // Random digits anywhere, contradicting or not
+                grid.reset();
+                for _ in 0..rng.gen_range(1..grid.cells.len()) {
+                    let idx = rng.gen_range(0..grid.cells.len());
+                    grid.cells[idx].possible_values = vec![rng.gen_range(1..=grid.size.side() as u8)];
+                }
+                propagate(&mut grid, &mut rng);
+            }
+        }
+    }
+
+    #[test]
+    fn unsubscribed_observers_are_not_told() {
+        let mut grid = Grid::new(BoardSize::default(), Variants::default());
+        let (kept, kept_events) = events::channel();
+        let (dropped, dropped_events) = events::channel();
+        grid.subscribe(kept);
+        let id = grid.subscribe(dropped);
+        grid.unsubscribe(id);
+        grid.seed(0);
+        grid.step().unwrap();
+        assert!(kept_events.try_iter().count() > 0);
+        assert!(dropped_events.try_recv().is_err());
+        assert_eq!(grid.observers.len(), 1);
+    }
+
+    #[test]
+    fn peers_share_a_house() {
+        let jigsaw = variant::load(&format!("{}/jigsaw.txt", VARIANTS), BoardSize::default()).unwrap();
+        let grids = [
+            Grid::new(BoardSize::default(), Variants::default()),
+            Grid::new(BoardSize::from_side(6).unwrap(), Variants::default()),
+            Grid::new(BoardSize::default(), jigsaw),
+        ];
+        for grid in grids {
+            let side = grid.size.side();
+            for idx in 0..grid.cells.len() {
+                let coord = Coord::from_index(idx, side);
+                let expected: Vec<usize> = (0..grid.cells.len())
+                    .filter(|&peer| {
+                        let other = Coord::from_index(peer, side);
+                        let shares_line = other.row == coord.row || other.col == coord.col;
+                        peer != idx && (shares_line || grid.region_of(peer) == grid.region_of(idx))
+                    })
+                    .collect();
+                let peers: Vec<usize> = grid.peers(idx).map(|peer| peer.idx).collect();
+                assert_eq!(peers, expected, "peers of {}", coord);
+            }
+        }
+        assert_eq!(Grid::new(BoardSize::default(), Variants::default()).peers(0).count(), 20);
+    }
+
+    // Killer cages carry their totals from one prune to the next, restores included
+    #[test]
+    fn restored_snapshots_solve_the_same() {
+        let killer = variant::load(&format!("{}/killer.txt", VARIANTS), BoardSize::default()).unwrap();
+        let grids = [
+            Grid::new(BoardSize::default(), Variants { diagonal: true, ..Variants::default() }),
+            Grid::new(BoardSize::default(), killer),
+        ];
+        let finish = |grid: &mut Grid| {
+            while !grid.is_resolve() {
+                if grid.step().is_err() {
+                    grid.reset();
+                }
+            }
+            (grid.cells.clone(), grid.collapses.clone(), grid.eliminations)
+        };
+        for mut grid in grids {
+            grid.seed(0);
+            for _ in 0..20 {
+                if grid.step().is_err() {
+                    grid.reset();
+                }
+            }
+            let snapshot = grid.snapshot();
+            let first = finish(&mut grid);
+            grid.restore(&snapshot);
+            assert_eq!(grid.snapshot().to_cells(), snapshot.to_cells());
+            assert_eq!(finish(&mut grid), first);
+        }
+    }
+
+    #[test]
+    fn clashes_are_invalid() {
+        let mut grid = Grid::new(BoardSize::default(), Variants::default());
+        assert!(grid.is_valid());
+        grid.cells[0].possible_values = vec![1];
+        grid.cells[1].possible_values = vec![1];
+        assert!(!grid.is_valid());
+        grid.cells[1].possible_values = Vec::new();
+        assert!(!grid.is_valid());
+    }
+}
@@ -0,0 +1,26 @@
+use macroquad::prelude::*;
+
+use sudoku::grid::Grid;
+use sudoku::layout::Layout;
+use sudoku::theme;
+
+
+// Tints the unsolved cells by candidate count, red for the fewest, which are
+// where the lowest entropy collapse happens next, blue for the most
+pub fn draw(grid: &Grid, layout: &Layout) {
+    let style = &theme::current().heatmap;
+    let side = grid.size.side();
+    let size = layout.cell_size;
+    for (idx, cell) in grid.cells.iter().enumerate() {
+        let count = cell.possible_values.len();
+        if count < 2 {
+            continue;
+        }
+        let coldness = (count - 2) as f32 / (side - 2).max(1) as f32;
+        let hue = style.hottest_hue + (style.coldest_hue - style.hottest_hue) * coldness;
+        let mut color = macroquad::color::hsl_to_rgb(hue, 0.8, 0.5);
+        color.a = style.alpha;
+        let p = layout.cell_position(idx);
+        draw_rectangle(p.x, p.y, size, size, color);
+    }
+}
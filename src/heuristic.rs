@@ -0,0 +1,28 @@
+// How the solver picks the cell to collapse at each step, among those with more than
+// one candidate left, ties broken by the generator of the grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Heuristic {
+    // A cell with the fewest candidates, the least likely to lead to a contradiction
+    #[default]
+    MinEntropy,
+    // The first open cell in reading order, like filling the grid by hand
+    Scan,
+    // Any open cell
+    Random,
+}
+
+impl Heuristic {
+    pub const ALL: [Heuristic; 3] = [Heuristic::MinEntropy, Heuristic::Scan, Heuristic::Random];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Heuristic::MinEntropy => "min-entropy",
+            Heuristic::Scan => "scan",
+            Heuristic::Random => "random",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|heuristic| heuristic.name() == name)
+    }
+}
@@ -0,0 +1,75 @@
+use macroquad::prelude::*;
+
+use sudoku::layout::{Layout, Margins};
+use sudoku::{text, theme};
+
+use crate::input;
+
+
+const CLEAR_LABEL: &str = "x";
+// Columns kept right of the board for the hotbar, in cells
+const WIDTH: f32 = 1.25;
+// Space between keys, relative to the key size
+const GAP: f32 = 0.08;
+
+
+// Column of digits right of the board in digit-first play, the digit picked there or
+// typed being placed in every cell clicked after. The last key picks the eraser
+#[derive(Default)]
+pub struct Hotbar {
+    // Some(None) for the eraser
+    pub picked: Option<Option<u8>>,
+}
+
+impl Hotbar {
+    // Room for the hotbar on top of the board's own margins
+    pub fn margins(margins: Margins) -> Margins {
+        Margins { right: margins.right + WIDTH, ..margins }
+    }
+
+    // Digits 1 up to the side then the eraser, top to bottom along the board
+    fn keys(side: usize, layout: &Layout, margins: Margins) -> Vec<Rect> {
+        let key = (layout.extent() / (side + 1) as f32).min(layout.cell_size);
+        let x = layout.origin.x + layout.extent() + (margins.right + WIDTH - 1.) * layout.cell_size;
+        let y = layout.origin.y + (layout.extent() - (side + 1) as f32 * key) / 2.;
+        (0..=side).map(|i| Rect::new(x, y + i as f32 * key, key, key)).collect()
+    }
+
+    // Picks what was typed or clicked, returning whether a key was clicked
+    pub fn update(&mut self, side: usize, layout: &Layout, margins: Margins) -> bool {
+        let typed = input::type_value(self.picked.flatten(), side);
+        if typed != self.picked.flatten() {
+            self.picked = Some(typed);
+        }
+        if !is_mouse_button_pressed(MouseButton::Left) {
+            return false;
+        }
+        let position = mouse_position().into();
+        let Some(i) = Self::keys(side, layout, margins).iter().position(|key| key.contains(position)) else {
+            return false;
+        };
+        self.picked = Some((i < side).then(|| i as u8 + 1));
+        true
+    }
+
+    pub fn draw(&self, side: usize, layout: &Layout, margins: Margins) {
+        let theme = theme::current();
+        let style = &theme.numpad;
+        for (i, key) in Self::keys(side, layout, margins).iter().enumerate() {
+            let gap = GAP * key.w;
+            draw_rectangle(key.x, key.y, key.w, key.h, style.panel_color);
+            draw_rectangle(key.x + gap / 2., key.y + gap / 2., key.w - gap, key.h - gap, style.key_color);
+            let value = (i < side).then(|| i as u8 + 1);
+            if self.picked == Some(value) {
+                let thickness = theme.selection.thickness * layout.scale();
+                draw_rectangle_lines(key.x, key.y, key.w, key.h, thickness, theme.selection.color);
+            }
+            let label = value.map_or(CLEAR_LABEL.to_string(), |value| value.to_string());
+            let font_size = style.font_size * layout.scale() * key.w / layout.cell_size;
+            let dimensions = text::measure(&label, font_size);
+            let x = key.x + (key.w - dimensions.width) / 2.;
+            let y = key.y + (key.h + dimensions.offset_y) / 2.;
+            text::draw(&label, x, y, font_size, style.digit_color);
+        }
+    }
+}
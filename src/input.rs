@@ -0,0 +1,58 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use macroquad::prelude::*;
+
+use crate::keys::{self, Action};
+use crate::language::{tr, Text};
+
+
+static FULLSCREEN: AtomicBool = AtomicBool::new(false);
+static DIGIT_FIRST: AtomicBool = AtomicBool::new(false);
+
+
+// Whether play picks a digit then the cells to place it in, rather than a cell then
+// its digit
+pub fn digit_first() -> bool {
+    DIGIT_FIRST.load(Ordering::Relaxed)
+}
+
+pub fn set_digit_first(digit_first: bool) {
+    DIGIT_FIRST.store(digit_first, Ordering::Relaxed);
+}
+
+
+// Value of a cell after this frame's typing: digits set it, boards past 9 taking two
+// digit values typed one after the other, and Backspace or Delete clear it
+pub fn type_value(value: Option<u8>, side: usize) -> Option<u8> {
+    let mut value = value;
+    while let Some(c) = get_char_pressed() {
+        if let Some(digit) = c.to_digit(10) {
+            let digit = digit as u8;
+            value = match value.map(|value| value * 10 + digit) {
+                Some(combined) if combined as usize <= side => Some(combined),
+                _ if digit >= 1 && digit as usize <= side => Some(digit),
+                _ => value,
+            };
+        }
+    }
+    if is_key_pressed(KeyCode::Backspace) || is_key_pressed(KeyCode::Delete) {
+        value = None;
+    }
+    value
+}
+
+// F11 or Alt+Enter switch fullscreen on and off, layouts following the new screen
+// size from the next frame. Returns whether it was switched
+pub fn toggle_fullscreen() -> bool {
+    let alt = is_key_down(KeyCode::LeftAlt) || is_key_down(KeyCode::RightAlt);
+    if !(keys::pressed(Action::Fullscreen) || (alt && is_key_pressed(KeyCode::Enter))) {
+        return false;
+    }
+    let fullscreen = !FULLSCREEN.fetch_xor(true, Ordering::Relaxed);
+    unsafe { get_internal_gl() }.quad_context.set_fullscreen(fullscreen);
+    true
+}
+
+pub fn fullscreen_help() -> String {
+    tr(Text::Fullscreen, &[&keys::label(Action::Fullscreen)])
+}
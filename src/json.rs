@@ -0,0 +1,193 @@
+use std::fmt::Write;
+
+
+// Enough JSON for the files written here. Numbers are kept as written, so integers
+// of any size read back exactly
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    pub fn get(&self, key: &str) -> Result<&Value, String> {
+        match self {
+            Value::Object(fields) => fields
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value)
+                .ok_or_else(|| format!("missing '{}'", key)),
+            _ => Err(format!("expected an object holding '{}'", key)),
+        }
+    }
+
+    pub fn as_array(&self) -> Result<&[Value], String> {
+        match self {
+            Value::Array(items) => Ok(items),
+            _ => Err("expected an array".to_string()),
+        }
+    }
+
+    pub fn as_str(&self) -> Result<&str, String> {
+        match self {
+            Value::String(text) => Ok(text),
+            _ => Err("expected a string".to_string()),
+        }
+    }
+
+    // Integers, written as numbers or as strings when too large for other readers
+    pub fn as_int<T: std::str::FromStr>(&self) -> Result<T, String> {
+        match self {
+            Value::Number(text) | Value::String(text) => {
+                text.parse().map_err(|_| format!("invalid integer '{}'", text))
+            }
+            _ => Err("expected an integer".to_string()),
+        }
+    }
+}
+
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(escaped, "\\u{:04x}", c as u32).unwrap(),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+pub fn parse(text: &str) -> Result<Value, String> {
+    let mut parser = Parser { chars: text.chars().collect(), pos: 0 };
+    let value = parser.value()?;
+    parser.skip_whitespace();
+    match parser.chars.get(parser.pos) {
+        None => Ok(value),
+        Some(c) => Err(format!("unexpected '{}' after the value", c)),
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn skip_whitespace(&mut self) {
+        while self.chars.get(self.pos).is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn next(&mut self) -> Result<char, String> {
+        let c = *self.chars.get(self.pos).ok_or("unexpected end of file")?;
+        self.pos += 1;
+        Ok(c)
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_whitespace();
+        match self.next()? {
+            c if c == expected => Ok(()),
+            c => Err(format!("expected '{}', found '{}'", expected, c)),
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        self.skip_whitespace();
+        match self.chars.get(self.pos).copied().ok_or("unexpected end of file")? {
+            '{' => {
+                self.pos += 1;
+                let mut fields = Vec::new();
+                self.skip_whitespace();
+                if self.chars.get(self.pos) == Some(&'}') {
+                    self.pos += 1;
+                    return Ok(Value::Object(fields));
+                }
+                loop {
+                    self.expect('"')?;
+                    let name = self.string()?;
+                    self.expect(':')?;
+                    fields.push((name, self.value()?));
+                    self.skip_whitespace();
+                    match self.next()? {
+                        ',' => {}
+                        '}' => return Ok(Value::Object(fields)),
+                        c => return Err(format!("expected ',' or '}}', found '{}'", c)),
+                    }
+                }
+            }
+            '[' => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                self.skip_whitespace();
+                if self.chars.get(self.pos) == Some(&']') {
+                    self.pos += 1;
+                    return Ok(Value::Array(items));
+                }
+                loop {
+                    items.push(self.value()?);
+                    self.skip_whitespace();
+                    match self.next()? {
+                        ',' => {}
+                        ']' => return Ok(Value::Array(items)),
+                        c => return Err(format!("expected ',' or ']', found '{}'", c)),
+                    }
+                }
+            }
+            '"' => {
+                self.pos += 1;
+                self.string().map(Value::String)
+            }
+            c if c == '-' || c.is_ascii_digit() => {
+                let start = self.pos;
+                while self.chars.get(self.pos).is_some_and(|&c| c == '-' || c == '+' || c == '.' || c == 'e' || c == 'E' || c.is_ascii_digit()) {
+                    self.pos += 1;
+                }
+                Ok(Value::Number(self.chars[start..self.pos].iter().collect()))
+            }
+            _ => {
+                for (word, value) in [("null", Value::Null), ("true", Value::Bool(true)), ("false", Value::Bool(false))] {
+                    if self.chars[self.pos..].starts_with(&word.chars().collect::<Vec<_>>()) {
+                        self.pos += word.len();
+                        return Ok(value);
+                    }
+                }
+                Err(format!("unexpected '{}'", self.chars[self.pos]))
+            }
+        }
+    }
+
+    // Past the opening quote
+    fn string(&mut self) -> Result<String, String> {
+        let mut text = String::new();
+        loop {
+            match self.next()? {
+                '"' => return Ok(text),
+                '\\' => match self.next()? {
+                    'n' => text.push('\n'),
+                    't' => text.push('\t'),
+                    'r' => text.push('\r'),
+                    'b' => text.push('\u{8}'),
+                    'f' => text.push('\u{c}'),
+                    'u' => {
+                        let digits: String = (0..4).map(|_| self.next()).collect::<Result<_, _>>()?;
+                        let code = u32::from_str_radix(&digits, 16).map_err(|_| format!("invalid escape '\\u{}'", digits))?;
+                        text.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+                    }
+                    c => text.push(c),
+                },
+                c => text.push(c),
+            }
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use std::cell::RefCell;
+
+use macroquad::prelude::*;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    NewGrid,
+    Replay,
+    Browse,
+    Edit,
+    Play,
+    Heatmap,
+    Volatility,
+    Theme,
+    Palette,
+    Mute,
+    Fullscreen,
+    Screenshot,
+    Record,
+    SaveState,
+    // Along with Ctrl, or Cmd on macOS
+    Copy,
+    Paste,
+    Undo,
+    Redo,
+    Check,
+    Notes,
+    Hint,
+    InputMode,
+    Teach,
+    Menu,
+    // Out of a lesson, the tutorials or another screen, cancelling typed text
+    Back,
+    // Menu item, lesson or puzzle
+    Pick,
+    // Variants switched during a solve
+    Diagonal,
+    Windoku,
+    AntiKnight,
+    AntiKing,
+    NonConsecutive,
+    DisjointGroups,
+    VariantRules,
+    // Editor
+    EditorSolve,
+    EditorSave,
+    EditorUndo,
+    ClearRegions,
+    ToolRegions,
+    ToolThermo,
+    ToolArrow,
+    ToolCage,
+    ToolPalindrome,
+    ToolRelation,
+    ToolRule,
+}
+
+const BINDINGS: [(Action, &str, KeyCode); 44] = [
+    (Action::NewGrid, "new-grid", KeyCode::Space),
+    (Action::Replay, "replay", KeyCode::R),
+    (Action::Browse, "browse", KeyCode::B),
+    (Action::Edit, "edit", KeyCode::E),
+    (Action::Play, "play", KeyCode::P),
+    (Action::Heatmap, "heatmap", KeyCode::H),
+    (Action::Volatility, "volatility", KeyCode::V),
+    (Action::Theme, "theme", KeyCode::L),
+    (Action::Palette, "palette", KeyCode::C),
+    (Action::Mute, "mute", KeyCode::M),
+    (Action::Fullscreen, "fullscreen", KeyCode::F11),
+    (Action::Screenshot, "screenshot", KeyCode::F12),
+    (Action::Record, "record", KeyCode::G),
+    (Action::SaveState, "save-state", KeyCode::F8),
+    (Action::Copy, "copy", KeyCode::C),
+    (Action::Paste, "paste", KeyCode::V),
+    (Action::Undo, "undo", KeyCode::Z),
+    (Action::Redo, "redo", KeyCode::Y),
+    (Action::Check, "check", KeyCode::K),
+    (Action::Notes, "notes", KeyCode::N),
+    (Action::Hint, "hint", KeyCode::I),
+    (Action::InputMode, "input-mode", KeyCode::O),
+    (Action::Teach, "teach", KeyCode::T),
+    (Action::Menu, "menu", KeyCode::Escape),
+    (Action::Back, "back", KeyCode::Escape),
+    (Action::Pick, "pick", KeyCode::Enter),
+    (Action::Diagonal, "diagonal", KeyCode::F1),
+    (Action::Windoku, "windoku", KeyCode::F2),
+    (Action::AntiKnight, "anti-knight", KeyCode::F3),
+    (Action::AntiKing, "anti-king", KeyCode::F4),
+    (Action::NonConsecutive, "non-consecutive", KeyCode::F5),
+    (Action::DisjointGroups, "disjoint-groups", KeyCode::F6),
+    (Action::VariantRules, "variant-rules", KeyCode::F7),
+    (Action::EditorSolve, "editor-solve", KeyCode::Enter),
+    (Action::EditorSave, "editor-save", KeyCode::S),
+    (Action::EditorUndo, "editor-undo", KeyCode::Backspace),
+    (Action::ClearRegions, "clear-regions", KeyCode::Delete),
+    (Action::ToolRegions, "tool-regions", KeyCode::R),
+    (Action::ToolThermo, "tool-thermo", KeyCode::T),
+    (Action::ToolArrow, "tool-arrow", KeyCode::A),
+    (Action::ToolCage, "tool-cage", KeyCode::C),
+    (Action::ToolPalindrome, "tool-palindrome", KeyCode::P),
+    (Action::ToolRelation, "tool-relation", KeyCode::X),
+    (Action::ToolRule, "tool-rule", KeyCode::V),
+];
+
+// Keys that can be bound, named as they are shown
+const KEYS: [KeyCode; 77] = [
+    KeyCode::Space, KeyCode::Apostrophe, KeyCode::Comma, KeyCode::Minus, KeyCode::Period, KeyCode::Slash,
+    KeyCode::Key0, KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4,
+    KeyCode::Key5, KeyCode::Key6, KeyCode::Key7, KeyCode::Key8, KeyCode::Key9,
+    KeyCode::Semicolon, KeyCode::Equal,
+    KeyCode::A, KeyCode::B, KeyCode::C, KeyCode::D, KeyCode::E, KeyCode::F, KeyCode::G, KeyCode::H, KeyCode::I,
+    KeyCode::J, KeyCode::K, KeyCode::L, KeyCode::M, KeyCode::N, KeyCode::O, KeyCode::P, KeyCode::Q, KeyCode::R,
+    KeyCode::S, KeyCode::T, KeyCode::U, KeyCode::V, KeyCode::W, KeyCode::X, KeyCode::Y, KeyCode::Z,
+    KeyCode::LeftBracket, KeyCode::Backslash, KeyCode::RightBracket, KeyCode::GraveAccent,
+    KeyCode::Escape, KeyCode::Enter, KeyCode::Tab, KeyCode::Backspace, KeyCode::Insert, KeyCode::Delete,
+    KeyCode::Right, KeyCode::Left, KeyCode::Down, KeyCode::Up,
+    KeyCode::PageUp, KeyCode::PageDown, KeyCode::Home, KeyCode::End, KeyCode::Pause,
+    KeyCode::F1, KeyCode::F2, KeyCode::F3, KeyCode::F4, KeyCode::F5, KeyCode::F6,
+    KeyCode::F7, KeyCode::F8, KeyCode::F9, KeyCode::F10, KeyCode::F11, KeyCode::F12,
+    KeyCode::KpEnter, KeyCode::KpAdd,
+];
+
+
+thread_local! {
+    static CURRENT: RefCell<Vec<KeyCode>> = RefCell::new(BINDINGS.iter().map(|&(.., key)| key).collect());
+}
+
+fn index(action: Action) -> usize {
+    BINDINGS.iter().position(|&(bound, ..)| bound == action).unwrap()
+}
+
+// Key the action is bound to
+pub fn get(action: Action) -> KeyCode {
+    CURRENT.with(|current| current.borrow()[index(action)])
+}
+
+// Name of the key as shown in the help and written in key files
+pub fn name(key: KeyCode) -> String {
+    format!("{:?}", key)
+}
+
+pub fn label(action: Action) -> String {
+    name(get(action))
+}
+
+pub fn pressed(action: Action) -> bool {
+    is_key_pressed(get(action))
+}
+
+pub fn set(bindings: Vec<KeyCode>) {
+    CURRENT.with(|current| *current.borrow_mut() = bindings);
+}
+
+pub fn load(path: &str) -> Result<Vec<KeyCode>, String> {
+    let text = std::fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+    parse(&text).map_err(|err| format!("{}: {}", path, err))
+}
+
+// Key files give an action then its key on each line, `#` starting a comment,
+// actions left out keeping their default key:
+//
+//   new-grid Enter
+//   replay F5
+//   screenshot P
+//
+// Keys are named as in the help text: letters, Key0 to Key9 for the digits, F1 to F12,
+// Space, Enter, Up... Names are read whatever their case
+pub fn parse(text: &str) -> Result<Vec<KeyCode>, String> {
+    let mut bindings: Vec<KeyCode> = BINDINGS.iter().map(|&(.., key)| key).collect();
+    for (n, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let [action, key] = words[..] else {
+            return Err(format!("line {}: expected an action and a key", n + 1));
+        };
+        let i = BINDINGS
+            .iter()
+            .position(|&(_, name, _)| name == action)
+            .ok_or_else(|| format!("line {}: unknown action '{}'", n + 1, action))?;
+        bindings[i] = KEYS
+            .into_iter()
+            .find(|&known| name(known).eq_ignore_ascii_case(key))
+            .ok_or_else(|| format!("line {}: unknown key '{}'", n + 1, key))?;
+    }
+    Ok(bindings)
+}
+
+pub fn save(path: &str) -> Result<(), String> {
+    std::fs::write(path, serialize()).map_err(|err| format!("{}: {}", path, err))
+}
+
+// Every action with its current key
+pub fn serialize() -> String {
+    BINDINGS.iter().map(|&(action, name, _)| format!("{} {}\n", name, label(action))).collect()
+}
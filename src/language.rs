@@ -0,0 +1,344 @@
+use std::cell::Cell;
+use std::fmt::Display;
+
+use sudoku::contradiction::Contradiction;
+use sudoku::hints::{Hint, Technique};
+use sudoku::library::Difficulty;
+use sudoku::notation::format_cells;
+
+
+// Cells of a propagation path shown, the last ones before the contradiction
+const PATH_SHOWN: usize = 5;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    English,
+    French,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::French];
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::French => "fr",
+        }
+    }
+
+    // As written in the language itself
+    pub fn name(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::French => "Français",
+        }
+    }
+
+    // Also takes locales like fr_FR.UTF-8
+    pub fn from_code(code: &str) -> Option<Self> {
+        let code = code.split(['_', '-', '.']).next().unwrap_or("").to_lowercase();
+        Self::ALL.into_iter().find(|language| language.code() == code)
+    }
+
+    // From the locale of the environment, English when it's none of the others
+    pub fn detect() -> Self {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| std::env::var(name).ok().filter(|value| !value.is_empty()))
+            .find_map(|locale| Self::from_code(&locale))
+            .unwrap_or(Language::English)
+    }
+}
+
+thread_local! {
+    static CURRENT: Cell<Language> = const { Cell::new(Language::English) };
+}
+
+pub fn set(language: Language) {
+    CURRENT.with(|current| current.set(language));
+}
+
+pub fn current() -> Language {
+    CURRENT.with(Cell::get)
+}
+
+// Every text shown on screen in each language, `{}` marking where values go in
+// the order they're given. Error details from files are left in English
+macro_rules! texts {
+    ($($name:ident => $english:expr, $french:expr;)*) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum Text {
+            $($name,)*
+        }
+
+        impl Text {
+            #[cfg(test)]
+            pub const ALL: &'static [Text] = &[$(Text::$name,)*];
+
+            pub fn template(self, language: Language) -> &'static str {
+                match (self, language) {
+                    $(
+                        (Text::$name, Language::English) => $english,
+                        (Text::$name, Language::French) => $french,
+                    )*
+                }
+            }
+        }
+    };
+}
+
+texts! {
+    HelpKeys => "Press [{}] for a new grid, [{}] to replay, [{}] for puzzles, [{}] to edit, [{}] to play, [{}] for the heatmap, [{}] for the menu",
+        "[{}] nouvelle grille, [{}] rejouer, [{}] puzzles, [{}] éditer, [{}] jouer, [{}] carte de chaleur, [{}] menu";
+    HelpMore => "{}, [{}] mute, [{}] light/dark, [{}] palette, {}, [{}] screenshot, [{}] record GIF, [{}] save solve, [Ctrl+{}/{}] copy/paste, [Ctrl+{}/{}] undo/redo, [{}] check, [{}] notes, [{}] hint, [{}] cell or digit first, [{}] teacher mode, click a cell and type a digit to lock it",
+        "{}, [{}] son, [{}] clair/sombre, [{}] palette, {}, [{}] capture, [{}] GIF, [{}] sauvegarder, [Ctrl+{}/{}] copier/coller, [Ctrl+{}/{}] annuler/rétablir, [{}] vérifier, [{}] notes, [{}] indice, [{}] case ou chiffre d'abord, [{}] mode professeur, cliquez une case et tapez un chiffre pour le fixer";
+    VictoryKeys => "[{}] new grid, [{}] replay, [{}] volatility",
+        "[{}] nouvelle grille, [{}] rejouer, [{}] volatilité";
+    Solved => "Solved in {} s with {} restarts",
+        "Résolu en {} s avec {} redémarrages";
+    SolvedOneRestart => "Solved in {} s with 1 restart",
+        "Résolu en {} s avec 1 redémarrage";
+    Loaded => "Loaded {}",
+        "{} chargé";
+    DoesntFit => "{} doesn't fit the current board and rules",
+        "{} ne convient pas à la grille et aux règles actuelles";
+    Copied => "Copied grid",
+        "Grille copiée";
+    Pasted => "Pasted puzzle",
+        "Puzzle collé";
+    PasteFailed => "Couldn't paste puzzle, {}",
+        "Impossible de coller le puzzle, {}";
+    PaletteName => "{} palette",
+        "Palette {}";
+    Daily => "Daily {}",
+        "Grille du {}";
+    SavedSolve => "Saved {}, --restore it to carry on",
+        "{} enregistré, --restore pour reprendre";
+    SaveSolveFailed => "Couldn't save solve {}",
+        "Impossible de sauvegarder la résolution {}";
+    Saved => "Saved {}",
+        "{} enregistré";
+    ScreenshotFailed => "Couldn't save screenshot {}",
+        "Impossible d'enregistrer la capture {}";
+    RecordingStarted => "Recording a GIF until the grid is solved, [{}] to stop",
+        "Enregistrement d'un GIF jusqu'à la résolution, [{}] pour arrêter";
+    RecordingFailed => "Couldn't record GIF {}",
+        "Impossible d'enregistrer le GIF {}";
+    Fullscreen => "[{}] fullscreen",
+        "[{}] plein écran";
+    NoProgress => "No progress for {} seconds",
+        "Aucun progrès depuis {} secondes";
+    BestAttempt => "{} restarts, the best attempt resolved {} of {} cells",
+        "{} redémarrages, le meilleur essai a résolu {} cases sur {}";
+    StallReason => "The rules may contradict each other or leave too few solutions,",
+        "Les règles se contredisent peut-être ou laissent trop peu de solutions,";
+    StallAdvice => "try relaxing some constraints in the editor.",
+        "essayez d'assouplir quelques contraintes dans l'éditeur.";
+    LastContradiction => "Last contradiction: {}", "Dernière contradiction : {}";
+    Dismiss => "Press [{}] to dismiss",
+        "[{}] pour fermer";
+    BrowserTitle => "Puzzles   [Up/Down] choose  [{}] solve  [{}] play  [{}] close",
+        "Puzzles   [Haut/Bas] choisir  [{}] résoudre  [{}] jouer  [{}] fermer";
+    Givens => "{} givens",
+        "{} indices";
+    Easy => "Easy", "Facile";
+    Medium => "Medium", "Moyen";
+    Hard => "Hard", "Difficile";
+    Expert => "Expert", "Expert";
+    PuzzleSolved => "Solved in {}, mistakes: {}", "Résolu en {}, erreurs : {}";
+    NewBest => "Solved in {}, mistakes: {}, a new best for {} puzzles",
+        "Résolu en {}, erreurs : {}, nouveau record du niveau {}";
+    Mistakes => "mistakes: {}", "erreurs : {}";
+    NoMistakes => "No wrong digits so far", "Aucun chiffre faux pour l'instant";
+    WrongDigits => "Wrong digits: {}", "Chiffres faux : {}";
+    Best => "best {}", "record {}";
+    BestTimes => "Best times", "Meilleurs temps";
+    NakedSingle => "Naked single: {} can only hold {}", "Singleton nu : {} ne peut contenir que {}";
+    HiddenSingle => "Hidden single: {} is the only place for {} in the highlighted house",
+        "Singleton caché : {} est la seule place pour {} dans le groupe en surbrillance";
+    Intersection => "Intersection: the {} of one highlighted house all lie in the other, which loses it from {}",
+        "Intersection : les {} d'un groupe en surbrillance sont tous dans l'autre, qui le perd en {}";
+    NakedPair => "Naked pair: {} only hold {} between them, which leaves {}",
+        "Paire nue : {} ne contiennent que {} à elles deux, ce qui libère {}";
+    NoHint => "No hint from here, check the entries for mistakes",
+        "Aucun indice d'ici, vérifiez les chiffres entrés";
+    CellFirst => "Click a cell, then type its digit", "Cliquez une case, puis tapez son chiffre";
+    DigitFirst => "Pick a digit on the right, then click the cells to place it in",
+        "Choisissez un chiffre à droite, puis cliquez les cases où le placer";
+    MenuKeys => "[Up/Down] choose  [{}] pick", "[Haut/Bas] choisir  [{}] valider";
+    MenuResume => "Resume the last game", "Reprendre la dernière partie";
+    MenuSolve => "Watch a solve", "Regarder une résolution";
+    MenuPuzzles => "Puzzles", "Puzzles";
+    MenuRace => "Race grids", "Course de grilles";
+    MenuCompare => "Compare heuristics", "Comparer les heuristiques";
+    MenuAnalytics => "Restart analytics", "Statistiques des redémarrages";
+    MenuTutorials => "Technique tutorials", "Tutoriels des techniques";
+    MenuEditor => "Rule editor", "Éditeur de règles";
+    MenuOptions => "Options", "Options";
+    MenuQuit => "Quit", "Quitter";
+    OptionsTitle => "Options   [Up/Down] choose  [{}] change  [{}] back",
+        "Options   [Haut/Bas] choisir  [{}] changer  [{}] retour";
+    OptionLanguage => "Language: {}", "Langue : {}";
+    OptionInput => "Input: {}", "Saisie : {}";
+    InputCell => "cell first", "case d'abord";
+    InputDigit => "digit first", "chiffre d'abord";
+    OptionTheme => "Switch light/dark", "Basculer clair/sombre";
+    OptionPalette => "Palette: {}", "Palette : {}";
+    OptionSound => "Sound: {}", "Son : {}";
+    On => "on", "activé";
+    Off => "off", "coupé";
+    Back => "Back", "Retour";
+    ToggleHelp => "[{}] toggle variants, [{}] file rules",
+        "[{}] variantes, [{}] règles du fichier";
+    VariantRules => "variant rules",
+        "règles de la variante";
+    SwitchedOn => "{} on", "{} activé";
+    SwitchedOff => "{} off", "{} désactivé";
+    ToggleDropped => "{}: {} placements became invalid and were removed: {}",
+        "{} : {} placements devenus invalides ont été retirés : {}";
+    ToggleRefused => "{} would break the givens, the rules are unchanged: {}",
+        "{} enfreindrait les indices, les règles restent inchangées : {}";
+    Locked => "{} locked to {}", "{} fixé à {}";
+    Unlocked => "{} unlocked", "{} libéré";
+    LockDropped => "{}: {} placements no longer fit and were removed: {}",
+        "{} : {} placements ne convenaient plus et ont été retirés : {}";
+    LockRefused => "{} would break the rules, nothing was locked: {}",
+        "{} enfreindrait les règles, rien n'a été fixé : {}";
+    Volatility => "{} candidate changes, most in {} ({} each)",
+        "{} changements de candidats, surtout en {} ({} chacune)";
+    EditorHelp => "[{}] undo  [{}] clear regions  [{}] save  [{}] solve  {}",
+        "[{}] annuler  [{}] effacer les régions  [{}] sauvegarder  [{}] résoudre  {}";
+    EditorPrompt => "{}: {}_   [{}] cancel",
+        "{} : {}_   [{}] annuler";
+    SavedTo => "Saved to {}", "Enregistré dans {}";
+    ToolRegions => "regions", "régions";
+    ToolThermo => "thermo", "thermo";
+    ToolArrow => "arrow", "flèche";
+    ToolCage => "cage", "cage";
+    ToolPalindrome => "palindrome", "palindrome";
+    ToolRelation => "relation", "relation";
+    ToolRule => "rule", "règle";
+    EmptiedCell => "{} lost its last candidates {} to {}", "{} a perdu ses derniers candidats {} par {}";
+    BrokenRule => "{} fits no digits", "{} n'admet aucun chiffre";
+    BasicRules => "the basic rules", "les règles de base";
+    AfterEliminations => "{}, after eliminations in {}", "{}, après des éliminations en {}";
+    RaceHelp => "[{}] new race, [{}] menu", "[{}] nouvelle course, [{}] menu";
+    RaceSolving => "solving, {} restarts, seed {}", "en cours, {} redémarrages, graine {}";
+    RaceFinished => "#{} in {} s, {} restarts, seed {}", "n°{} en {} s, {} redémarrages, graine {}";
+    CompareHelp => "Seed {}, [{}] new seed, [{}] menu", "Graine {}, [{}] nouvelle graine, [{}] menu";
+    CompareSolving => "{}: {} steps, {} restarts", "{} : {} étapes, {} redémarrages";
+    CompareSolved => "{}: solved in {} steps, {} restarts", "{} : résolue en {} étapes, {} redémarrages";
+    AnalyticsTitle => "Restarts this session   [{}] back", "Redémarrages de la session   [{}] retour";
+    AnalyticsNone => "No grid solved yet", "Aucune grille résolue pour l'instant";
+    AnalyticsSolves => "{} grids solved, {}% of the attempts dead-ended",
+        "{} grilles résolues, {} % des tentatives dans une impasse";
+    AnalyticsRestarts => "Restarts: min {}, average {}, max {}", "Redémarrages : min {}, moyenne {}, max {}";
+    AnalyticsWorst => "Worst seed {}, {} restarts", "Pire graine {}, {} redémarrages";
+    TeachFewest => "Cell {} collapsed to {} because it had the fewest candidates ({})",
+        "La case {} s'est réduite à {} car elle avait le moins de candidats ({})";
+    TeachFirst => "Cell {} collapsed to {} because it was the first open cell ({} candidates)",
+        "La case {} s'est réduite à {} car c'était la première case ouverte ({} candidats)";
+    TeachRandom => "Cell {} collapsed to {}, picked at random among the open cells ({} candidates)",
+        "La case {} s'est réduite à {}, tirée au hasard parmi les cases ouvertes ({} candidats)";
+    TeachEliminated => "{} eliminated from {} ({})", "{} éliminé de {} ({})";
+    TeachSameRow => "same row as {}", "même ligne que {}";
+    TeachSameColumn => "same column as {}", "même colonne que {}";
+    TeachSameBox => "same box as {}", "même bloc que {}";
+    TeachSameHouse => "same house as {}", "même groupe que {}";
+    TeachConstraints => "ruled out by the other constraints", "exclu par les autres contraintes";
+    TeachContradiction => "Collapsing {} left a cell without candidates",
+        "Réduire {} a laissé une case sans candidat";
+    TeachRestart => "The solve starts over", "La résolution recommence";
+    TeachBackjump => "Backjumping rules {} out of {}", "Le retour arrière exclut {} de {}";
+    TeachSolved => "Every cell is down to one candidate, the grid is solved",
+        "Chaque case n'a plus qu'un candidat, la grille est résolue";
+    TutorialsTitle => "Tutorials   [Up/Down] choose  [{}] start  [{}] back",
+        "Tutoriels   [Haut/Bas] choisir  [{}] commencer  [{}] retour";
+    LessonNakedSingle => "Naked singles", "Singletons nus";
+    LessonHiddenSingle => "Hidden singles", "Singletons cachés";
+    LessonNakedPair => "Naked pairs", "Paires nues";
+    IntroNakedSingle => "A naked single is a cell left with one candidate, every other digit being already in its row, column or box. The small digits are the candidates of each open cell",
+        "Un singleton nu est une case à un seul candidat, tous les autres chiffres étant déjà dans sa ligne, sa colonne ou son bloc. Les petits chiffres sont les candidats de chaque case ouverte";
+    IntroHiddenSingle => "A hidden single is a digit with a single cell left for it in a row, column or box, whatever else that cell could hold",
+        "Un singleton caché est un chiffre qui n'a plus qu'une case possible dans une ligne, une colonne ou un bloc, quoi que cette case puisse contenir d'autre";
+    IntroNakedPair => "A naked pair is two cells of a house left with the same two candidates: the two digits go in these cells, so the rest of the house loses them",
+        "Une paire nue est deux cases d'un groupe aux deux mêmes candidats : les deux chiffres vont dans ces cases, le reste du groupe les perd donc";
+    TutorialKeys => "{}   [{}] next  [{}] lessons", "{}   [{}] suite  [{}] leçons";
+    TutorialLook => "Look at the highlighted cells and their candidates, what do they tell?",
+        "Regardez les cases en surbrillance et leurs candidats, que disent-ils ?";
+    TutorialFilled => "{} simpler steps were taken to get here", "{} étapes plus simples ont été faites pour en arriver là";
+    TutorialPlaced => "{} goes in {}", "{} va en {}";
+    TutorialEliminated => "{} can no longer hold {}", "{} ne peuvent plus contenir {}";
+    TutorialDone => "That's the lesson, {} examples seen. [{}] back to the lessons",
+        "Fin de la leçon, {} exemples vus. [{}] retour aux leçons";
+    CageSum => "Cage sum", "Somme de la cage";
+    RelationPrompt => "Relation between a and b", "Relation entre a et b";
+    RulePrompt => "Variant file rule, click cells to add them", "Règle de fichier de variante, cliquez des cases pour les ajouter";
+}
+
+pub fn difficulty(difficulty: Difficulty) -> String {
+    let text = match difficulty {
+        Difficulty::Easy => Text::Easy,
+        Difficulty::Medium => Text::Medium,
+        Difficulty::Hard => Text::Hard,
+        Difficulty::Expert => Text::Expert,
+    };
+    tr(text, &[])
+}
+
+pub fn hint(hint: &Hint, side: usize) -> String {
+    let cells = format_cells(&hint.cells, side);
+    let digits = hint.candidates.iter().map(u8::to_string).collect::<Vec<_>>().join("/");
+    let eliminated: Vec<usize> = hint.eliminations.iter().map(|&(idx, _)| idx).collect();
+    let eliminated = format_cells(&eliminated, side);
+    match hint.technique {
+        Technique::NakedSingle => tr(Text::NakedSingle, &[&cells, &digits]),
+        Technique::HiddenSingle => tr(Text::HiddenSingle, &[&cells, &digits]),
+        Technique::Intersection => tr(Text::Intersection, &[&digits, &eliminated]),
+        Technique::NakedPair => tr(Text::NakedPair, &[&cells, &digits, &eliminated]),
+    }
+}
+
+// Where the contradiction was found, and the last cells it propagated through
+pub fn contradiction(contradiction: &Contradiction, side: usize) -> String {
+    let rule = contradiction.rule.clone().unwrap_or_else(|| tr(Text::BasicRules, &[]));
+    let found = match contradiction.cell {
+        Some(idx) => {
+            let removed = contradiction.removed.iter().map(u8::to_string).collect::<Vec<_>>().join("/");
+            tr(Text::EmptiedCell, &[&format_cells(&[idx], side), &removed, &rule])
+        }
+        None => tr(Text::BrokenRule, &[&rule]),
+    };
+    match &contradiction.path[contradiction.path.len().saturating_sub(PATH_SHOWN)..] {
+        [] => found,
+        path => tr(Text::AfterEliminations, &[&found, &format_cells(path, side)]),
+    }
+}
+
+// Text in the current language with the values filled in
+pub fn tr(text: Text, values: &[&dyn Display]) -> String {
+    let mut parts = text.template(current()).split("{}");
+    let mut filled = parts.next().unwrap_or("").to_string();
+    for (part, value) in parts.zip(values.iter().map(|value| value.to_string()).chain(std::iter::repeat(String::new()))) {
+        filled.push_str(&value);
+        filled.push_str(part);
+    }
+    filled
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn translations_take_the_same_values() {
+        for &text in Text::ALL {
+            let values = |language| text.template(language).matches("{}").count();
+            for language in Language::ALL {
+                assert_eq!(values(language), values(Language::English), "{:?} in {:?}", text, language);
+            }
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use macroquad::prelude::*;
+
+use crate::grid::BoardSize;
+use crate::theme;
+
+
+// Reference cell size fonts, offsets and line thicknesses are given for
+pub const CELL_SIZE: f32 = 50.;
+
+// Lines of text kept clear above and below the board, for help and messages
+const TOP_TEXT_LINES: f32 = 3.;
+const BOTTOM_TEXT_LINES: f32 = 2.;
+
+
+// Space reserved around the board for outside clues, in cells
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Margins {
+    pub top: f32,
+    pub bottom: f32,
+    pub left: f32,
+    pub right: f32,
+}
+
+impl Margins {
+    pub fn edge(edge: Edge, depth: f32) -> Margins {
+        let mut margins = Margins::default();
+        match edge {
+            Edge::Top => margins.top = depth,
+            Edge::Bottom => margins.bottom = depth,
+            Edge::Left => margins.left = depth,
+            Edge::Right => margins.right = depth,
+        }
+        margins
+    }
+
+    pub fn max(self, other: Margins) -> Margins {
+        Margins {
+            top: self.top.max(other.top),
+            bottom: self.bottom.max(other.bottom),
+            left: self.left.max(other.left),
+            right: self.right.max(other.right),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+impl Edge {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "top" => Some(Edge::Top),
+            "bottom" => Some(Edge::Bottom),
+            "left" => Some(Edge::Left),
+            "right" => Some(Edge::Right),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Edge::Top => "top",
+            Edge::Bottom => "bottom",
+            Edge::Left => "left",
+            Edge::Right => "right",
+        }
+    }
+
+    // Cells of the column (top/bottom) or row (left/right) facing this edge, read inward
+    pub fn line_cells(self, side: usize, line: usize) -> Vec<usize> {
+        (0..side)
+            .map(|depth| match self {
+                Edge::Top => depth * side + line,
+                Edge::Bottom => (side - 1 - depth) * side + line,
+                Edge::Left => line * side + depth,
+                Edge::Right => line * side + side - 1 - depth,
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Layout {
+    pub origin: Vec2,
+    pub cell_size: f32,
+    pub side: usize,
+}
+
+impl Layout {
+    // Board filling the window between the text lines, computed again every frame so
+    // it follows resizes
+    pub fn new(size: BoardSize, margins: Margins) -> Self {
+        let font_size = theme::current().text.font_size;
+        let top = TOP_TEXT_LINES * font_size;
+        let height = screen_height() - top - BOTTOM_TEXT_LINES * font_size;
+        let mut layout = Self::fit(size, margins, screen_width(), height);
+        layout.origin.y += top;
+        // Whole device pixels keep lines sharp on high-DPI displays
+        let dpi = unsafe { get_internal_gl() }.quad_context.dpi_scale();
+        layout.cell_size = (layout.cell_size * dpi).floor().max(1.) / dpi;
+        layout.origin = (layout.origin * dpi).round() / dpi;
+        layout
+    }
+
+    // Center board and margins in the area, with cells as large as fit
+    pub fn fit(size: BoardSize, margins: Margins, width: f32, height: f32) -> Self {
+        let side = size.side();
+        let columns = side as f32 + margins.left + margins.right;
+        let rows = side as f32 + margins.top + margins.bottom;
+
+        let board = &theme::current().board;
+        let cell_size = ((width - 2. * board.screen_padding) / columns)
+            .min((height - 2. * board.screen_padding) / rows)
+            .max(1.);
+
+        let origin = vec2(
+            (width - columns * cell_size) / 2. + margins.left * cell_size,
+            (height - rows * cell_size) / 2. + margins.top * cell_size,
+        );
+
+        Self { origin, cell_size, side }
+    }
+
+    // Ratio to the reference 50px cell, used to scale fonts, offsets and lines
+    pub fn scale(&self) -> f32 {
+        self.cell_size / CELL_SIZE
+    }
+
+    pub fn extent(&self) -> f32 {
+        self.cell_size * self.side as f32
+    }
+
+    // Top-left corner of the margin cell in front of a line, `depth` cells away from the board
+    pub fn outside_cell_position(&self, edge: Edge, line: usize, depth: usize) -> Vec2 {
+        let line = line as f32 * self.cell_size;
+        let offset = (depth + 1) as f32 * self.cell_size;
+        match edge {
+            Edge::Top => self.origin + vec2(line, -offset),
+            Edge::Bottom => self.origin + vec2(line, self.extent() + offset - self.cell_size),
+            Edge::Left => self.origin + vec2(-offset, line),
+            Edge::Right => self.origin + vec2(self.extent() + offset - self.cell_size, line),
+        }
+    }
+
+    pub fn cell_position(&self, idx: usize) -> Vec2 {
+        self.origin + vec2(
+            (idx % self.side) as f32 * self.cell_size,
+            (idx / self.side) as f32 * self.cell_size,
+        )
+    }
+
+    // Cell under a screen position, if any
+    pub fn cell_at(&self, position: Vec2) -> Option<usize> {
+        let offset = (position - self.origin) / self.cell_size;
+        let side = self.side as f32;
+        if offset.x < 0. || offset.y < 0. || offset.x >= side || offset.y >= side {
+            return None;
+        }
+        Some(offset.y as usize * self.side + offset.x as usize)
+    }
+}
@@ -0,0 +1,25 @@
+// Result<_, ()> marks contradictions inside the constraints, the grid reports them
+// as `Contradiction`s
+#![allow(clippy::result_unit_err)]
+
+pub mod cell;
+pub mod constraints;
+pub mod contradiction;
+pub mod events;
+pub mod expression;
+pub mod generator;
+pub mod grid;
+pub mod heuristic;
+pub mod hints;
+pub mod json;
+pub mod layout;
+pub mod library;
+pub mod notation;
+pub mod puzzle;
+pub mod snapshot;
+pub mod state;
+pub mod symmetry;
+pub mod text;
+pub mod theme;
+pub mod units;
+pub mod variant;
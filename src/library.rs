@@ -0,0 +1,69 @@
+use crate::grid::BoardSize;
+use crate::puzzle;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard, Difficulty::Expert];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Medium => "Medium",
+            Difficulty::Hard => "Hard",
+            Difficulty::Expert => "Expert",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|difficulty| difficulty.name().eq_ignore_ascii_case(name))
+    }
+}
+
+pub struct Entry {
+    pub name: &'static str,
+    pub difficulty: Difficulty,
+    pub text: &'static str,
+}
+
+impl Entry {
+    // Bundled puzzles are all valid 9x9 ones
+    pub fn givens(&self) -> Vec<Option<u8>> {
+        puzzle::parse(self.text, BoardSize::default()).unwrap()
+    }
+}
+
+macro_rules! entry {
+    ($name:expr, $difficulty:ident, $file:expr) => {
+        Entry { name: $name, difficulty: Difficulty::$difficulty, text: include_str!(concat!("../puzzles/", $file)) }
+    };
+}
+
+// Classic 9x9 puzzles, each with a single solution. Easy ones fall to naked singles,
+// medium ones need hidden singles, hard ones more than that. Expert ones are among the
+// best known hardest
+pub const PUZZLES: [Entry; 16] = [
+    entry!("Classic", Easy, "classic.txt"),
+    entry!("Easy 2", Easy, "easy-2.txt"),
+    entry!("Easy 3", Easy, "easy-3.txt"),
+    entry!("Easy 4", Easy, "easy-4.txt"),
+    entry!("Medium 1", Medium, "medium-1.txt"),
+    entry!("Medium 2", Medium, "medium-2.txt"),
+    entry!("Medium 3", Medium, "medium-3.txt"),
+    entry!("Medium 4", Medium, "medium-4.txt"),
+    entry!("Hard 1", Hard, "hard-1.txt"),
+    entry!("Hard 2", Hard, "hard-2.txt"),
+    entry!("Hard 3", Hard, "hard-3.txt"),
+    entry!("Hard 4", Hard, "hard-4.txt"),
+    entry!("Hard 5", Hard, "hard-5.txt"),
+    entry!("Arto Inkala 2012", Expert, "inkala.txt"),
+    entry!("Easter Monster", Expert, "easter-monster.txt"),
+    entry!("Norvig hard1 (17 givens)", Expert, "norvig-hard1.txt"),
+];
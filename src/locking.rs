@@ -0,0 +1,61 @@
+use macroquad::prelude::*;
+
+use sudoku::grid::Grid;
+use sudoku::layout::Layout;
+use sudoku::notation::format_cells;
+use sudoku::theme;
+
+use crate::input;
+use crate::language::{self, tr, Text};
+use crate::numpad::Numpad;
+
+
+// Locks cells to chosen digits while the solver runs, steering it: locked digits
+// become givens, kept through restarts, so the rest of the board has to follow them
+#[derive(Default)]
+pub struct Locking {
+    selected: Option<usize>,
+    numpad: Numpad,
+}
+
+impl Locking {
+    // Returns what was locked or refused, if anything was typed
+    pub fn update(&mut self, grid: &mut Grid, layout: &Layout) -> Option<String> {
+        let tapped = self.numpad.tap(grid.size, layout, self.selected);
+        if tapped.is_none() && is_mouse_button_pressed(MouseButton::Left) {
+            self.selected = layout.cell_at(mouse_position().into());
+        }
+        let Some(idx) = self.selected else {
+            while get_char_pressed().is_some() {}
+            return None;
+        };
+
+        let side = grid.size.side();
+        let current = grid.givens()[idx];
+        let value = tapped.unwrap_or(input::type_value(current, side));
+        if value == current {
+            return None;
+        }
+
+        let cell = format_cells(&[idx], side);
+        let action = match value {
+            Some(value) => tr(Text::Locked, &[&cell, &value]),
+            None => tr(Text::Unlocked, &[&cell]),
+        };
+        Some(match grid.set_given(idx, value) {
+            Ok(dropped) if dropped.is_empty() => action,
+            Ok(dropped) => tr(Text::LockDropped, &[&action, &dropped.len(), &format_cells(&dropped, side)]),
+            Err(contradiction) => tr(Text::LockRefused, &[&action, &language::contradiction(&contradiction, side)]),
+        })
+    }
+
+    pub fn draw(&self, grid: &Grid, layout: &Layout) {
+        let theme = theme::current();
+        if let Some(idx) = self.selected {
+            let p = layout.cell_position(idx);
+            let size = layout.cell_size;
+            draw_rectangle_lines(p.x, p.y, size, size, theme.selection.thickness * layout.scale(), theme.selection.color);
+        }
+        self.numpad.draw(grid.size, layout, self.selected);
+    }
+}
@@ -1,248 +1,386 @@
-use ::rand::{
-    seq::SliceRandom,
-    prelude::IteratorRandom
-};
 use macroquad::prelude::*;
 
-
-const CELL_SIZE: f32 = 50.;
-const BIG_FONT_SIZE: f32 = 40.;
-const SMALL_FONT_SIZE: f32 = 20.;
-const TEXT_FONT_SIZE: f32 = 20.;
-
-const BIG_NUM_OFFSET: (f32, f32) = (10., 40.);
-const SMALL_NUM_OFFSET: (f32, f32) = (5., 18.);
-
-const SMALL_LINES_THICKNESS: f32 = 1.;
-const BIG_LINES_THICKNESS: f32 = 3.;
-
-const BACKGROUND_COLOR: Color = BLACK;
-const GRID_COLOR: Color = WHITE;
-const BIG_NUM_COLOR: Color = WHITE;
-const SMALL_NUM_COLOR: Color = WHITE;
-const TEXT_COLOR: Color = WHITE;
-
-const TICK_SECONDS: f64 = 0.2;
-
-const RESET_GRID_KEY: KeyCode = KeyCode::Space;
-
-
-#[derive(Debug, Clone)]
-struct Cell {
-    possible_values: Vec<u8>,
-    propagated: bool,
-}
-
-impl Default for Cell {
-    fn default() -> Self {
-        Self { possible_values: (1..=9).collect(), propagated: false }
+mod batch;
+mod analytics;
+mod autosave;
+mod bench;
+mod browser;
+mod cli;
+mod compare;
+mod clipboard;
+mod date;
+mod editor;
+mod flash;
+mod frames;
+mod game;
+mod gif;
+mod heatmap;
+mod hotbar;
+mod input;
+mod keys;
+mod language;
+mod locking;
+mod menu;
+mod metrics;
+mod notice;
+mod numpad;
+mod options;
+mod play;
+mod race;
+mod recording;
+mod records;
+mod replay;
+mod screen;
+mod screenshot;
+mod self_test;
+mod settings;
+mod sounds;
+mod teacher;
+mod title;
+mod toggles;
+mod trace;
+#[cfg(unix)]
+mod tui;
+mod tutorial;
+mod victory;
+mod volatility;
+mod watchdog;
+mod wave;
+mod web;
+
+use autosave::Saved;
+use date::Date;
+use editor::Editor;
+use language::Language;
+use screen::Screen;
+use settings::Settings;
+use sounds::Sounds;
+use title::Choice as TitleChoice;
+use sudoku::generator::Target;
+use sudoku::grid::{BoardSize, Grid, Variants};
+use sudoku::library::Difficulty;
+use sudoku::puzzle::Format;
+use sudoku::symmetry::Symmetry;
+use sudoku::{puzzle, state, theme, variant};
+
+
+
+const DEFAULT_SAVE_PATH: &str = "variant.txt";
+const DEFAULT_THEME_PATH: &str = "theme.toml";
+const DEFAULT_KEYS_PATH: &str = "keys.txt";
+
+
+fn main() {
+    let args = cli::Args::parse(&web::args()).unwrap_or_else(|err| web::fail(&err));
+
+    if args.flag("--self-test") {
+        let mut checks = self_test::run();
+        // Headless machines can't open the window the render check needs
+        if args.flag("--headless") {
+            std::process::exit(if self_test::report(&checks) { 0 } else { 1 });
+        }
+        macroquad::Window::new("Wave Function Collapse Sudoku self-test", async move {
+            checks.push(self_test::render().await);
+            std::process::exit(if self_test::report(&checks) { 0 } else { 1 });
+        });
+        return;
     }
-}
 
-impl Cell {
-    fn collapse(&mut self) -> u8 {
-        if self.possible_values.len() > 1 {
-            let value = *self.possible_values.choose(&mut ::rand::thread_rng()).unwrap();
-            self.possible_values = vec![value];
-        }
-        self.possible_values[0]
-    }
-
-    fn remove_possibility(&mut self, value: u8) -> Result<(), ()> {
-        if self.possible_values.len() > 1 {
-            self.possible_values = self.possible_values
-                .iter()
-                .filter_map(|val|
-                    if *val != value {
-                        Some(*val)
-                    } else {
-                        None
-                    }
-                )
-                .collect();
-        } else {
-            if self.possible_values[0] == value {
-                return Err(());
-            }
+    // A theme.toml in the working directory restyles the board unless --theme picks another
+    let default_theme = Some(DEFAULT_THEME_PATH).filter(|path| std::path::Path::new(path).exists());
+    if let Some(path) = args.value("--theme").or(default_theme) {
+        let theme = web::inline(path).map_or_else(|| theme::load(path), theme::parse);
+        theme::set(theme.unwrap_or_else(|err| web::fail(&format!("Invalid theme file {}", err))));
+    }
+    if let Some(path) = args.value("--save-theme") {
+        if let Err(err) = theme::save(path, &theme::current()) {
+            web::fail(&format!("Couldn't save theme file {}", err));
         }
-        Ok(())
     }
-}
 
-struct Grid {
-    cells: Vec<Cell>,
-}
+    // Likewise keys.txt rebinds keys unless --keys picks another file
+    let default_keys = Some(DEFAULT_KEYS_PATH).filter(|path| std::path::Path::new(path).exists());
+    if let Some(path) = args.value("--keys").or(default_keys) {
+        keys::set(keys::load(path).unwrap_or_else(|err| web::fail(&format!("Invalid keys file {}", err))));
+    }
+    if let Some(path) = args.value("--save-keys") {
+        if let Err(err) = keys::save(path) {
+            web::fail(&format!("Couldn't save keys file {}", err));
+        }
+    }
 
-impl Grid {
-    fn new() -> Self {
-        Self {
-            cells: vec![Cell::default(); 81]
+    input::set_digit_first(match args.value("--input") {
+        None | Some("cell") => false,
+        Some("digit") => true,
+        Some(mode) => web::fail(&format!("Unknown input mode '{}', expected cell or digit", mode)),
+    });
+
+    // On-screen text follows the locale unless --language picks another
+    language::set(args.value("--language").map_or_else(Language::detect, |code| {
+        Language::from_code(code).unwrap_or_else(|| {
+            let codes = Language::ALL.map(Language::code).join(", ");
+            web::fail(&format!("Unknown language '{}', expected one of {}", code, codes))
+        })
+    }));
+
+    let mut settings = match args.value("--preset") {
+        Some(preset) => settings::load(preset).unwrap_or_else(|err| {
+            let presets = settings::preset_names().collect::<Vec<_>>().join(", ");
+            web::fail(&format!("Invalid preset {}\nBundled presets: {}", err, presets));
+        }),
+        None => Settings::default(),
+    };
+
+    if let Some(side) = args.value("--size") {
+        let size = side.parse().ok().and_then(BoardSize::from_side);
+        let size = size.unwrap_or_else(|| web::fail(&format!("Unsupported board size: {}", side)));
+        let variants = &settings.variants;
+        if size != settings.size && (variants.regions.is_some() || !variants.constraints.is_empty()) {
+            web::fail(&format!("The preset rules are for {0}x{0} boards", settings.size.side()));
         }
+        settings.size = size;
     }
+    let size = settings.size;
 
-    fn is_resolve(&self) -> bool {
-        !self.cells.iter().any(|c| c.possible_values.len() > 1)
+    if let Some(path) = args.value("--variant") {
+        let variants = web::inline(path).map_or_else(|| variant::load(path, size), |text| variant::parse(text, size));
+        settings.variants = variants.unwrap_or_else(|err| web::fail(&format!("Invalid variant file {}", err)));
+    }
+    let variants = &mut settings.variants;
+    variants.diagonal |= args.flag("--diagonal");
+    variants.windoku |= args.flag("--windoku");
+    variants.anti_knight |= args.flag("--anti-knight");
+    variants.anti_king |= args.flag("--anti-king");
+    variants.non_consecutive |= args.flag("--non-consecutive");
+    variants.disjoint_groups |= args.flag("--disjoint-groups");
+    if let Some(path) = args.value("--regions") {
+        let regions = web::inline(path)
+            .map_or_else(|| variant::load_regions(path, size), |text| variant::parse_region_layout(text, size));
+        variants.regions = Some(regions.unwrap_or_else(|err| web::fail(&format!("Invalid region file {}", err))));
     }
 
-    fn get_lowest_entropy_cell_idx(&self) -> usize {
-        let min = self.cells
-            .iter()
-            .fold(9usize, |min, c| {
-                if c.possible_values.len() == 1 {
-                    min
-                } else {
-                    c.possible_values.len().min(min)
-                }
-            });
-
-        self.cells
-            .iter()
-            .enumerate()
-            .filter_map(|(i, c)|
-                if c.possible_values.len() == min {
-                    Some(i)
-                } else {
-                    None
-                })
-                .choose(&mut ::rand::thread_rng())
-                .unwrap()
-    }
-
-    fn propagate(&mut self, idx: usize) -> Result<(), ()> {
-        if self.cells[idx].possible_values.len() == 1 {
-            self.cells[idx].propagated = true;
-            let cell_value = self.cells[idx].possible_values[0];
-
-            for idx in Grid::iter_col(idx) {
-                if !self.cells[idx].propagated {
-                    self.cells[idx].remove_possibility(cell_value)?;
-                    self.propagate(idx)?;
-                }
-            }
-            for idx in Grid::iter_row(idx) {
-                if !self.cells[idx].propagated {
-                    self.cells[idx].remove_possibility(cell_value)?;
-                    self.propagate(idx)?;
-                }
-            }
-            for idx in Grid::iter_square(idx) {
-                if !self.cells[idx].propagated {
-                    self.cells[idx].remove_possibility(cell_value)?;
-                    self.propagate(idx)?;
-                }
-            }
+    if let Some(tick_seconds) = args.parsed("--speed", "seconds") {
+        settings.tick_seconds = tick_seconds;
+    }
+    if let Some(timeout) = args.parsed("--watchdog", "seconds") {
+        settings.watchdog_timeout = timeout;
+    }
+    if let Some(path) = args.value("--save-preset") {
+        if let Err(err) = settings::save(path, &settings) {
+            web::fail(&format!("Couldn't save preset file {}", err));
         }
-        Ok(())
     }
-
-    fn iter_row(idx: usize) -> impl Iterator<Item = usize> {
-        (0..81).filter(move |i| idx / 9 == i / 9)
+    let Settings { size, variants, tick_seconds, watchdog_timeout } = settings;
+    let restored = args.value("--restore")
+        .map(|path| state::load(path).unwrap_or_else(|err| web::fail(&format!("Invalid saved solve {}", err))));
+
+    // Picks a puzzle in .sdm collections
+    let number = args.value("--puzzle-number").map_or(Some(1), |number| number.parse().ok().filter(|&n| n > 0));
+    let number = number.unwrap_or_else(|| web::fail("Invalid puzzle number, they count from 1"));
+    let givens = match args.value("--puzzle") {
+        Some(path) => web::inline(path)
+            .map_or_else(|| puzzle::load(path, size, number), |text| puzzle::parse_format(text, Format::Plain, size, number))
+            .unwrap_or_else(|err| web::fail(&format!("Invalid puzzle file {}", err))),
+        None => match args.value("--givens") {
+            Some(text) => puzzle::parse(text, size).unwrap_or_else(|err| web::fail(&format!("Invalid givens: {}", err))),
+            None => vec![None; size.cell_count()],
+        },
+    };
+    if Grid::with_givens(size, variants.clone(), givens.clone()).is_err() {
+        web::fail("The puzzle givens break the variant rules");
     }
 
-    fn iter_col(idx: usize) -> impl Iterator<Item = usize> {
-        (0..81).filter(move |i| idx % 9 == i % 9)
+    let seed = args.parsed("--seed", "a whole number");
+    // Everyone gets the same grid on the same day
+    let daily = args.flag("--daily").then(Date::today);
+    if daily.is_some() && seed.is_some() {
+        web::fail("--daily picks the seed, it can't be given too");
     }
-
-    fn iter_square(idx: usize) ->  impl Iterator<Item = usize> {
-        let get_square_idx = |idx: usize| {
-            27 * ((idx / 9) / 3) + 3 * ((idx % 9) / 3)
-        };
-        let square_idx = get_square_idx(idx);
-        (0..81).filter(move |&i| get_square_idx(i) == square_idx)
+    let seed = seed.or(daily.map(|date| date.seed()));
+
+    let backjump = args.flag("--backjump");
+    let edit = args.flag("--edit");
+    let race = args.flag("--race");
+    let race_grids = args.value("--race").map_or(Some(race::DEFAULT_GRIDS), |grids| grids.parse().ok().filter(|&grids| grids > 0));
+    let race_grids = race_grids.unwrap_or_else(|| web::fail("Invalid number of grids to race"));
+    let compare = args.flag("--compare");
+    let heuristics = args.value("--compare").map_or(Some(compare::DEFAULT_HEURISTICS), compare::parse);
+    let heuristics = heuristics.unwrap_or_else(|| web::fail("Invalid heuristics to compare, expected two of min-entropy, scan or random"));
+    if let Some(path) = args.value("--save-variant").filter(|_| !edit) {
+        if let Err(err) = variant::save(path, &variants, size) {
+            web::fail(&format!("Couldn't save variant file {}", err));
+        }
     }
 
-    fn end_propagation(&mut self) {
-        self.cells.iter_mut().for_each(|c| c.propagated = false);
+    if let Some(path) = args.value("--trace") {
+        if let Err(err) = trace::start(path) {
+            web::fail(&format!("Couldn't start the trace {}", err));
+        }
     }
 
-    fn draw(&self) {
-        let grid_position = (
-            screen_width() / 2. - CELL_SIZE * 4.5,
-            screen_height() / 2. - CELL_SIZE * 4.5,
-        );
-        for i in 0..10 {
-            let thickness = if i % 3 == 0 {
-                BIG_LINES_THICKNESS
-            } else {
-                SMALL_LINES_THICKNESS
-            };
-            draw_line(
-                grid_position.0,
-                grid_position.1 + i as f32 * CELL_SIZE,
-                grid_position.0 + 9. * CELL_SIZE,
-                grid_position.1 + i as f32 * CELL_SIZE,
-                thickness,
-                GRID_COLOR);
-            draw_line(
-                grid_position.0 + i as f32 * CELL_SIZE,
-                grid_position.1,
-                grid_position.0 + i as f32 * CELL_SIZE,
-                grid_position.1 + 9. * CELL_SIZE,
-                thickness,
-                GRID_COLOR
-            );
+    if let Some(path) = args.value("--metrics") {
+        if let Err(err) = metrics::start(path) {
+            web::fail(&format!("Couldn't open the metrics {}", err));
         }
+    }
 
-        for idx in 0..81 {
-            let values = &self.cells[idx].possible_values;
-
-            if values.len() == 1 {
-                draw_text(
-                    &values[0].to_string(),
-                    grid_position.0 + (idx % 9) as f32 * CELL_SIZE + BIG_NUM_OFFSET.0,
-                    grid_position.1 + (idx / 9) as f32 * CELL_SIZE + BIG_NUM_OFFSET.1,
-                    BIG_FONT_SIZE,
-                    BIG_NUM_COLOR);
-            } else {
-                for (i, v) in values.iter().enumerate() {
-                    draw_text(
-                        &v.to_string(),
-                        grid_position.0 + (idx % 9) as f32 * CELL_SIZE + (i % 3) as f32 * CELL_SIZE / 3.5 + SMALL_NUM_OFFSET.0,
-                        grid_position.1 + (idx / 9) as f32 * CELL_SIZE + (i / 3) as f32 * CELL_SIZE / 3.5 + SMALL_NUM_OFFSET.1,
-                        SMALL_FONT_SIZE,
-                        SMALL_NUM_COLOR);
-                }
-            }
+    if args.flag("--bench") {
+        let runs = args.parsed("--bench", "a number of runs").unwrap_or(bench::DEFAULT_RUNS);
+        bench::run(runs, puzzle_grid(size, variants, &givens, seed, false));
+        return;
+    }
+    if let Some(count) = args.value("--generate") {
+        let count = count.parse().ok().filter(|&count| count > 0);
+        let count = count.unwrap_or_else(|| web::fail("Invalid number of puzzles to generate"));
+        // Printed so the same puzzles can be generated again
+        let symmetry = args.value("--symmetry").map_or(Some(Symmetry::default()), Symmetry::parse);
+        let symmetry = symmetry.unwrap_or_else(|| web::fail("Invalid symmetry, expected none, rotational, mirror or diagonal"));
+        let clues = args.value("--clues").map(|clues| clues.parse().ok().filter(|&clues| clues > 0 && clues <= size.cell_count()));
+        let clues = clues.map(|clues| clues.unwrap_or_else(|| web::fail("Invalid number of clues")));
+        let difficulty = args.value("--difficulty").map(|difficulty| {
+            Difficulty::parse(difficulty).unwrap_or_else(|| web::fail("Invalid difficulty, expected easy, medium, hard or expert"))
+        });
+        if clues.is_some() && args.flag("--minimal") {
+            web::fail("Puzzles with a set number of clues can't be made minimal");
         }
+        let target = Target { symmetry, clues, difficulty };
+        let seed = seed.unwrap_or_else(::rand::random);
+        eprintln!("Seed {}", seed);
+        if let Err(err) = batch::run(count, size, &variants, seed, target, args.value("--sdm"), args.flag("--minimal")) {
+            web::fail(&format!("Couldn't generate puzzles {}", err));
+        }
+        return;
     }
-}
-
-#[macroquad::main("Wave Function Collapse Sudoku")]
-async fn main() {
-
-    let mut grid = Grid::new();
-
-    let mut tick = get_time();
-    loop {
-        clear_background(BACKGROUND_COLOR);
-
-        if get_time() - tick > TICK_SECONDS {
-            tick = get_time();
-            if !grid.is_resolve() {
-                let cell_idx = grid.get_lowest_entropy_cell_idx();
-                grid.cells[cell_idx].collapse();
-                if grid.propagate(cell_idx).is_ok() {
-                    grid.end_propagation();
-                } else {
-                    // Reset grid in case of unresolvable cell
-                    grid = Grid::new();
+    if let Some(dir) = args.value("--dump-frames") {
+        let resolution = args.value("--resolution").map_or(Some(frames::DEFAULT_RESOLUTION), frames::parse_resolution);
+        let resolution = resolution.unwrap_or_else(|| web::fail("Invalid resolution, expected WIDTHxHEIGHT"));
+        let fps = args.value("--fps").map_or(Some(frames::DEFAULT_FPS), |fps| fps.parse().ok().filter(|&fps| fps > 0.));
+        let fps = fps.unwrap_or_else(|| web::fail("Invalid number of frames per second"));
+        // Printed so the same frames can be rendered again
+        let seed = seed.unwrap_or_else(::rand::random);
+        println!("Seed {}", seed);
+        let grid = puzzle_grid(size, variants, &givens, Some(seed), backjump);
+        let dir = dir.to_string();
+        macroquad::Window::new("Wave Function Collapse Sudoku frame dump", async move {
+            match frames::dump(&dir, grid, resolution, fps, tick_seconds).await {
+                Ok(count) => {
+                    println!("{} frames written to {}, to make a video:", count, dir);
+                    println!("ffmpeg -framerate {} -i {}/frame-%06d.png -pix_fmt yuv420p solve.mp4", fps, dir);
+                    std::process::exit(0);
                 }
+                Err(err) => web::fail(&format!("Couldn't dump frames {}", err)),
             }
+        });
+        return;
+    }
+    if args.flag("--tui") {
+        #[cfg(unix)]
+        if let Err(err) = tui::run(puzzle_grid(size, variants, &givens, None, backjump), seed, tick_seconds) {
+            web::fail(&format!("Terminal error: {}", err));
         }
-
-        if is_key_pressed(RESET_GRID_KEY) {
-            grid = Grid::new();
+        #[cfg(not(unix))]
+        web::fail("The terminal frontend needs a Unix terminal");
+        return;
+    }
+    if let Some(path) = args.value("--export-replay") {
+        if let Err(err) = replay::export(path, puzzle_grid(size, variants, &givens, seed, backjump)) {
+            web::fail(&format!("Couldn't export replay {}", err));
         }
+        return;
+    }
 
-        grid.draw();
-        draw_text(
-            &format!("Press [{:?}] to reset",
-            RESET_GRID_KEY),
-            0., TEXT_FONT_SIZE,
-            TEXT_FONT_SIZE,
-            TEXT_COLOR
-        );
+    // The editor saves where --save-variant points to
+    let save_path = args.value("--save-variant").unwrap_or(DEFAULT_SAVE_PATH).to_string();
+    macroquad::Window::new("Wave Function Collapse Sudoku", async move {
+        // Closing the window autosaves the game first, see run
+        prevent_quit();
+        let mut sounds = Sounds::load().await;
+        // Saved solves go straight to the solver, --edit to the editor, anything else
+        // starts on the title screen
+        let mut resumed = restored.map(|grid| Saved { grid, play: None });
+        let mut screen = match (&resumed, edit, race, compare) {
+            (Some(_), ..) => Screen::Game { browse: false },
+            (None, true, ..) => Screen::Editor,
+            (None, false, true, _) => Screen::Race,
+            (None, false, false, true) => Screen::Compare,
+            (None, false, false, false) => Screen::Title,
+        };
+        let (mut size, mut variants, mut givens) = (size, variants, givens);
+        let mut editor = Editor::new(size, variants.clone(), save_path.clone());
+        loop {
+            // The game left at the last exit brings its own board and rules
+            if let Some(saved) = &resumed {
+                size = saved.grid.size;
+                variants = saved.grid.variants.clone();
+                givens = saved.grid.givens().to_vec();
+                editor = Editor::new(size, variants.clone(), save_path.clone());
+            }
+            screen = match screen {
+                Screen::Title => match title::run().await {
+                    TitleChoice::Resume => {
+                        resumed = autosave::resume();
+                        Screen::Game { browse: false }
+                    }
+                    TitleChoice::Solve => Screen::Game { browse: false },
+                    TitleChoice::Puzzles => Screen::Game { browse: true },
+                    TitleChoice::Race => Screen::Race,
+                    TitleChoice::Compare => Screen::Compare,
+                    TitleChoice::Analytics => Screen::Analytics,
+                    TitleChoice::Tutorials => Screen::Tutorials,
+                    TitleChoice::Editor => Screen::Editor,
+                    TitleChoice::Options => Screen::Options,
+                    TitleChoice::Quit => std::process::exit(0),
+                },
+                Screen::Options => {
+                    options::run(&mut sounds).await;
+                    Screen::Title
+                }
+                Screen::Analytics => {
+                    analytics::run().await;
+                    Screen::Title
+                }
+                Screen::Tutorials => {
+                    tutorial::run().await;
+                    Screen::Title
+                }
+                Screen::Game { browse } => {
+                    let seed = seed.unwrap_or_else(::rand::random);
+                    let start = match resumed.take() {
+                        Some(mut saved) => {
+                            saved.grid.backjumping = backjump;
+                            (saved.grid, saved.play, browse)
+                        }
+                        None => (puzzle_grid(size, variants.clone(), &givens, Some(seed), backjump), None, browse),
+                    };
+                    let (solved, next) = game::run(start, &mut givens, seed, daily, tick_seconds, watchdog_timeout, &mut sounds).await;
+                    editor.set_variants(solved.clone());
+                    variants = solved;
+                    next
+                }
+                Screen::Race => {
+                    let new_grid = |seed| puzzle_grid(size, variants.clone(), &givens, Some(seed), backjump);
+                    race::run(race_grids, new_grid, tick_seconds).await
+                }
+                Screen::Compare => {
+                    let new_grid = |seed| puzzle_grid(size, variants.clone(), &givens, Some(seed), backjump);
+                    compare::run(heuristics, new_grid, tick_seconds).await
+                }
+                Screen::Editor => {
+                    variants = editor.run().await;
+                    Screen::Game { browse: false }
+                }
+            };
+        }
+    });
+}
 
-        next_frame().await;
+// Givens are dropped when the edited rules no longer allow them
+fn puzzle_grid(size: BoardSize, variants: Variants, givens: &[Option<u8>], seed: Option<u64>, backjumping: bool) -> Grid {
+    let mut grid = Grid::with_givens(size, variants.clone(), givens.to_vec())
+        .unwrap_or_else(|_| Grid::new(size, variants));
+    grid.backjumping = backjumping;
+    if let Some(seed) = seed {
+        grid.seed(seed);
     }
+    grid
 }
@@ -0,0 +1,66 @@
+use macroquad::prelude::*;
+
+use sudoku::{text, theme};
+
+use crate::keys::{self, Action};
+
+
+// Panel of items in the middle of the screen, in the style of the puzzle browser,
+// picked with the arrows and the pick key or the mouse
+#[derive(Default)]
+pub struct Menu {
+    pub selected: usize,
+}
+
+impl Menu {
+    // Panel and a box for each item under the title
+    fn boxes(title: &str, items: &[String]) -> (Rect, Vec<Rect>) {
+        let style = &theme::current().browser;
+        let width = items
+            .iter()
+            .map(String::as_str)
+            .chain(std::iter::once(title))
+            .map(|line| text::measure(line, style.font_size).width)
+            .fold(0., f32::max) + 2. * style.padding;
+        // A blank line between the title and the items
+        let height = (items.len() + 2) as f32 * style.font_size + 2. * style.padding;
+        let panel = Rect::new((screen_width() - width) / 2., (screen_height() - height) / 2., width, height);
+        let boxes = (0..items.len())
+            .map(|i| Rect::new(panel.x, panel.y + style.padding + (i + 2) as f32 * style.font_size, width, style.font_size))
+            .collect();
+        (panel, boxes)
+    }
+
+    // Item picked this frame, if any
+    pub fn update(&mut self, title: &str, items: &[String]) -> Option<usize> {
+        if is_key_pressed(KeyCode::Down) {
+            self.selected = (self.selected + 1) % items.len();
+        }
+        if is_key_pressed(KeyCode::Up) {
+            self.selected = (self.selected + items.len() - 1) % items.len();
+        }
+        self.selected = self.selected.min(items.len() - 1);
+
+        let position = mouse_position().into();
+        let hovered = Self::boxes(title, items).1.iter().position(|rect| rect.contains(position));
+        if let Some(i) = hovered {
+            self.selected = i;
+        }
+        let clicked = hovered.is_some() && is_mouse_button_pressed(MouseButton::Left);
+        (clicked || keys::pressed(Action::Pick)).then_some(self.selected)
+    }
+
+    pub fn draw(&self, title: &str, items: &[String]) {
+        let style = &theme::current().browser;
+        let (panel, boxes) = Self::boxes(title, items);
+        draw_rectangle(panel.x, panel.y, panel.w, panel.h, style.panel_color);
+        let baseline = |y: f32| y + style.font_size - style.font_size / 4.;
+        text::draw(title, panel.x + style.padding, baseline(panel.y + style.padding), style.font_size, style.heading_color);
+        for (i, (item, rect)) in items.iter().zip(boxes).enumerate() {
+            if i == self.selected {
+                draw_rectangle(rect.x, rect.y, rect.w, rect.h, style.selected_color);
+            }
+            text::draw(item, rect.x + style.padding, baseline(rect.y), style.font_size, style.text_color);
+        }
+    }
+}
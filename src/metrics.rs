@@ -0,0 +1,59 @@
+use std::cell::RefCell;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+
+use sudoku::grid::Grid;
+
+
+const HEADER: &str = "seed,seconds,collapses,eliminations,restarts,heuristic";
+
+
+// Rows appended to a CSV file with --metrics, one for each grid solved, watched,
+// raced or in the terminal, to look at long sessions in a spreadsheet:
+//
+//   seed,seconds,collapses,eliminations,restarts,heuristic
+//   1234,3.52,41,812,2,min-entropy
+//
+// Collapses and eliminations are those of the attempt that solved the grid
+struct Metrics {
+    path: String,
+    file: File,
+}
+
+thread_local! {
+    static METRICS: RefCell<Option<Metrics>> = const { RefCell::new(None) };
+}
+
+// Appends to the file if it exists, the header only going at the top of a new one
+pub fn start(path: &str) -> Result<(), String> {
+    let error = |err: std::io::Error| format!("{}: {}", path, err);
+    let mut file = OpenOptions::new().create(true).append(true).open(path).map_err(error)?;
+    if file.metadata().map_err(error)?.len() == 0 {
+        writeln!(file, "{}", HEADER).map_err(error)?;
+    }
+    METRICS.with(|current| *current.borrow_mut() = Some(Metrics { path: path.to_string(), file }));
+    Ok(())
+}
+
+// Writes the row of a grid just solved, the log stopping at the first error
+pub fn record(grid: &Grid, seed: u64, seconds: f64, restarts: usize) {
+    METRICS.with(|current| {
+        let mut current = current.borrow_mut();
+        let Some(metrics) = current.as_mut() else {
+            return;
+        };
+        let row = format!(
+            "{},{:.2},{},{},{},{}",
+            seed,
+            seconds,
+            grid.collapses().len(),
+            grid.eliminations,
+            restarts,
+            grid.heuristic.name()
+        );
+        if let Err(err) = writeln!(metrics.file, "{}", row) {
+            eprintln!("Couldn't write the metrics {}: {}", metrics.path, err);
+            *current = None;
+        }
+    });
+}
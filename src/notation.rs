@@ -0,0 +1,87 @@
+use std::fmt;
+
+use crate::grid::BoardSize;
+
+
+// Row and column of a cell, counted from 0. Displayed as R4C7, or G4 in A1 style where
+// columns are letters and rows are numbers counted from 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Coord {
+    pub row: usize,
+    pub col: usize,
+}
+
+impl Coord {
+    pub fn new(row: usize, col: usize) -> Self {
+        Self { row, col }
+    }
+
+    pub fn from_index(idx: usize, side: usize) -> Self {
+        Self { row: idx / side, col: idx % side }
+    }
+
+    pub fn index(self, side: usize) -> usize {
+        self.row * side + self.col
+    }
+
+    // Row of boxes holding the cell
+    pub fn band(self, size: BoardSize) -> usize {
+        self.row / size.box_height
+    }
+
+    // Column of boxes holding the cell
+    pub fn stack(self, size: BoardSize) -> usize {
+        self.col / size.box_width
+    }
+
+    // Boxes are numbered left to right, then top to bottom
+    pub fn box_index(self, size: BoardSize) -> usize {
+        self.band(size) * size.box_height + self.stack(size)
+    }
+
+    pub fn is_adjacent(self, other: Coord) -> bool {
+        self.row.abs_diff(other.row) + self.col.abs_diff(other.col) == 1
+    }
+
+    pub fn a1(self) -> String {
+        format!("{}{}", (b'A' + self.col as u8) as char, self.row + 1)
+    }
+
+    // Read R4C7 (in any case) or A1 style G4
+    pub fn parse(word: &str, side: usize) -> Result<Self, String> {
+        let invalid = || format!("invalid cell '{}'", word);
+        let word = word.to_ascii_lowercase();
+        let rc = word
+            .strip_prefix('r')
+            .and_then(|rest| rest.split_once('c'))
+            .and_then(|(row, col)| Some((row.parse::<usize>().ok()?, col.parse::<usize>().ok()?)));
+        let (row, col) = match rc {
+            Some(rc) => rc,
+            None => {
+                let mut chars = word.chars();
+                let col = chars.next().filter(char::is_ascii_lowercase).ok_or_else(invalid)?;
+                let row = chars.as_str().parse::<usize>().map_err(|_| invalid())?;
+                (row, (col as u8 - b'a') as usize + 1)
+            }
+        };
+        if !(1..=side).contains(&row) || !(1..=side).contains(&col) {
+            return Err(invalid());
+        }
+        Ok(Self::new(row - 1, col - 1))
+    }
+}
+
+impl fmt::Display for Coord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "R{}C{}", self.row + 1, self.col + 1)
+    }
+}
+
+// Cells as written in variant files, lowercase R4C7 style separated by spaces
+pub fn format_cells(cells: &[usize], side: usize) -> String {
+    let words: Vec<String> = cells
+        .iter()
+        .map(|&idx| Coord::from_index(idx, side).to_string().to_lowercase())
+        .collect();
+    words.join(" ")
+}
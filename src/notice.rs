@@ -0,0 +1,27 @@
+use macroquad::prelude::*;
+
+use sudoku::text;
+use sudoku::theme;
+
+
+const SHOWN_SECONDS: f64 = 6.;
+
+
+// Message shown at the bottom of the screen for a few seconds
+pub struct Notice {
+    text: String,
+    since: f64,
+}
+
+impl Notice {
+    pub fn new(text: String, time: f64) -> Self {
+        Self { text, since: time }
+    }
+
+    pub fn draw(&self, time: f64) {
+        let text = &theme::current().text;
+        if time - self.since < SHOWN_SECONDS {
+            text::draw(&self.text, 0., screen_height() - text.font_size / 2., text.font_size, text.message_color);
+        }
+    }
+}
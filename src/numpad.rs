@@ -0,0 +1,64 @@
+use macroquad::prelude::*;
+
+use sudoku::grid::BoardSize;
+use sudoku::layout::Layout;
+use sudoku::{text, theme};
+
+
+const CLEAR_LABEL: &str = "x";
+// Space between keys, relative to the cell size
+const GAP: f32 = 0.08;
+
+
+// On-screen digits for touch screens, popping up beside the selected cell once the
+// screen has been touched. Taps come in as mouse clicks
+#[derive(Default)]
+pub struct Numpad {
+    touched: bool,
+}
+
+impl Numpad {
+    // Digits 1 up to the side then the clear key, laid out in as many columns as a
+    // box has, right of the cell when there's room, left of it otherwise
+    fn keys(size: BoardSize, layout: &Layout, idx: usize) -> Vec<Rect> {
+        let side = size.side();
+        let columns = size.box_width;
+        let rows = (side + 1).div_ceil(columns);
+        let key = layout.cell_size;
+        let (width, height) = (columns as f32 * key, rows as f32 * key);
+
+        let cell = layout.cell_position(idx);
+        let x = if cell.x + key + width <= screen_width() { cell.x + key } else { cell.x - width };
+        let y = cell.y.min(screen_height() - height).max(0.);
+        (0..=side)
+            .map(|i| Rect::new(x + (i % columns) as f32 * key, y + (i / columns) as f32 * key, key, key))
+            .collect()
+    }
+
+    // Value tapped for the selected cell, Some(None) to clear it
+    pub fn tap(&mut self, size: BoardSize, layout: &Layout, selected: Option<usize>) -> Option<Option<u8>> {
+        self.touched |= !touches().is_empty();
+        let idx = selected.filter(|_| self.touched && is_mouse_button_pressed(MouseButton::Left))?;
+        let position = mouse_position().into();
+        let i = Self::keys(size, layout, idx).iter().position(|key| key.contains(position))?;
+        Some((i < size.side()).then(|| i as u8 + 1))
+    }
+
+    pub fn draw(&self, size: BoardSize, layout: &Layout, selected: Option<usize>) {
+        let Some(idx) = selected.filter(|_| self.touched) else {
+            return;
+        };
+        let style = &theme::current().numpad;
+        let gap = GAP * layout.cell_size;
+        let font_size = style.font_size * layout.scale();
+        for (i, key) in Self::keys(size, layout, idx).iter().enumerate() {
+            draw_rectangle(key.x, key.y, key.w, key.h, style.panel_color);
+            draw_rectangle(key.x + gap / 2., key.y + gap / 2., key.w - gap, key.h - gap, style.key_color);
+            let label = if i < size.side() { (i + 1).to_string() } else { CLEAR_LABEL.to_string() };
+            let dimensions = text::measure(&label, font_size);
+            let x = key.x + (key.w - dimensions.width) / 2.;
+            let y = key.y + (key.h + dimensions.offset_y) / 2.;
+            text::draw(&label, x, y, font_size, style.digit_color);
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use macroquad::prelude::*;
+
+use sudoku::theme;
+
+use crate::input;
+use crate::keys::{self, Action};
+use crate::language::{self, tr, Language, Text};
+use crate::menu::Menu;
+use crate::sounds::Sounds;
+
+
+#[derive(Clone, Copy)]
+enum Setting {
+    Language,
+    Input,
+    Theme,
+    Palette,
+    Sound,
+    Back,
+}
+
+const SETTINGS: [Setting; 6] = [Setting::Language, Setting::Input, Setting::Theme, Setting::Palette, Setting::Sound, Setting::Back];
+
+fn label(setting: Setting, sounds: &Sounds) -> String {
+    match setting {
+        Setting::Language => tr(Text::OptionLanguage, &[&language::current().name()]),
+        Setting::Input => {
+            let mode = tr(if input::digit_first() { Text::InputDigit } else { Text::InputCell }, &[]);
+            tr(Text::OptionInput, &[&mode])
+        }
+        Setting::Theme => tr(Text::OptionTheme, &[]),
+        Setting::Palette => tr(Text::OptionPalette, &[&theme::current().palette.name.name()]),
+        Setting::Sound => tr(Text::OptionSound, &[&tr(if sounds.muted() { Text::Off } else { Text::On }, &[])]),
+        Setting::Back => tr(Text::Back, &[]),
+    }
+}
+
+// Moves a setting on to its next value
+fn change(setting: Setting, sounds: &mut Sounds) {
+    match setting {
+        Setting::Language => {
+            let i = Language::ALL.iter().position(|&language| language == language::current()).unwrap();
+            language::set(Language::ALL[(i + 1) % Language::ALL.len()]);
+        }
+        Setting::Input => input::set_digit_first(!input::digit_first()),
+        Setting::Theme => theme::swap(),
+        Setting::Palette => theme::set_palette(theme::current().palette.name.next()),
+        Setting::Sound => sounds.toggle_mute(),
+        Setting::Back => {}
+    }
+}
+
+// Settings also reachable with their keys in game, each picked to go through its
+// values, until going back to the title
+pub async fn run(sounds: &mut Sounds) {
+    let mut menu = Menu::default();
+    loop {
+        if is_quit_requested() {
+            std::process::exit(0);
+        }
+        let items: Vec<String> = SETTINGS.iter().map(|&setting| label(setting, sounds)).collect();
+        let title = tr(Text::OptionsTitle, &[&keys::label(Action::Pick), &keys::label(Action::Back)]);
+
+        clear_background(theme::current().board.background_color);
+        let picked = menu.update(&title, &items);
+        menu.draw(&title, &items);
+        match picked.map(|i| SETTINGS[i]) {
+            Some(Setting::Back) => return,
+            Some(setting) => change(setting, sounds),
+            None if keys::pressed(Action::Back) => return,
+            None => {}
+        }
+        next_frame().await;
+    }
+}
@@ -0,0 +1,467 @@
+use macroquad::prelude::*;
+
+use sudoku::grid::Grid;
+use sudoku::hints::{self, Hint};
+use sudoku::json::Value;
+use sudoku::layout::{Layout, Margins};
+use sudoku::library::Difficulty;
+use sudoku::{text, theme};
+
+use crate::clipboard;
+use crate::hotbar::Hotbar;
+use crate::input;
+use crate::keys::{self, Action};
+use crate::language::{tr, Text};
+use crate::numpad::Numpad;
+use crate::records;
+
+
+// Moves kept for undoing, the oldest forgotten past this
+const HISTORY_LIMIT: usize = 10_000;
+
+
+#[derive(Clone, Copy)]
+struct Move {
+    idx: usize,
+    before: Option<u8>,
+    after: Option<u8>,
+}
+
+// Player filling the cells the solver left open, those it had resolved staying locked
+pub struct Play {
+    locked: Vec<Option<u8>>,
+    entries: Vec<Option<u8>>,
+    // Filled cells clashing with a house or a variant constraint
+    conflicts: Vec<bool>,
+    selected: Option<usize>,
+    numpad: Numpad,
+    hotbar: Hotbar,
+    history: Vec<Move>,
+    // Moves undone since the last one played, most recent last
+    undone: Vec<Move>,
+    // Of the library puzzle being played
+    pub difficulty: Option<Difficulty>,
+    // Time played, which stops once every cell is filled without conflicts
+    pub seconds: f64,
+    solved: bool,
+    // Digits entries are checked against, none unless the locked digits leave a single
+    // solution: entries are then only wrong when they conflict
+    solution: Option<Vec<u8>>,
+    // Entries found wrong by the last check, until they're changed
+    wrong: Vec<bool>,
+    pub mistakes: usize,
+    // Candidates noted in the open cells, kept up with every digit placed while shown
+    notes: bool,
+    marks: Vec<Vec<u8>>,
+    // Shown until the next move
+    hint: Option<Hint>,
+}
+
+impl Play {
+    fn with_cells(grid: &Grid, locked: Vec<Option<u8>>, entries: Vec<Option<u8>>, difficulty: Option<Difficulty>) -> Self {
+        let solution = Grid::with_givens(grid.size, grid.variants.clone(), locked.clone())
+            .ok()
+            .and_then(|mut locked| match locked.count_solutions(2) {
+                1 => locked.solution(),
+                _ => None,
+            });
+        let mut play = Self {
+            conflicts: Vec::new(),
+            wrong: vec![false; locked.len()],
+            locked,
+            entries,
+            selected: None,
+            numpad: Numpad::default(),
+            hotbar: Hotbar::default(),
+            history: Vec::new(),
+            undone: Vec::new(),
+            difficulty,
+            seconds: 0.,
+            solved: false,
+            solution,
+            mistakes: 0,
+            notes: false,
+            marks: Vec::new(),
+            hint: None,
+        };
+        play.refresh(grid);
+        play.solved = play.is_solved();
+        play
+    }
+
+    pub fn new(grid: &Grid) -> Self {
+        let locked: Vec<Option<u8>> = grid.cells
+            .iter()
+            .map(|cell| match cell.possible_values[..] {
+                [value] => Some(value),
+                _ => None,
+            })
+            .collect();
+        let entries = vec![None; locked.len()];
+        Self::with_cells(grid, locked, entries, None)
+    }
+
+    // Puzzle played from its givens alone, whatever the solver resolved
+    pub fn puzzle(grid: &Grid, difficulty: Option<Difficulty>) -> Self {
+        let locked = grid.givens().to_vec();
+        let entries = vec![None; locked.len()];
+        Self::with_cells(grid, locked, entries, difficulty)
+    }
+
+    // Locked and entered digits as JSON, 0 for the empty cells, with the time played
+    // and the mistakes found
+    pub fn serialize(&self) -> String {
+        let list = |values: &[Option<u8>]| {
+            values.iter().map(|value| value.unwrap_or(0).to_string()).collect::<Vec<_>>().join(",")
+        };
+        let difficulty = self.difficulty.map_or("null".to_string(), |difficulty| format!("\"{}\"", difficulty.name()));
+        format!(
+            "{{\"locked\":[{}],\"entries\":[{}],\"seconds\":{:.1},\"mistakes\":{},\"notes\":{},\"difficulty\":{}}}",
+            list(&self.locked),
+            list(&self.entries),
+            self.seconds,
+            self.mistakes,
+            self.notes,
+            difficulty
+        )
+    }
+
+    // Games saved before the timer came in start it from zero, with no mistakes
+    pub fn from_value(value: &Value, grid: &Grid) -> Result<Self, String> {
+        let cells = |name: &str| -> Result<Vec<Option<u8>>, String> {
+            let values = value.get(name)?.as_array()?;
+            if values.len() != grid.cells.len() {
+                return Err(format!("expected {} {}, found {}", grid.cells.len(), name, values.len()));
+            }
+            values
+                .iter()
+                .map(|value| match value.as_int()? {
+                    0 => Ok(None),
+                    digit if digit as usize <= grid.size.side() => Ok(Some(digit)),
+                    digit => Err(format!("invalid digit {}", digit)),
+                })
+                .collect()
+        };
+        let difficulty = match value.get("difficulty") {
+            Ok(Value::String(name)) => Some(
+                Difficulty::ALL
+                    .into_iter()
+                    .find(|difficulty| difficulty.name() == name)
+                    .ok_or_else(|| format!("unknown difficulty '{}'", name))?,
+            ),
+            _ => None,
+        };
+        let mut play = Self::with_cells(grid, cells("locked")?, cells("entries")?, difficulty);
+        if let Ok(seconds) = value.get("seconds") {
+            play.seconds = seconds.as_int::<f64>()?;
+        }
+        if let Ok(mistakes) = value.get("mistakes") {
+            play.mistakes = mistakes.as_int()?;
+        }
+        if let Ok(Value::Bool(notes)) = value.get("notes") {
+            play.notes = *notes;
+            play.refresh(grid);
+        }
+        Ok(play)
+    }
+
+    // Marks the entries differing from the solution, or conflicting when there's none,
+    // counting those not marked yet as mistakes. Returns the number of wrong entries
+    pub fn check(&mut self) -> usize {
+        let mut wrong = 0;
+        for (idx, entry) in self.entries.iter().enumerate() {
+            let is_wrong = match (entry, &self.solution) {
+                (None, _) => false,
+                (Some(value), Some(solution)) => *value != solution[idx],
+                (Some(_), None) => self.conflicts[idx],
+            };
+            if is_wrong {
+                wrong += 1;
+                if !self.wrong[idx] {
+                    self.wrong[idx] = true;
+                    self.mistakes += 1;
+                }
+            }
+        }
+        wrong
+    }
+
+    // Cells filled with the digit of the solution, or without conflicts when there's none
+    pub fn correct(&self) -> Vec<bool> {
+        self.values()
+            .iter()
+            .enumerate()
+            .map(|(idx, value)| match (value, &self.solution) {
+                (None, _) => false,
+                (Some(value), Some(solution)) => *value == solution[idx],
+                (Some(_), None) => !self.conflicts[idx],
+            })
+            .collect()
+    }
+
+    fn highlighted(&self) -> Option<u8> {
+        match input::digit_first() {
+            true => self.hotbar.picked.flatten(),
+            false => self.selected.and_then(|idx| self.locked[idx].or(self.entries[idx])),
+        }
+    }
+
+    fn is_solved(&self) -> bool {
+        self.values().iter().all(Option::is_some) && !self.conflicts.contains(&true)
+    }
+
+    // Check the filled cells again after they or the rules changed
+    pub fn refresh(&mut self, grid: &Grid) {
+        let values = self.values();
+        self.conflicts = grid.conflicts(&values);
+        self.hint = None;
+        if self.notes {
+            self.marks = grid.pencil_marks(&values);
+        }
+    }
+
+    // Next step from the filled cells, highlighted on the board
+    pub fn hint(&mut self, grid: &Grid) -> Option<&Hint> {
+        self.hint = hints::find(grid, &self.values());
+        self.hint.as_ref()
+    }
+
+    pub fn toggle_notes(&mut self, grid: &Grid) {
+        self.notes = !self.notes;
+        self.refresh(grid);
+    }
+
+    fn values(&self) -> Vec<Option<u8>> {
+        self.locked.iter().zip(&self.entries).map(|(locked, entry)| locked.or(*entry)).collect()
+    }
+
+    // Ctrl+Z takes back the last move and Ctrl+Y, or Ctrl+Shift+Z, plays it again
+    fn undo_redo(&mut self, grid: &Grid) {
+        if !clipboard::modifier_down() {
+            return;
+        }
+        let shift = is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
+        let redo = keys::pressed(Action::Redo) || (shift && keys::pressed(Action::Undo));
+        let (from, to) = match (redo, keys::pressed(Action::Undo)) {
+            (true, _) => (&mut self.undone, &mut self.history),
+            (false, true) => (&mut self.history, &mut self.undone),
+            (false, false) => return,
+        };
+        let Some(played) = from.pop() else {
+            return;
+        };
+        to.push(played);
+        self.entries[played.idx] = if redo { played.after } else { played.before };
+        self.wrong[played.idx] = false;
+        self.selected = Some(played.idx);
+        self.refresh(grid);
+    }
+
+    // Returns whether the last open cell was just filled in correctly
+    pub fn update(&mut self, grid: &Grid, layout: &Layout) -> bool {
+        if !self.solved {
+            self.seconds += get_frame_time() as f64;
+        }
+        self.undo_redo(grid);
+        self.enter(grid, layout);
+        let solved = !self.solved && self.is_solved();
+        self.solved |= solved;
+        solved
+    }
+
+    // Board margins with room for the hotbar in digit-first play
+    pub fn margins(grid: &Grid) -> Margins {
+        match input::digit_first() {
+            true => Hotbar::margins(grid.margins()),
+            false => grid.margins(),
+        }
+    }
+
+    fn enter(&mut self, grid: &Grid, layout: &Layout) {
+        if input::digit_first() {
+            self.paint(grid, layout);
+            return;
+        }
+        let tapped = self.numpad.tap(grid.size, layout, self.editable());
+        if tapped.is_none() && is_mouse_button_pressed(MouseButton::Left) {
+            self.selected = layout.cell_at(mouse_position().into());
+        }
+        let Some(idx) = self.editable() else {
+            while get_char_pressed().is_some() {}
+            return;
+        };
+
+        let typed = input::type_value(self.entries[idx], grid.size.side());
+        self.place(grid, idx, tapped.unwrap_or(typed));
+    }
+
+    // Selected cell unless it's locked, those being selected to pick out their digit
+    fn editable(&self) -> Option<usize> {
+        self.selected.filter(|&idx| self.locked[idx].is_none())
+    }
+
+    // Digit picked on the hotbar placed in the cell clicked, or taken out of it when
+    // it's already there. Clicking a locked digit picks it
+    fn paint(&mut self, grid: &Grid, layout: &Layout) {
+        if self.hotbar.update(grid.size.side(), layout, grid.margins()) || !is_mouse_button_pressed(MouseButton::Left) {
+            return;
+        }
+        let clicked = layout.cell_at(mouse_position().into());
+        if let Some(value) = clicked.and_then(|idx| self.locked[idx]) {
+            self.hotbar.picked = Some(Some(value));
+            return;
+        }
+        self.selected = clicked;
+        if let (Some(idx), Some(picked)) = (clicked, self.hotbar.picked) {
+            let entry = if self.entries[idx] == picked { None } else { picked };
+            self.place(grid, idx, entry);
+        }
+    }
+
+    fn place(&mut self, grid: &Grid, idx: usize, entry: Option<u8>) {
+        if entry != self.entries[idx] {
+            if self.history.len() == HISTORY_LIMIT {
+                self.history.remove(0);
+            }
+            self.history.push(Move { idx, before: self.entries[idx], after: entry });
+            self.undone.clear();
+            self.entries[idx] = entry;
+            self.wrong[idx] = false;
+            self.refresh(grid);
+        }
+    }
+
+    pub fn draw(&self, grid: &Grid, layout: &Layout) {
+        let theme = theme::current();
+        let tint = |idx: usize, color: Color| {
+            let p = layout.cell_position(idx);
+            draw_rectangle(p.x, p.y, layout.cell_size, layout.cell_size, color);
+        };
+        // Every cell holding the digit picked or selected, and with notes shown those
+        // where it's still a candidate
+        if let Some(digit) = self.highlighted() {
+            for (idx, value) in self.values().into_iter().enumerate() {
+                if value == Some(digit) {
+                    tint(idx, theme.selection.digit_color);
+                } else if value.is_none() && self.notes && self.marks[idx].contains(&digit) {
+                    tint(idx, theme.selection.candidate_color);
+                }
+            }
+        }
+        if let Some(hint) = &self.hint {
+            tint_hint(hint, layout);
+        }
+        for idx in (0..self.conflicts.len()).filter(|&idx| self.conflicts[idx]) {
+            tint(idx, theme.digits.conflict_color);
+        }
+        grid.draw_board(layout);
+
+        for (idx, (locked, entry)) in self.locked.iter().zip(&self.entries).enumerate() {
+            match (locked, entry) {
+                _ if grid.is_given(idx) => grid.draw_given(layout, idx),
+                (Some(value), _) => grid.draw_digit(layout, idx, *value, theme.digits.solved_color),
+                (None, Some(value)) if self.wrong[idx] => grid.draw_digit(layout, idx, *value, theme.digits.mistake_color),
+                (None, Some(value)) => grid.draw_digit(layout, idx, *value, theme.digits.entry_color),
+                (None, None) if self.notes => grid.draw_marks(layout, idx, &self.marks[idx]),
+                (None, None) => {}
+            }
+        }
+
+        if let Some(hint) = &self.hint {
+            mark_hint(grid, hint, layout);
+        }
+
+        if let Some(idx) = self.selected {
+            let p = layout.cell_position(idx);
+            let size = layout.cell_size;
+            draw_rectangle_lines(p.x, p.y, size, size, theme.selection.thickness * layout.scale(), theme.selection.color);
+        }
+        match input::digit_first() {
+            true => self.hotbar.draw(grid.size.side(), layout, grid.margins()),
+            false => self.numpad.draw(grid.size, layout, self.editable()),
+        }
+
+        let time = records::format_time(self.seconds);
+        let font_size = theme.text.font_size;
+        let x = screen_width() - text::measure(&time, font_size).width - font_size / 2.;
+        let color = if self.solved { theme.text.message_color } else { theme.text.color };
+        text::draw(&time, x, 3. * font_size, font_size, color);
+        if self.mistakes > 0 {
+            let mistakes = tr(Text::Mistakes, &[&self.mistakes]);
+            let x = screen_width() - text::measure(&mistakes, font_size).width - font_size / 2.;
+            text::draw(&mistakes, x, 4. * font_size, font_size, theme.digits.mistake_color);
+        }
+    }
+}
+
+// Houses and cells of the hint, under the board
+pub fn tint_hint(hint: &Hint, layout: &Layout) {
+    let theme = theme::current();
+    let tint = |idx: usize, color: Color| {
+        let p = layout.cell_position(idx);
+        draw_rectangle(p.x, p.y, layout.cell_size, layout.cell_size, color);
+    };
+    hint.houses.iter().flatten().for_each(|&idx| tint(idx, theme.hint.house_color));
+    hint.cells.iter().for_each(|&idx| tint(idx, theme.hint.cell_color));
+}
+
+// Candidates the hint rests on, and those it removes, over the digits
+pub fn mark_hint(grid: &Grid, hint: &Hint, layout: &Layout) {
+    let theme = theme::current();
+    for &idx in &hint.cells {
+        for &value in &hint.candidates {
+            grid.draw_mark(layout, idx, value, theme.hint.candidate_color);
+        }
+    }
+    for &(idx, value) in &hint.eliminations {
+        grid.draw_mark(layout, idx, value, theme.hint.elimination_color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sudoku::grid::{BoardSize, Variants};
+    use sudoku::{puzzle, variant};
+
+    use super::*;
+
+    #[test]
+    fn marks_are_stable_across_refreshes() {
+        let size = BoardSize::default();
+        let grid = Grid::new(size, variant::parse("cage 3 r1c1 r1c2", size).unwrap());
+        let mut play = Play::puzzle(&grid, None);
+        play.toggle_notes(&grid);
+        let marks = play.marks.clone();
+        assert_eq!(marks[0], [1, 2]);
+        for _ in 0..3 {
+            play.refresh(&grid);
+            assert_eq!(play.marks, marks);
+        }
+    }
+
+    // A watch state leaving many solutions checks by conflicts, a puzzle by its solution
+    #[test]
+    fn checks_only_trust_a_single_solution() {
+        let size = BoardSize::default();
+        let grid = Grid::new(size, Variants::default());
+        let mut play = Play::new(&grid);
+        play.place(&grid, 0, Some(1));
+        play.place(&grid, 10, Some(2));
+        assert_eq!(play.check(), 0);
+        assert!(play.correct()[0]);
+        play.place(&grid, 1, Some(1));
+        assert_eq!(play.check(), 2);
+        assert_eq!(play.mistakes, 2);
+
+        let givens = puzzle::parse("53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79", size).unwrap();
+        let grid = Grid::with_givens(size, Variants::default(), givens).unwrap();
+        let mut play = Play::puzzle(&grid, None);
+        // r1c3 holds 4, 1 clashes with nothing yet
+        play.place(&grid, 2, Some(1));
+        assert_eq!(play.check(), 1);
+        assert!(!play.correct()[2]);
+        play.place(&grid, 2, Some(4));
+        assert_eq!(play.check(), 0);
+        assert!(play.correct()[2]);
+    }
+}
@@ -0,0 +1,141 @@
+use crate::grid::BoardSize;
+
+
+// Puzzle files list the cells row by row, a digit for each given and `.` or `0` for
+// the empty ones. Line breaks and spaces are ignored:
+//
+//   53..7....
+//   6..195...
+//   ...
+//
+// Boards past 9 separate the cells with whitespace since values take two digits.
+// Files from other programs are read by their extension, see `Format`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Plain,
+    // SadMan Sudoku: `#` metadata lines, then the rows, optionally under [Puzzle]
+    // with the solving progress in later sections
+    Sdk,
+    // SadMan collections: one puzzle a line, 81 digits with 0 for the empty cells, and
+    // `#` comment lines for metadata
+    Sdm,
+    // Simple Sudoku: the rows with `|` between boxes and `---+---+---` between bands
+    Ss,
+}
+
+impl Format {
+    pub fn from_path(path: &str) -> Self {
+        let extension = path.rsplit_once('.').map_or(String::new(), |(_, extension)| extension.to_lowercase());
+        match extension.as_str() {
+            "sdk" => Format::Sdk,
+            "sdm" => Format::Sdm,
+            "ss" => Format::Ss,
+            _ => Format::Plain,
+        }
+    }
+}
+
+// Number picks the puzzle of a collection, counted from 1, other files holding one
+pub fn load(path: &str, size: BoardSize, number: usize) -> Result<Vec<Option<u8>>, String> {
+    let text = std::fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+    parse_format(&text, Format::from_path(path), size, number).map_err(|err| format!("{}: {}", path, err))
+}
+
+pub fn parse_format(text: &str, format: Format, size: BoardSize, number: usize) -> Result<Vec<Option<u8>>, String> {
+    if format != Format::Sdm && number != 1 {
+        return Err(format!("puzzle {} asked for, only .sdm collections hold several", number));
+    }
+    if format != Format::Plain && size != BoardSize::default() {
+        return Err("files from other programs only hold 9x9 puzzles".to_string());
+    }
+    match format {
+        Format::Plain => parse(text, size),
+        Format::Sdk => {
+            let mut in_puzzle = true;
+            let mut rows = Vec::new();
+            for line in text.lines().map(str::trim) {
+                if line.starts_with('[') {
+                    in_puzzle = line.eq_ignore_ascii_case("[puzzle]");
+                } else if in_puzzle && !line.starts_with('#') {
+                    rows.push(line);
+                }
+            }
+            parse(&rows.join("\n"), size)
+        }
+        Format::Sdm => {
+            let mut puzzles = text.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#'));
+            let count = puzzles.clone().count();
+            let puzzle = puzzles
+                .nth(number.max(1) - 1)
+                .ok_or_else(|| format!("puzzle {} asked for, the collection holds {}", number, count))?;
+            parse(puzzle, size).map_err(|err| format!("puzzle {}: {}", number, err))
+        }
+        Format::Ss => {
+            let rows: Vec<String> = text
+                .lines()
+                .filter(|line| !line.chars().all(|c| "-+*|".contains(c) || c.is_whitespace()))
+                .map(|line| line.replace('|', ""))
+                .collect();
+            parse(&rows.join("\n"), size)
+        }
+    }
+}
+
+pub fn parse(text: &str, size: BoardSize) -> Result<Vec<Option<u8>>, String> {
+    let side = size.side();
+    let words: Vec<String> = if side > 9 {
+        text.split_whitespace().map(str::to_string).collect()
+    } else {
+        text.chars().filter(|c| !c.is_whitespace()).map(String::from).collect()
+    };
+    if words.len() != size.cell_count() {
+        return Err(format!("expected {} cells, found {}", size.cell_count(), words.len()));
+    }
+
+    words
+        .iter()
+        .map(|word| match word.as_str() {
+            "." | "0" => Ok(None),
+            _ => match word.parse() {
+                Ok(value) if (1..=side).contains(&(value as usize)) => Ok(Some(value)),
+                _ => Err(format!("invalid cell '{}'", word)),
+            },
+        })
+        .collect()
+}
+
+// One row per line, in the form read by `parse`
+pub fn serialize(givens: &[Option<u8>], size: BoardSize) -> String {
+    let side = size.side();
+    givens
+        .chunks(side)
+        .map(|row| {
+            let cells: Vec<String> = row
+                .iter()
+                .map(|given| given.map_or(".".to_string(), |value| value.to_string()))
+                .collect();
+            let separator = if side > 9 { " " } else { "" };
+            cells.join(separator) + "\n"
+        })
+        .collect()
+}
+
+// All cells on one line, digits run together up to 9 and spaced apart past it
+pub fn serialize_line(givens: &[Option<u8>], size: BoardSize) -> String {
+    let separator = if size.side() > 9 { " " } else { "" };
+    givens
+        .iter()
+        .map(|given| given.map_or(".".to_string(), |value| value.to_string()))
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+// .sdm collection of 9x9 puzzles, the header lines written as comments above them
+pub fn serialize_collection(puzzles: &[Vec<Option<u8>>], header: &[String]) -> String {
+    let comments = header.iter().map(|line| format!("#{}\n", line));
+    let lines = puzzles.iter().map(|givens| {
+        let digits: String = givens.iter().map(|given| given.map_or('0', |value| (b'0' + value) as char)).collect();
+        digits + "\n"
+    });
+    comments.chain(lines).collect()
+}
@@ -0,0 +1,113 @@
+use macroquad::prelude::*;
+
+use sudoku::grid::Grid;
+use sudoku::layout::{Layout, Margins};
+use sudoku::{text, theme};
+
+use crate::analytics;
+use crate::input;
+use crate::keys::{self, Action};
+use crate::language::{tr, Text};
+use crate::metrics;
+use crate::screen::Screen;
+
+
+pub const DEFAULT_GRIDS: usize = 4;
+// Time the results stay up before the next race starts by itself
+const RESULTS_SECONDS: f64 = 8.;
+
+
+struct Runner {
+    grid: Grid,
+    seed: u64,
+    restarts: usize,
+    // Place and time, once solved
+    finish: Option<(usize, f64)>,
+}
+
+impl Runner {
+    fn new(new_grid: &impl Fn(u64) -> Grid) -> Self {
+        let seed = ::rand::random();
+        Self { grid: new_grid(seed), seed, restarts: 0, finish: None }
+    }
+
+    fn label(&self) -> String {
+        match self.finish {
+            Some((place, seconds)) => tr(Text::RaceFinished, &[&place, &format!("{:.1}", seconds), &self.restarts, &self.seed]),
+            None => tr(Text::RaceSolving, &[&self.restarts, &self.seed]),
+        }
+    }
+}
+
+// Tiles as close to square as the window allows, row by row, below a help line
+pub fn tiles(count: usize) -> Vec<Rect> {
+    let font_size = theme::current().text.font_size;
+    let columns = (count as f32).sqrt().ceil() as usize;
+    let rows = count.div_ceil(columns);
+    // The help line at the top
+    let top = 2. * font_size;
+    let (width, height) = (screen_width() / columns as f32, (screen_height() - top) / rows as f32);
+    (0..count)
+        .map(|i| Rect::new((i % columns) as f32 * width, top + (i / columns) as f32 * height, width, height))
+        .collect()
+}
+
+// Grids solving side by side from their own seeds, as a demo or a screensaver, each
+// labeled with its place once solved. A new race starts a while after the last one
+// ends, or when asked for, until going back to the title screen
+pub async fn run(count: usize, new_grid: impl Fn(u64) -> Grid, tick_seconds: f64) -> Screen {
+    let mut runners: Vec<Runner> = (0..count).map(|_| Runner::new(&new_grid)).collect();
+    let mut start = get_time();
+    let mut tick = get_time();
+    loop {
+        if is_quit_requested() {
+            std::process::exit(0);
+        }
+        if keys::pressed(Action::Menu) {
+            return Screen::Title;
+        }
+        // When the last grid was solved, once they all are
+        let ended = runners
+            .iter()
+            .map(|runner| runner.finish.map(|(_, seconds)| seconds))
+            .collect::<Option<Vec<f64>>>()
+            .map(|times| start + times.into_iter().fold(0., f64::max));
+        if keys::pressed(Action::NewGrid) || ended.is_some_and(|time| get_time() - time > RESULTS_SECONDS) {
+            runners = (0..count).map(|_| Runner::new(&new_grid)).collect();
+            start = get_time();
+        }
+        input::toggle_fullscreen();
+
+        if get_time() - tick > tick_seconds {
+            tick = get_time();
+            let mut place = runners.iter().filter(|runner| runner.finish.is_some()).count();
+            for runner in runners.iter_mut().filter(|runner| runner.finish.is_none()) {
+                if runner.grid.step().is_err() {
+                    runner.grid.reset();
+                    runner.restarts += 1;
+                } else if runner.grid.is_resolve() {
+                    place += 1;
+                    runner.finish = Some((place, tick - start));
+                    analytics::record(runner.seed, runner.restarts);
+                    metrics::record(&runner.grid, runner.seed, tick - start, runner.restarts);
+                }
+            }
+        }
+
+        let theme = theme::current();
+        clear_background(theme.board.background_color);
+        let help = tr(Text::RaceHelp, &[&keys::label(Action::NewGrid), &keys::label(Action::Menu)]);
+        text::draw(&help, 0., theme.text.font_size, theme.text.font_size, theme.text.color);
+        for (runner, tile) in runners.iter().zip(tiles(count)) {
+            let label_height = theme.text.font_size;
+            let mut layout = Layout::fit(runner.grid.size, Margins::default(), tile.w, tile.h - label_height);
+            layout.origin += vec2(tile.x, tile.y + label_height);
+            runner.grid.draw(&layout);
+            let color = if runner.finish.is_some_and(|(place, _)| place == 1) { theme.text.message_color } else { theme.text.color };
+            let label = runner.label();
+            let x = tile.x + (tile.w - text::measure(&label, theme.text.font_size).width) / 2.;
+            text::draw(&label, x, tile.y + label_height, theme.text.font_size, color);
+        }
+        next_frame().await;
+    }
+}
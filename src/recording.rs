@@ -0,0 +1,124 @@
+use std::fs::File;
+use std::io::BufWriter;
+
+use color_quant::NeuQuant;
+use macroquad::prelude::*;
+
+use crate::date;
+use crate::gif;
+use crate::screenshot;
+
+
+// Frames are shrunk to fit this many pixels a side
+const MAX_SIDE: usize = 640;
+// Hundredths of a second the solved grid stays before the loop starts over
+const FINAL_DELAY: u16 = 300;
+// NeuQuant learns from every nth pixel, lower is slower and better
+const SAMPLE_FACTOR: i32 = 10;
+
+
+struct Frame {
+    palette: Vec<u8>,
+    indices: Vec<u8>,
+}
+
+// Animated GIF of the frames captured at every solver tick, streamed to a timestamped
+// file. Each frame is written once the next one comes, so the last can be held longer
+pub struct Recording {
+    path: String,
+    delay: u16,
+    width: usize,
+    height: usize,
+    encoder: Option<gif::Encoder<BufWriter<File>>>,
+    pending: Option<Frame>,
+}
+
+impl Recording {
+    pub fn new(tick_seconds: f64) -> Self {
+        Self {
+            path: screenshot::free_path(&format!("sudoku-{}", date::timestamp()), "gif"),
+            // Browsers play anything faster than 2 hundredths slower
+            delay: ((tick_seconds * 100.).round() as u16).max(2),
+            width: 0,
+            height: 0,
+            encoder: None,
+            pending: None,
+        }
+    }
+
+    // Adds what has been drawn of the current frame. The GIF takes the size of the
+    // first one, later ones being stretched to it if the window was resized
+    pub fn capture(&mut self) -> Result<(), String> {
+        let image = get_screen_data();
+        if self.encoder.is_none() {
+            let (width, height) = (image.width as usize, image.height as usize);
+            let shrink = width.max(height).div_ceil(MAX_SIDE).max(1);
+            (self.width, self.height) = ((width / shrink).max(1), (height / shrink).max(1));
+            let file = File::create(&self.path).map_err(|err| self.error(err))?;
+            let encoder = gif::Encoder::new(BufWriter::new(file), self.width as u16, self.height as u16);
+            self.encoder = Some(encoder.map_err(|err| self.error(err))?);
+        }
+
+        let pixels = self.resample(&image);
+        let quantizer = NeuQuant::new(SAMPLE_FACTOR, 256, &pixels);
+        let frame = Frame {
+            palette: quantizer.color_map_rgb(),
+            indices: pixels.chunks(4).map(|pixel| quantizer.index_of(pixel) as u8).collect(),
+        };
+        if let Some(previous) = self.pending.replace(frame) {
+            self.write(&previous, self.delay)?;
+        }
+        Ok(())
+    }
+
+    // Closes the file, returning its path
+    pub fn finish(mut self) -> Result<String, String> {
+        if let Some(last) = self.pending.take() {
+            self.write(&last, FINAL_DELAY)?;
+        }
+        let encoder = self.encoder.take().ok_or_else(|| format!("{}: no frames recorded", self.path))?;
+        encoder.finish().map_err(|err| self.error(err))?;
+        Ok(self.path)
+    }
+
+    fn write(&mut self, frame: &Frame, delay: u16) -> Result<(), String> {
+        let (width, height) = (self.width as u16, self.height as u16);
+        let encoder = self.encoder.as_mut().unwrap();
+        let result = encoder.frame(width, height, &frame.palette, &frame.indices, delay);
+        result.map_err(|err| self.error(err))
+    }
+
+    fn error(&self, err: std::io::Error) -> String {
+        format!("{}: {}", self.path, err)
+    }
+
+    // RGBA rows top down at the recording size, each pixel averaging the screen
+    // pixels it covers. Screen rows come bottom up
+    fn resample(&self, image: &Image) -> Vec<u8> {
+        let (source_width, source_height) = (image.width as usize, image.height as usize);
+        let span = |i: usize, size: usize, source: usize| {
+            let start = i * source / size;
+            start..((i + 1) * source / size).max(start + 1)
+        };
+        let mut pixels = Vec::with_capacity(self.width * self.height * 4);
+        for y in 0..self.height {
+            let rows = span(y, self.height, source_height);
+            for x in 0..self.width {
+                let columns = span(x, self.width, source_width);
+                let mut sum = [0u32; 4];
+                for row in rows.clone() {
+                    let row = source_height - 1 - row;
+                    for column in columns.clone() {
+                        let pixel = &image.bytes[(row * source_width + column) * 4..][..4];
+                        for (total, &channel) in sum.iter_mut().zip(pixel) {
+                            *total += channel as u32;
+                        }
+                    }
+                }
+                let count = (rows.len() * columns.len()) as u32;
+                pixels.extend(sum.iter().map(|total| (total / count) as u8));
+            }
+        }
+        pixels
+    }
+}
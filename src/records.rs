@@ -0,0 +1,80 @@
+use sudoku::library::Difficulty;
+
+use crate::autosave;
+
+
+const FILE_NAME: &str = "best-times.txt";
+
+
+// Minutes and seconds, hours only when it took that long
+pub fn format_time(seconds: f64) -> String {
+    let seconds = seconds as u64;
+    match seconds / 3600 {
+        0 => format!("{}:{:02}", seconds / 60, seconds % 60),
+        hours => format!("{}:{:02}:{:02}", hours, seconds / 60 % 60, seconds % 60),
+    }
+}
+
+fn key(difficulty: Difficulty) -> String {
+    difficulty.name().to_lowercase()
+}
+
+// Best times file, a difficulty and its time in seconds on each line:
+//
+//   easy 154.2
+//   hard 612.9
+pub fn parse(text: &str) -> Result<Vec<(Difficulty, f64)>, String> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(n, line)| {
+            let (name, seconds) = line.trim().split_once(' ').ok_or_else(|| format!("line {}: expected a difficulty and a time", n + 1))?;
+            let difficulty = Difficulty::ALL
+                .into_iter()
+                .find(|&difficulty| key(difficulty) == name)
+                .ok_or_else(|| format!("line {}: unknown difficulty '{}'", n + 1, name))?;
+            let seconds = seconds.trim().parse().map_err(|_| format!("line {}: invalid time '{}'", n + 1, seconds))?;
+            Ok((difficulty, seconds))
+        })
+        .collect()
+}
+
+pub fn serialize(times: &[(Difficulty, f64)]) -> String {
+    times.iter().map(|&(difficulty, seconds)| format!("{} {:.1}\n", key(difficulty), seconds)).collect()
+}
+
+// Best time of each difficulty played, none when the file is missing or unreadable
+pub fn load() -> Vec<(Difficulty, f64)> {
+    let Some(path) = autosave::config_path(FILE_NAME).filter(|path| path.exists()) else {
+        return Vec::new();
+    };
+    std::fs::read_to_string(&path)
+        .map_err(|err| err.to_string())
+        .and_then(|text| parse(&text))
+        .unwrap_or_else(|err| {
+            eprintln!("Couldn't read the best times {}: {}", path.display(), err);
+            Vec::new()
+        })
+}
+
+pub fn best(times: &[(Difficulty, f64)], difficulty: Difficulty) -> Option<f64> {
+    times.iter().find(|&&(played, _)| played == difficulty).map(|&(_, seconds)| seconds)
+}
+
+// Keeps the time if it beats the best one of its difficulty, returning whether it did
+pub fn record(difficulty: Difficulty, seconds: f64) -> Result<bool, String> {
+    let mut times = load();
+    if best(&times, difficulty).is_some_and(|best| best <= seconds) {
+        return Ok(false);
+    }
+    times.retain(|&(played, _)| played != difficulty);
+    times.push((difficulty, seconds));
+    times.sort_by_key(|&(played, _)| Difficulty::ALL.iter().position(|&known| known == played));
+    let Some(path) = autosave::config_path(FILE_NAME) else {
+        return Ok(true);
+    };
+    let error = |err: std::io::Error| format!("{}: {}", path.display(), err);
+    std::fs::create_dir_all(path.parent().unwrap()).map_err(error)?;
+    std::fs::write(&path, serialize(&times)).map_err(error)?;
+    Ok(true)
+}
@@ -0,0 +1,72 @@
+use macroquad::prelude::Color;
+
+use sudoku::grid::Grid;
+use sudoku::theme;
+
+
+// Solves giving up past this many steps, the rules likely having no solution
+const MAX_STEPS: usize = 200_000;
+
+const PLAYER: &str = include_str!("replay.html");
+
+
+fn css(color: Color) -> String {
+    format!("\"rgba({:.0},{:.0},{:.0},{:.3})\"", color.r * 255., color.g * 255., color.b * 255., color.a)
+}
+
+fn json_list(values: impl Iterator<Item = impl ToString>) -> String {
+    format!("[{}]", values.map(|value| value.to_string()).collect::<Vec<_>>().join(","))
+}
+
+// Solves the grid from its seed, recording the candidates of every step as a trace:
+// the board shape, the starting candidate masks, then for each step the collapsed
+// cell, whether it hit a contradiction and restarted, and the cells whose masks changed
+fn record(grid: &mut Grid) -> Result<String, String> {
+    grid.reset();
+    let masks = |grid: &Grid| grid.cells.iter().map(|cell| cell.mask()).collect::<Vec<_>>();
+    let start = masks(grid);
+    let mut previous = start.clone();
+    let mut steps = Vec::new();
+    while !grid.is_resolve() {
+        if steps.len() == MAX_STEPS {
+            return Err(format!("no solution after {} steps", MAX_STEPS));
+        }
+        let failed = grid.step().is_err();
+        let collapse = grid.last_collapse().map_or(-1, |idx| idx as i64);
+        if failed {
+            grid.reset();
+        }
+        let current = masks(grid);
+        let changes = (0..current.len())
+            .filter(|&idx| current[idx] != previous[idx])
+            .flat_map(|idx| [idx as u32, current[idx]]);
+        steps.push(format!("[{},{},{}]", collapse, failed as u8, json_list(changes)));
+        previous = current;
+    }
+
+    let side = grid.size.side();
+    let digits = &theme::current().digits;
+    let board = &theme::current().board;
+    Ok(format!(
+        "{{\"side\":{},\"columns\":{},\"regions\":{},\"givens\":{},\"colors\":{{\"background\":{},\"grid\":{},\"given\":{},\"solved\":{},\"candidate\":{}}},\"start\":{},\"steps\":[{}]}}",
+        side,
+        grid.size.box_width,
+        json_list((0..side * side).map(|idx| grid.region_of(idx))),
+        json_list(grid.givens().iter().map(|given| given.unwrap_or(0))),
+        css(board.background_color),
+        css(board.grid_color),
+        css(digits.given_color),
+        css(digits.solved_color),
+        css(digits.candidate_color),
+        json_list(start.iter()),
+        steps.join(",")
+    ))
+}
+
+// Writes a single HTML file replaying the solve in any browser, scrubbing back and
+// forth through the steps. Variant markings other than jigsaw regions aren't drawn
+pub fn export(path: &str, mut grid: Grid) -> Result<(), String> {
+    let trace = record(&mut grid)?;
+    let html = PLAYER.replace("/*TRACE*/null", &trace);
+    std::fs::write(path, html).map_err(|err| format!("{}: {}", path, err))
+}
@@ -0,0 +1,46 @@
+use crate::play::Play;
+use crate::victory::Victory;
+
+
+// What the window shows, each screen running until it hands over to the next
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Screen {
+    Title,
+    Options,
+    // Solving or playing, with the puzzle browser open from the start
+    Game { browse: bool },
+    // Grids solving side by side
+    Race,
+    // Two heuristics solving the same grid in lockstep
+    Compare,
+    Analytics,
+    // Lessons walking through the techniques of the hints
+    Tutorials,
+    Editor,
+}
+
+// Stage of the game on the board
+pub enum Phase {
+    // The solver collapsing cells
+    Solving,
+    // The solver done, showing how it went
+    Solved(Victory),
+    // The player filling in the cells left open
+    Playing(Box<Play>),
+}
+
+impl Phase {
+    pub fn play(&self) -> Option<&Play> {
+        match self {
+            Phase::Playing(play) => Some(play.as_ref()),
+            _ => None,
+        }
+    }
+
+    pub fn play_mut(&mut self) -> Option<&mut Play> {
+        match self {
+            Phase::Playing(play) => Some(play.as_mut()),
+            _ => None,
+        }
+    }
+}
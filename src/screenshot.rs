@@ -0,0 +1,26 @@
+use macroquad::prelude::*;
+
+use crate::date;
+
+
+// First of name.extension, name-2.extension... not taken yet in the working directory
+pub fn free_path(name: &str, extension: &str) -> String {
+    (1..)
+        .map(|n| if n == 1 { format!("{}.{}", name, extension) } else { format!("{}-{}.{}", name, n, extension) })
+        .find(|path| !std::path::Path::new(path).exists())
+        .unwrap()
+}
+
+// Writes what has been drawn of the current frame to a timestamped PNG at the
+// display's full resolution, returning its path
+pub fn capture() -> Result<String, String> {
+    let image = get_screen_data();
+    let (width, height) = (image.width as usize, image.height as usize);
+    // Screen rows come bottom up
+    let rows: Vec<u8> = image.bytes.chunks(width * 4).rev().flatten().copied().collect();
+
+    let path = free_path(&format!("sudoku-{}", date::timestamp()), "png");
+    image::save_buffer(&path, &rows, width as u32, height as u32, image::ColorType::Rgba8)
+        .map_err(|err| format!("{}: {}", path, err))?;
+    Ok(path)
+}
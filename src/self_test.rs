@@ -0,0 +1,351 @@
+use macroquad::prelude::*;
+
+use sudoku::grid::{BoardSize, Grid, Variants};
+use sudoku::layout::Layout;
+use sudoku::library::Difficulty;
+use sudoku::notation::format_cells;
+use sudoku::puzzle::Format;
+use sudoku::symmetry::Symmetry;
+use sudoku::theme::{self, Theme};
+use sudoku::{generator, hints, library, puzzle, state, variant};
+
+use crate::keys;
+use crate::records;
+
+
+const CLASSIC: &str = "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+const CLASSIC_SOLUTION: &str = "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+
+// Variants quick enough to solve here, the slower ones are only read and written back
+const SOLVED_VARIANTS: [&str; 5] = ["thermo", "kropki", "arrows", "odd-even", "relations"];
+const VARIANT_FILES: [(&str, &str); 9] = [
+    ("arrows", include_str!("../variants/arrows.txt")),
+    ("killer", include_str!("../variants/killer.txt")),
+    ("kropki", include_str!("../variants/kropki.txt")),
+    ("odd-even", include_str!("../variants/odd-even.txt")),
+    ("quadruples", include_str!("../variants/quadruples.txt")),
+    ("relations", include_str!("../variants/relations.txt")),
+    ("sandwich", include_str!("../variants/sandwich.txt")),
+    ("skyscraper-xsums", include_str!("../variants/skyscraper-xsums.txt")),
+    ("thermo", include_str!("../variants/thermo.txt")),
+];
+const JIGSAW_REGIONS: &str = include_str!("../variants/jigsaw-regions.txt");
+const BOARD_SIDES: [usize; 3] = [4, 6, 12];
+
+const SEED: u64 = 0x5eed;
+const EMPTY_FINGERPRINT: u64 = 0x2cf7dd8dc1ed2416;
+const MAX_RESTARTS: usize = 10_000;
+const RENDER_SIZE: u32 = 256;
+
+
+pub struct Check {
+    name: String,
+    result: Result<(), String>,
+}
+
+impl Check {
+    fn new(name: &str, result: Result<(), String>) -> Self {
+        Self { name: name.to_string(), result }
+    }
+}
+
+// Checks needing no window: solving, file formats and seeded determinism
+pub fn run() -> Vec<Check> {
+    let size = BoardSize::default();
+    let mut checks = vec![Check::new("solve classic puzzle", solve_classic())];
+
+    for side in BOARD_SIDES {
+        let size = BoardSize::from_side(side).unwrap();
+        let result = solve(&mut Grid::new(size, Variants::default())).map(|_| ());
+        checks.push(Check::new(&format!("solve empty {}x{} board", side, side), result));
+    }
+    for name in SOLVED_VARIANTS {
+        let (_, text) = VARIANT_FILES.iter().find(|(file, _)| *file == name).unwrap();
+        let result = variant::parse(text, size)
+            .and_then(|variants| solve(&mut Grid::new(size, variants)).map(|_| ()));
+        checks.push(Check::new(&format!("solve {} variant", name), result));
+    }
+
+    for (name, text) in VARIANT_FILES {
+        checks.push(Check::new(&format!("round-trip {} variant", name), round_trip_variant(text)));
+    }
+    let regions = format!("regions {}", JIGSAW_REGIONS.split_whitespace().collect::<Vec<_>>().join(" "));
+    checks.push(Check::new("round-trip jigsaw regions", round_trip_variant(&regions)));
+    checks.push(Check::new("round-trip puzzle", round_trip_puzzle()));
+    checks.push(Check::new("note candidates", pencil_marks()));
+    checks.push(Check::new("follow hints", follow_hints()));
+    checks.push(Check::new("read puzzle formats", read_puzzle_formats()));
+    checks.push(Check::new("round-trip keys", round_trip_keys()));
+    checks.push(Check::new("round-trip best times", round_trip_best_times()));
+    checks.push(Check::new("generate unique puzzle", generate_puzzle()));
+    checks.push(Check::new("load bundled puzzles", bundled_puzzles()));
+    checks.push(Check::new("round-trip theme", round_trip_theme()));
+
+    checks.push(Check::new("seeded solves repeat", determinism()));
+    checks.push(Check::new("restore saved solve", restore_solve()));
+    checks.push(Check::new("explain contradictions", explain_contradiction()));
+    checks
+}
+
+// Bundled puzzles parse and their givens keep to the rules
+fn bundled_puzzles() -> Result<(), String> {
+    for entry in &library::PUZZLES {
+        let size = BoardSize::default();
+        let givens = puzzle::parse(entry.text, size).map_err(|err| format!("{}: {}", entry.name, err))?;
+        Grid::with_givens(size, Variants::default(), givens).map_err(|_| format!("{}: givens clash", entry.name))?;
+    }
+    Ok(())
+}
+
+// Solution digits, failing if the solver gives up or breaks a rule or a given
+fn solve(grid: &mut Grid) -> Result<Vec<u8>, String> {
+    grid.reset();
+    let mut restarts = 0;
+    while !grid.is_resolve() {
+        if grid.step().is_err() {
+            restarts += 1;
+            if restarts > MAX_RESTARTS {
+                return Err(format!("no solution after {} restarts", MAX_RESTARTS));
+            }
+            grid.reset();
+        }
+    }
+
+    if !grid.is_complete_and_valid() {
+        return Err("solution breaks a constraint or a given".to_string());
+    }
+    Ok(grid.cells.iter().map(|cell| cell.possible_values[0]).collect())
+}
+
+fn solve_classic() -> Result<(), String> {
+    let size = BoardSize::default();
+    let givens = puzzle::parse(CLASSIC, size)?;
+    let mut grid = Grid::with_givens(size, Variants::default(), givens)
+        .map_err(|_| "givens rejected".to_string())?;
+    // Searched for, as play mode checks entries, then collapsed like the animation
+    let searched: String = grid.solution().ok_or("no solution found")?.iter().map(|value| value.to_string()).collect();
+    let solution: String = solve(&mut grid)?.iter().map(|value| value.to_string()).collect();
+    if solution != CLASSIC_SOLUTION || searched != CLASSIC_SOLUTION {
+        return Err(format!("unexpected solution {}", if searched == CLASSIC_SOLUTION { solution } else { searched }));
+    }
+    Ok(())
+}
+
+fn pencil_marks() -> Result<(), String> {
+    let size = BoardSize::default();
+    let givens = puzzle::parse(CLASSIC, size)?;
+    let grid = Grid::with_givens(size, Variants::default(), givens.clone()).map_err(|_| "givens rejected".to_string())?;
+    let marks = grid.pencil_marks(&givens);
+    for (idx, digit) in CLASSIC_SOLUTION.bytes().enumerate() {
+        if !marks[idx].contains(&(digit - b'0')) {
+            return Err(format!("the solution's {} is missing from {}", digit as char, format_cells(&[idx], 9)));
+        }
+    }
+    // The top left open cell sees 3, 5, 6, 7, 8 and 9
+    if marks[2] != [1, 2, 4] {
+        return Err(format!("unexpected candidates {:?}", marks[2]));
+    }
+    Ok(())
+}
+
+// The classic puzzle falls to singles alone
+fn follow_hints() -> Result<(), String> {
+    let size = BoardSize::default();
+    let mut values = puzzle::parse(CLASSIC, size)?;
+    let grid = Grid::with_givens(size, Variants::default(), values.clone()).map_err(|_| "givens rejected".to_string())?;
+    while values.contains(&None) {
+        let hint = hints::find(&grid, &values).ok_or("no hint found")?;
+        let (idx, value) = hint.placement.ok_or(format!("{:?} places no digit", hint.technique))?;
+        values[idx] = Some(value);
+    }
+    let solution: String = values.iter().map(|value| value.unwrap().to_string()).collect();
+    if solution != CLASSIC_SOLUTION {
+        return Err(format!("unexpected solution {}", solution));
+    }
+    Ok(())
+}
+
+fn round_trip_variant(text: &str) -> Result<(), String> {
+    let size = BoardSize::default();
+    let first = variant::serialize(&variant::parse(text, size)?, size);
+    let second = variant::serialize(&variant::parse(&first, size)?, size);
+    if first != second {
+        return Err("serialized form changes when read back".to_string());
+    }
+    Ok(())
+}
+
+fn round_trip_puzzle() -> Result<(), String> {
+    let size = BoardSize::default();
+    let givens = puzzle::parse(CLASSIC, size)?;
+    if puzzle::parse(&puzzle::serialize(&givens, size), size)? != givens {
+        return Err("givens change when read back".to_string());
+    }
+    Ok(())
+}
+
+// The classic puzzle as other programs write it
+fn read_puzzle_formats() -> Result<(), String> {
+    let size = BoardSize::default();
+    let givens = puzzle::parse(CLASSIC, size)?;
+    let rows: Vec<&str> = (0..9).map(|row| &CLASSIC[row * 9..][..9]).collect();
+    let sdk = format!("#A Author\n#D Description\n[Puzzle]\n{}\n[State]\n{}\n", rows.join("\n"), CLASSIC_SOLUTION);
+    let sdm = format!("{}\n{}\n", CLASSIC_SOLUTION, CLASSIC.replace('.', "0"));
+    let band = |band: usize| {
+        rows[band * 3..][..3]
+            .iter()
+            .map(|row| format!("|{}|{}|{}|", &row[..3], &row[3..6], &row[6..]))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    let ss = format!("*-----------*\n{}\n|---+---+---|\n{}\n|---+---+---|\n{}\n*-----------*\n", band(0), band(1), band(2));
+    for (format, text, number) in [(Format::Sdk, sdk, 1), (Format::Sdm, sdm, 2), (Format::Ss, ss, 1)] {
+        if puzzle::parse_format(&text, format, size, number)? != givens {
+            return Err(format!("{:?} file read as another puzzle", format));
+        }
+    }
+    Ok(())
+}
+
+fn generate_puzzle() -> Result<(), String> {
+    let size = BoardSize::default();
+    let givens = generator::generate(size, &Variants::default(), SEED, Symmetry::None);
+    let mut grid = Grid::with_givens(size, Variants::default(), givens.clone()).map_err(|_| "givens conflict")?;
+    if grid.count_solutions(2) != 1 {
+        return Err("puzzle has more than one solution".to_string());
+    }
+    let collection = puzzle::serialize_collection(std::slice::from_ref(&givens), &["Header".to_string()]);
+    if puzzle::parse_format(&collection, Format::Sdm, size, 1)? != givens {
+        return Err("collection changes when read back".to_string());
+    }
+    Ok(())
+}
+
+fn round_trip_keys() -> Result<(), String> {
+    let bindings = keys::parse("new-grid Enter\nscreenshot key5 # comment\n")?;
+    if bindings[..2] != [KeyCode::Enter, KeyCode::R] || !bindings.contains(&KeyCode::Key5) {
+        return Err("bindings read wrong".to_string());
+    }
+    if keys::parse(&keys::serialize())? != keys::parse("")? {
+        return Err("serialized form changes when read back".to_string());
+    }
+    Ok(())
+}
+
+fn round_trip_best_times() -> Result<(), String> {
+    let times = records::parse("easy 83.5\nexpert 3661\n")?;
+    if records::best(&times, Difficulty::Expert) != Some(3661.) || records::format_time(3661.) != "1:01:01" {
+        return Err("best times read wrong".to_string());
+    }
+    if records::parse(&records::serialize(&times))? != times {
+        return Err("serialized form changes when read back".to_string());
+    }
+    Ok(())
+}
+
+fn round_trip_theme() -> Result<(), String> {
+    let first = theme::serialize(&Theme::default());
+    let second = theme::serialize(&theme::parse(&first)?);
+    if first != second {
+        return Err("serialized form changes when read back".to_string());
+    }
+    Ok(())
+}
+
+fn determinism() -> Result<(), String> {
+    let size = BoardSize::default();
+    let solutions: Vec<Vec<u8>> = (0..2)
+        .map(|_| {
+            let mut grid = Grid::new(size, Variants::default());
+            grid.seed(SEED);
+            solve(&mut grid)
+        })
+        .collect::<Result<_, _>>()?;
+    if solutions[0] != solutions[1] {
+        return Err("same seed, different solutions".to_string());
+    }
+    // Traces from other builds are compared by fingerprint
+    let fingerprint = Grid::new(size, Variants::default()).fingerprint();
+    if fingerprint != EMPTY_FINGERPRINT {
+        return Err(format!("empty grid fingerprint {:016x}, expected {:016x}", fingerprint, EMPTY_FINGERPRINT));
+    }
+    Ok(())
+}
+
+// A solve saved midway and restored ends the same as the one carrying on
+fn restore_solve() -> Result<(), String> {
+    let variants = Variants { diagonal: true, ..Variants::default() };
+    let mut grid = Grid::new(BoardSize::default(), variants);
+    grid.seed(SEED);
+    let finish = |grid: &mut Grid, steps: usize| {
+        for _ in 0..steps {
+            if grid.is_resolve() {
+                break;
+            }
+            if grid.step().is_err() {
+                grid.reset();
+            }
+        }
+    };
+    finish(&mut grid, 20);
+    let saved = state::serialize(&grid);
+    let mut restored = state::parse(&saved)?;
+    if state::serialize(&restored) != saved {
+        return Err("saved solve changes when read back".to_string());
+    }
+    finish(&mut grid, MAX_RESTARTS);
+    finish(&mut restored, MAX_RESTARTS);
+    if grid.cells != restored.cells || grid.eliminations != restored.eliminations {
+        return Err("restored solve went another way".to_string());
+    }
+    Ok(())
+}
+
+// Draws a board offscreen and checks something reached the pixels, needs a window
+pub async fn render() -> Check {
+    let target = render_target(RENDER_SIZE, RENDER_SIZE);
+    let extent = RENDER_SIZE as f32;
+    let mut camera = Camera2D::from_display_rect(Rect::new(0., 0., extent, extent));
+    camera.render_target = Some(target);
+
+    set_camera(&camera);
+    clear_background(theme::current().board.background_color);
+    let grid = Grid::new(BoardSize::default(), Variants::default());
+    grid.draw(&Layout::fit(grid.size, grid.margins(), extent, extent));
+    set_default_camera();
+    next_frame().await;
+
+    let image = target.texture.get_texture_data();
+    let lit = image.get_image_data().iter().filter(|pixel| pixel[..3] != [0, 0, 0]).count();
+    let result = if lit > 0 { Ok(()) } else { Err("frame is blank".to_string()) };
+    Check::new("render offscreen frame", result)
+}
+
+// Prints one line per check, returns whether all passed
+pub fn report(checks: &[Check]) -> bool {
+    for check in checks {
+        match &check.result {
+            Ok(()) => println!("PASS  {}", check.name),
+            Err(err) => println!("FAIL  {}: {}", check.name, err),
+        }
+    }
+    let failed = checks.iter().filter(|check| check.result.is_err()).count();
+    println!("{} checks, {} failed", checks.len(), failed);
+    failed == 0
+}
+
+// A given clashing with another in its row is pinned on one of them
+fn explain_contradiction() -> Result<(), String> {
+    let size = BoardSize::default();
+    let mut grid = Grid::with_givens(size, Variants::default(), puzzle::parse(CLASSIC, size)?)
+        .map_err(|_| "givens rejected".to_string())?;
+    // R1C1 holds a 5
+    let contradiction = match grid.set_given(2, Some(5)) {
+        Ok(_) => return Err("a second 5 in the first row was accepted".to_string()),
+        Err(contradiction) => contradiction,
+    };
+    match contradiction.cell {
+        Some(idx) if idx < 9 && contradiction.rule.is_none() => Ok(()),
+        _ => Err(format!("unexpected {:?}", contradiction)),
+    }
+}
@@ -0,0 +1,128 @@
+use sudoku::grid::{BoardSize, Variants};
+use sudoku::variant;
+
+use crate::watchdog;
+
+
+pub const DEFAULT_TICK_SECONDS: f64 = 0.2;
+
+// Name, board side, rules, seconds per solver step and watchdog timeout
+type Preset = (&'static str, usize, &'static str, f64, f64);
+
+// Recommended settings of each bundled variant, variants needing many restarts
+// step faster and give the watchdog longer before it reports a stall
+const PRESETS: [Preset; 18] = [
+    ("classic", 9, "", DEFAULT_TICK_SECONDS, watchdog::DEFAULT_TIMEOUT_SECONDS),
+    ("mini-4x4", 4, "", 0.4, watchdog::DEFAULT_TIMEOUT_SECONDS),
+    ("mini-6x6", 6, "", 0.3, watchdog::DEFAULT_TIMEOUT_SECONDS),
+    ("giant-16x16", 16, "", 0.05, 300.),
+    ("diagonal", 9, "diagonal", DEFAULT_TICK_SECONDS, watchdog::DEFAULT_TIMEOUT_SECONDS),
+    ("windoku", 9, "windoku", 0.1, 120.),
+    ("anti-knight", 9, "anti-knight", 0.1, 120.),
+    ("anti-king", 9, "anti-king", DEFAULT_TICK_SECONDS, watchdog::DEFAULT_TIMEOUT_SECONDS),
+    ("non-consecutive", 9, "non-consecutive", 0.02, 600.),
+    ("disjoint-groups", 9, "disjoint-groups", DEFAULT_TICK_SECONDS, watchdog::DEFAULT_TIMEOUT_SECONDS),
+    ("jigsaw", 9, include_str!("../variants/jigsaw.txt"), 0.05, 300.),
+    ("killer", 9, include_str!("../variants/killer.txt"), 0.02, 600.),
+    ("thermo", 9, include_str!("../variants/thermo.txt"), 0.1, 120.),
+    ("kropki", 9, include_str!("../variants/kropki.txt"), 0.1, 120.),
+    ("arrows", 9, include_str!("../variants/arrows.txt"), 0.1, 120.),
+    ("quadruples", 9, include_str!("../variants/quadruples.txt"), 0.05, 300.),
+    ("skyscraper-xsums", 9, include_str!("../variants/skyscraper-xsums.txt"), 0.02, 600.),
+    ("sandwich", 9, include_str!("../variants/sandwich.txt"), 0.02, 600.),
+];
+
+
+// Everything a solve is configured with, as held by presets
+pub struct Settings {
+    pub size: BoardSize,
+    pub variants: Variants,
+    pub tick_seconds: f64,
+    pub watchdog_timeout: f64,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            size: BoardSize::default(),
+            variants: Variants::default(),
+            tick_seconds: DEFAULT_TICK_SECONDS,
+            watchdog_timeout: watchdog::DEFAULT_TIMEOUT_SECONDS,
+        }
+    }
+}
+
+pub fn preset_names() -> impl Iterator<Item = &'static str> {
+    PRESETS.iter().map(|preset| preset.0)
+}
+
+// A bundled preset by name, otherwise a custom preset file
+pub fn load(preset: &str) -> Result<Settings, String> {
+    if let Some(&(name, side, rules, tick_seconds, watchdog_timeout)) =
+        PRESETS.iter().find(|(name, ..)| *name == preset)
+    {
+        let size = BoardSize::from_side(side).unwrap();
+        let variants = variant::parse(rules, size).map_err(|err| format!("{}: {}", name, err))?;
+        return Ok(Settings { size, variants, tick_seconds, watchdog_timeout });
+    }
+    let text = std::fs::read_to_string(preset).map_err(|err| format!("{}: {}", preset, err))?;
+    parse(&text).map_err(|err| format!("{}: {}", preset, err))
+}
+
+// Preset files are variant files starting with the solver settings, any of them
+// being optional:
+//
+//   size 9
+//   speed 0.05
+//   watchdog 300
+//   version 1
+//   cage 15 r1c1 r1c2 r2c1
+//   ...
+//
+// The speed is in seconds per solver step, the watchdog timeout in seconds, 0 disabling it.
+pub fn parse(text: &str) -> Result<Settings, String> {
+    let mut settings = Settings::default();
+    // Settings lines are blanked so errors in the rules keep their line numbers
+    let mut rules = Vec::new();
+    for (n, line) in text.lines().enumerate() {
+        let mut words = line.split('#').next().unwrap_or_default().split_whitespace();
+        let (key, value) = (words.next(), words.next());
+        let number = || {
+            value
+                .and_then(|value| value.parse::<f64>().ok())
+                .filter(|value| *value >= 0.)
+                .ok_or(format!("line {}: invalid {}", n + 1, key.unwrap_or_default()))
+        };
+        match key {
+            Some("size") => {
+                settings.size = value
+                    .and_then(|side| side.parse().ok())
+                    .and_then(BoardSize::from_side)
+                    .ok_or(format!("line {}: unsupported board size", n + 1))?;
+            }
+            Some("speed") => settings.tick_seconds = number()?,
+            Some("watchdog") => settings.watchdog_timeout = number()?,
+            _ => {
+                rules.push(line);
+                continue;
+            }
+        }
+        rules.push("");
+    }
+    settings.variants = variant::parse(&rules.join("\n"), settings.size)?;
+    Ok(settings)
+}
+
+pub fn serialize(settings: &Settings) -> String {
+    format!(
+        "size {}\nspeed {}\nwatchdog {}\n{}",
+        settings.size.side(),
+        settings.tick_seconds,
+        settings.watchdog_timeout,
+        variant::serialize(&settings.variants, settings.size)
+    )
+}
+
+pub fn save(path: &str, settings: &Settings) -> Result<(), String> {
+    std::fs::write(path, serialize(settings)).map_err(|err| format!("{}: {}", path, err))
+}
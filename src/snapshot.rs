@@ -0,0 +1,76 @@
+use std::rc::Rc;
+
+use rand_chacha::ChaCha12Rng;
+
+use crate::cell::Cell;
+use crate::grid::BoardSize;
+
+
+// Solver state of a grid to branch from and come back to, see `Grid::snapshot`: the
+// candidates of each cell as a bit set, the collapses made since the last reset and the
+// position of the generator. Taking one copies a few words per cell, cloning is O(1),
+// so backtracking, what-if analysis and solve histories can keep as many as they need
+#[derive(Debug, Clone)]
+pub struct GridSnapshot {
+    size: BoardSize,
+    masks: Rc<[u32]>,
+    pub(crate) eliminations: usize,
+    pub(crate) collapses: Rc<[(usize, u8)]>,
+    pub(crate) last_collapse: Option<usize>,
+    pub(crate) rng: ChaCha12Rng,
+}
+
+impl GridSnapshot {
+    pub(crate) fn new(
+        size: BoardSize,
+        cells: &[Cell],
+        eliminations: usize,
+        collapses: &[(usize, u8)],
+        last_collapse: Option<usize>,
+        rng: &ChaCha12Rng,
+    ) -> Self {
+        Self {
+            size,
+            masks: cells.iter().map(Cell::mask).collect(),
+            eliminations,
+            collapses: collapses.into(),
+            last_collapse,
+            rng: rng.clone(),
+        }
+    }
+
+    pub fn size(&self) -> BoardSize {
+        self.size
+    }
+
+    // Candidates of the cell, value v being bit v as in `Cell::mask`
+    pub fn mask(&self, idx: usize) -> u32 {
+        self.masks[idx]
+    }
+
+    pub fn cell(&self, idx: usize) -> Cell {
+        Cell { possible_values: (1..=self.size.side() as u8).filter(|v| self.masks[idx] & 1 << v != 0).collect() }
+    }
+
+    pub fn to_cells(&self) -> Vec<Cell> {
+        (0..self.masks.len()).map(|idx| self.cell(idx)).collect()
+    }
+
+    // Puts the candidates back in place, reusing the room the cells already have
+    pub(crate) fn restore_cells(&self, cells: &mut [Cell]) {
+        for (cell, &mask) in cells.iter_mut().zip(self.masks.iter()) {
+            if cell.mask() != mask {
+                cell.possible_values.clear();
+                cell.possible_values.extend((1..=self.size.side() as u8).filter(|v| mask & 1 << v != 0));
+            }
+        }
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.masks.iter().all(|mask| mask.count_ones() == 1)
+    }
+
+    pub fn eliminations(&self) -> usize {
+        self.eliminations
+    }
+}
@@ -0,0 +1,106 @@
+use macroquad::audio::{load_sound_from_bytes, play_sound, PlaySoundParams, Sound};
+use macroquad::prelude::*;
+
+use crate::keys::{self, Action};
+
+
+const SAMPLE_RATE: u32 = 44_100;
+const VOLUME: f32 = 0.25;
+
+// Notes as frequency in Hz, start and length in seconds
+const TICK: [(f32, f32, f32); 1] = [(1760., 0., 0.03)];
+// Two tones a semitone apart clash
+const CONTRADICTION: [(f32, f32, f32); 2] = [(196., 0., 0.35), (207.7, 0., 0.35)];
+// Rising C major arpeggio
+const CHIME: [(f32, f32, f32); 4] = [(1046.5, 0., 0.8), (1318.5, 0.09, 0.8), (1568., 0.18, 0.8), (2093., 0.27, 1.)];
+
+
+// Sine notes fading out, as a 16 bit mono WAV file
+fn synthesize(notes: &[(f32, f32, f32)]) -> Vec<u8> {
+    let length = notes.iter().map(|&(_, start, duration)| start + duration).fold(0., f32::max);
+    let count = (length * SAMPLE_RATE as f32) as usize;
+    let samples = (0..count).map(|i| {
+        let t = i as f32 / SAMPLE_RATE as f32;
+        let mix: f32 = notes
+            .iter()
+            .filter(|&&(_, start, duration)| (start..start + duration).contains(&t))
+            .map(|&(frequency, start, duration)| {
+                let age = t - start;
+                let envelope = (1. - age / duration).powi(3);
+                (std::f32::consts::TAU * frequency * age).sin() * envelope
+            })
+            .sum();
+        ((mix / notes.len() as f32).clamp(-1., 1.) * i16::MAX as f32) as i16
+    });
+
+    let data_size = count as u32 * 2;
+    let mut wav = Vec::with_capacity(44 + data_size as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_size).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    wav.extend_from_slice(&1u16.to_le_bytes()); // mono
+    wav.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    wav.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    wav.extend_from_slice(&2u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_size.to_le_bytes());
+    for sample in samples {
+        wav.extend_from_slice(&sample.to_le_bytes());
+    }
+    wav
+}
+
+// Tick on each collapse, clash on contradictions and chime on completion
+pub struct Sounds {
+    tick: Option<Sound>,
+    contradiction: Option<Sound>,
+    chime: Option<Sound>,
+    muted: bool,
+}
+
+impl Sounds {
+    // Sounds that fail to load stay silent
+    pub async fn load() -> Self {
+        Self {
+            tick: load_sound_from_bytes(&synthesize(&TICK)).await.ok(),
+            contradiction: load_sound_from_bytes(&synthesize(&CONTRADICTION)).await.ok(),
+            chime: load_sound_from_bytes(&synthesize(&CHIME)).await.ok(),
+            muted: false,
+        }
+    }
+
+    pub fn update(&mut self) {
+        if keys::pressed(Action::Mute) {
+            self.toggle_mute();
+        }
+    }
+
+    pub fn muted(&self) -> bool {
+        self.muted
+    }
+
+    pub fn toggle_mute(&mut self) {
+        self.muted = !self.muted;
+    }
+
+    fn play(&self, sound: Option<Sound>) {
+        if let Some(sound) = sound.filter(|_| !self.muted) {
+            play_sound(sound, PlaySoundParams { looped: false, volume: VOLUME });
+        }
+    }
+
+    pub fn collapse(&self) {
+        self.play(self.tick);
+    }
+
+    pub fn contradiction(&self) {
+        self.play(self.contradiction);
+    }
+
+    pub fn solved(&self) {
+        self.play(self.chime);
+    }
+}
@@ -0,0 +1,189 @@
+use ::rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+
+use crate::cell::Cell;
+use crate::grid::{BoardSize, Decision, Grid, Levels};
+use crate::json::{self, Value};
+use crate::snapshot::GridSnapshot;
+use crate::variant;
+
+
+pub const FORMAT_VERSION: u32 = 2;
+
+
+// Saved solves are JSON objects holding everything the rest of a solve depends on,
+// so a restored one carries on exactly as the saved one would have:
+//
+//   {"version":2,"box_width":3,"box_height":3,"rules":"version 1\ndiagonal\n",
+//    "givens":[5,3,0,...],"candidates":[32,8,1022,...],"eliminations":118,
+//    "collapses":[[2,4],[10,7]],"last_collapse":10,
+//    "decisions":[{"candidates":[1022,8,...],"reasons":[]},...],"reasons":[[2,[0]],...],
+//    "rng":{"seed":[12,250,...],"stream":"0","word_pos":"1184"}}
+//
+// Rules are written as a variant file, see `variant`. Givens use 0 for the empty
+// cells and candidates are bit masks, bit n set when n is still possible. Collapses
+// list the cells collapsed since the last reset with the digit each got. Decisions
+// are the collapses backjumping can go back to, the first ones when it was on: the
+// candidates before each and the reasons then. Reasons list the cells whose candidates
+// follow from decisions, as [cell, [decision, ...]]. The random generator is saved as
+// its seed and position, the large integers as strings. Version 1 solves have no
+// decisions, backjumping waiting for their next restart
+pub fn save(path: &str, grid: &Grid) -> Result<(), String> {
+    std::fs::write(path, serialize(grid)).map_err(|err| format!("{}: {}", path, err))
+}
+
+pub fn load(path: &str) -> Result<Grid, String> {
+    let text = std::fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+    parse(&text).map_err(|err| format!("{}: {}", path, err))
+}
+
+fn list(values: impl Iterator<Item = impl ToString>) -> String {
+    format!("[{}]", values.map(|value| value.to_string()).collect::<Vec<_>>().join(","))
+}
+
+// Cells whose candidates follow from decisions, with those decisions
+fn reasons(reasons: &[Levels]) -> String {
+    list(reasons
+        .iter()
+        .enumerate()
+        .filter(|(_, levels)| levels.iter().next().is_some())
+        .map(|(idx, levels)| format!("[{},{}]", idx, list(levels.iter()))))
+}
+
+pub fn serialize(grid: &Grid) -> String {
+    let collapses = grid.collapses.iter().map(|&(idx, value)| format!("[{},{}]", idx, value));
+    let decisions = grid.decisions.iter().map(|decision| {
+        let candidates = (0..grid.size.cell_count()).map(|idx| decision.before.mask(idx));
+        format!("{{\"candidates\":{},\"reasons\":{}}}", list(candidates), reasons(&decision.reasons))
+    });
+    format!(
+        "{{\"version\":{},\"box_width\":{},\"box_height\":{},\"rules\":{},\"givens\":{},\"candidates\":{},\"eliminations\":{},\"collapses\":{},\"last_collapse\":{},\"decisions\":{},\"reasons\":{},\"rng\":{{\"seed\":{},\"stream\":\"{}\",\"word_pos\":\"{}\"}}}}\n",
+        FORMAT_VERSION,
+        grid.size.box_width,
+        grid.size.box_height,
+        json::escape(&variant::serialize(&grid.variants, grid.size)),
+        list(grid.givens().iter().map(|given| given.unwrap_or(0))),
+        list(grid.cells.iter().map(Cell::mask)),
+        grid.eliminations,
+        list(collapses),
+        grid.last_collapse.map_or("null".to_string(), |idx| idx.to_string()),
+        list(decisions),
+        reasons(&grid.reasons),
+        list(grid.rng.get_seed().iter()),
+        grid.rng.get_stream(),
+        grid.rng.get_word_pos(),
+    )
+}
+
+pub fn parse(text: &str) -> Result<Grid, String> {
+    from_value(&json::parse(text)?)
+}
+
+// Solve saved within a larger file
+pub fn from_value(root: &Value) -> Result<Grid, String> {
+    let version: u32 = root.get("version")?.as_int()?;
+    if version > FORMAT_VERSION {
+        return Err(format!("version {} is newer than this program reads", version));
+    }
+    let size = BoardSize { box_width: root.get("box_width")?.as_int()?, box_height: root.get("box_height")?.as_int()? };
+    let side = size.side();
+    if BoardSize::from_side(side) != Some(size) {
+        return Err(format!("invalid board of {}x{} boxes", size.box_width, size.box_height));
+    }
+    let variants = variant::parse(root.get("rules")?.as_str()?, size)?;
+    let cells = |value: &Value, name: &str| -> Result<Vec<u32>, String> {
+        let values = value.get(name)?.as_array()?;
+        if values.len() != size.cell_count() {
+            return Err(format!("expected {} {}, found {}", size.cell_count(), name, values.len()));
+        }
+        values.iter().map(Value::as_int).collect()
+    };
+    let candidates = |value: &Value| -> Result<Vec<Cell>, String> {
+        cells(value, "candidates")?
+            .into_iter()
+            .map(|mask| {
+                let possible_values: Vec<u8> = (1..=side as u8).filter(|value| mask & 1 << value != 0).collect();
+                match possible_values.is_empty() {
+                    true => Err(format!("invalid candidates {}", mask)),
+                    false => Ok(Cell { possible_values }),
+                }
+            })
+            .collect()
+    };
+    // Decisions from `below` on came later, the candidates can't follow from them
+    let reasons = |value: &Value, below: usize| -> Result<Vec<Levels>, String> {
+        let mut reasons = vec![Levels::default(); size.cell_count()];
+        for entry in value.get("reasons")?.as_array()? {
+            let [idx, levels] = entry.as_array()? else {
+                return Err("expected reasons as [cell, decisions]".to_string());
+            };
+            let idx: usize = idx.as_int()?;
+            if idx >= size.cell_count() {
+                return Err("reason outside the board".to_string());
+            }
+            for level in levels.as_array()? {
+                match level.as_int()? {
+                    level if level < below => reasons[idx].insert(level),
+                    level => return Err(format!("reason on decision {} made later", level)),
+                }
+            }
+        }
+        Ok(reasons)
+    };
+
+    let givens = cells(root, "givens")?
+        .into_iter()
+        .map(|value| match value {
+            0 => Ok(None),
+            value if value as usize <= side => Ok(Some(value as u8)),
+            value => Err(format!("invalid given {}", value)),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut grid = Grid::with_givens(size, variants, givens).map_err(|_| "the givens break the rules".to_string())?;
+
+    grid.cells = candidates(root)?;
+    grid.eliminations = root.get("eliminations")?.as_int()?;
+    grid.collapses = root
+        .get("collapses")?
+        .as_array()?
+        .iter()
+        .map(|collapse| match collapse.as_array()? {
+            [idx, value] => Ok((idx.as_int()?, value.as_int()?)),
+            _ => Err("expected collapses as [cell, digit]".to_string()),
+        })
+        .collect::<Result<_, String>>()?;
+    grid.last_collapse = match root.get("last_collapse")? {
+        Value::Null => None,
+        idx => Some(idx.as_int()?),
+    };
+    if grid.collapses.iter().map(|&(idx, _)| idx).chain(grid.last_collapse).any(|idx| idx >= size.cell_count()) {
+        return Err("collapse outside the board".to_string());
+    }
+
+    let rng = root.get("rng")?;
+    let seed = rng
+        .get("seed")?
+        .as_array()?
+        .iter()
+        .map(Value::as_int)
+        .collect::<Result<Vec<u8>, _>>()?
+        .try_into()
+        .map_err(|_| "expected a 32 byte seed".to_string())?;
+    grid.rng = ChaCha12Rng::from_seed(seed);
+    grid.rng.set_stream(rng.get("stream")?.as_int()?);
+    grid.rng.set_word_pos(rng.get("word_pos")?.as_int()?);
+
+    if version >= 2 {
+        let decisions = root.get("decisions")?.as_array()?;
+        if decisions.len() > grid.collapses.len() {
+            return Err("more decisions than collapses".to_string());
+        }
+        for (level, decision) in decisions.iter().enumerate() {
+            let before = GridSnapshot::new(size, &candidates(decision)?, grid.eliminations, &grid.collapses[..level], None, &grid.rng);
+            let (idx, value) = grid.collapses[level];
+            grid.decisions.push(Decision { before, reasons: reasons(decision, level)?, idx, value });
+        }
+        grid.reasons = reasons(root, decisions.len())?;
+    }
+    Ok(grid)
+}
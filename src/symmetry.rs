@@ -0,0 +1,85 @@
+use crate::notation::Coord;
+
+// Pattern the generator keeps the givens in, cells mapped onto each other by it being
+// given or taken away together
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Symmetry {
+    // Givens anywhere
+    #[default]
+    None,
+    // Turning the grid half a turn around its center
+    Rotational,
+    // Flipping the grid left to right
+    Mirror,
+    // Flipping the grid over its main diagonal
+    Diagonal,
+}
+
+impl Symmetry {
+    pub const ALL: [Symmetry; 4] = [Symmetry::None, Symmetry::Rotational, Symmetry::Mirror, Symmetry::Diagonal];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Symmetry::None => "none",
+            Symmetry::Rotational => "rotational",
+            Symmetry::Mirror => "mirror",
+            Symmetry::Diagonal => "diagonal",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|symmetry| symmetry.name() == name)
+    }
+
+    // Cell the given at idx is mapped onto, itself on the axis or the center
+    pub fn image(self, idx: usize, side: usize) -> usize {
+        let Coord { row, col } = Coord::from_index(idx, side);
+        let image = match self {
+            Symmetry::None => Coord::new(row, col),
+            Symmetry::Rotational => Coord::new(side - 1 - row, side - 1 - col),
+            Symmetry::Mirror => Coord::new(row, side - 1 - col),
+            Symmetry::Diagonal => Coord::new(col, row),
+        };
+        image.index(side)
+    }
+
+    // Cells given or taken away together, each listed once in the order of their first
+    // cell in `order`
+    pub fn orbits(self, order: &[usize], side: usize) -> Vec<Vec<usize>> {
+        let mut seen = vec![false; side * side];
+        let mut orbits = Vec::new();
+        for &idx in order {
+            if seen[idx] {
+                continue;
+            }
+            let image = self.image(idx, side);
+            seen[idx] = true;
+            seen[image] = true;
+            orbits.push(if image == idx { vec![idx] } else { vec![idx, image] });
+        }
+        orbits
+    }
+
+    // Whether the givens follow the pattern
+    pub fn holds(self, givens: &[Option<u8>], side: usize) -> bool {
+        (0..givens.len()).all(|idx| givens[idx].is_some() == givens[self.image(idx, side)].is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator;
+    use crate::grid::{BoardSize, Grid, Variants};
+
+    #[test]
+    fn generated_givens_keep_the_pattern() {
+        let size = BoardSize::default();
+        for symmetry in Symmetry::ALL {
+            let givens = generator::generate(size, &Variants::default(), 0x5eed, symmetry);
+            assert!(symmetry.holds(&givens, size.side()), "{} givens out of pattern", symmetry.name());
+            let mut grid = Grid::with_givens(size, Variants::default(), givens).unwrap();
+            assert_eq!(grid.count_solutions(2), 1, "{} puzzle has more than one solution", symmetry.name());
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use std::collections::VecDeque;
+use std::sync::mpsc::{self, Receiver, Sender};
+
+use macroquad::prelude::*;
+
+use sudoku::cell::Cell;
+use sudoku::events::{self, Event, ObserverId};
+use sudoku::grid::Grid;
+use sudoku::heuristic::Heuristic;
+use sudoku::layout::{Layout, Margins};
+use sudoku::notation::Coord;
+use sudoku::{text, theme};
+
+use crate::language::{tr, Text};
+
+
+// Columns kept right of the board for the panel, in cells
+const WIDTH: f32 = 7.;
+// Sentences kept, the panel showing the latest that fit
+const KEPT: usize = 200;
+
+
+// Teacher mode: every solver step told in plain sentences in a panel right of the board,
+// the collapse and why the heuristic picked the cell, then each candidate it eliminated
+// and the cell that rules it out
+pub struct Teacher {
+    sender: Sender<Event>,
+    receiver: Receiver<Event>,
+    // Of the grid followed
+    observer: Option<ObserverId>,
+    lines: VecDeque<String>,
+}
+
+impl Teacher {
+    pub fn new(grid: &mut Grid) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let mut teacher = Self { sender, receiver, observer: None, lines: VecDeque::new() };
+        teacher.follow(grid);
+        teacher
+    }
+
+    // Have the steps of a new grid told, once for each grid
+    pub fn follow(&mut self, grid: &mut Grid) {
+        self.observer = Some(grid.subscribe(events::forward(self.sender.clone())));
+        self.lines.clear();
+    }
+
+    // Stop telling the steps of the grid followed, the panel closing
+    pub fn leave(self, grid: &mut Grid) {
+        if let Some(id) = self.observer {
+            grid.unsubscribe(id);
+        }
+    }
+
+    // Room for the panel on top of the board's own margins
+    pub fn margins(margins: Margins) -> Margins {
+        Margins { right: margins.right + WIDTH, ..margins }
+    }
+
+    // Tells the step just taken, from the candidates before it
+    pub fn step(&mut self, grid: &Grid, before: &[Cell]) {
+        let events: Vec<Event> = self.receiver.try_iter().collect();
+        self.lines.extend(explain(grid, before, &events));
+        let excess = self.lines.len().saturating_sub(KEPT);
+        self.lines.drain(..excess);
+    }
+
+    // The solve starting over, its earlier steps no longer on the board
+    pub fn clear(&mut self) {
+        self.receiver.try_iter().for_each(drop);
+        self.lines.clear();
+    }
+
+    // Latest sentences at the bottom, wrapped to the panel along the board
+    pub fn draw(&self, layout: &Layout, margins: Margins) {
+        let style = &theme::current().browser;
+        let font_size = style.font_size * layout.scale().min(1.);
+        let padding = style.padding * layout.scale().min(1.);
+        let x = layout.origin.x + layout.extent() + (margins.right + 0.25) * layout.cell_size;
+        let width = (WIDTH - 0.25) * layout.cell_size;
+        draw_rectangle(x, layout.origin.y, width, layout.extent(), style.panel_color);
+
+        let rows = ((layout.extent() - 2. * padding) / font_size).max(0.) as usize;
+        let mut wrapped: Vec<(String, bool)> = Vec::new();
+        for (i, line) in self.lines.iter().enumerate().rev() {
+            if wrapped.len() >= rows {
+                break;
+            }
+            let latest = i + 1 == self.lines.len();
+            let parts = text::wrap(line, width - 2. * padding, font_size);
+            wrapped.splice(0..0, parts.into_iter().map(|part| (part, latest)));
+        }
+        let skipped = wrapped.len().saturating_sub(rows);
+        for (row, (line, latest)) in wrapped[skipped..].iter().enumerate() {
+            let y = layout.origin.y + padding + (row + 1) as f32 * font_size - font_size / 4.;
+            let color = if *latest { style.heading_color } else { style.text_color };
+            text::draw(line, x + padding, y, font_size, color);
+        }
+    }
+}
+
+// Sentences for the events of a step, the candidates before it telling how many the
+// collapsed cell had
+pub fn explain(grid: &Grid, before: &[Cell], events: &[Event]) -> Vec<String> {
+    let side = grid.size.side();
+    let cell = |idx: usize| Coord::from_index(idx, side).to_string();
+    let mut collapsed = None;
+    let mut lines = Vec::new();
+    for (i, event) in events.iter().enumerate() {
+        match *event {
+            Event::CellCollapsed { idx, value } => {
+                collapsed = Some((idx, value));
+                let candidates = before[idx].possible_values.len();
+                let text = match grid.heuristic {
+                    Heuristic::MinEntropy => Text::TeachFewest,
+                    Heuristic::Scan => Text::TeachFirst,
+                    Heuristic::Random => Text::TeachRandom,
+                };
+                lines.push(tr(text, &[&cell(idx), &value, &candidates]));
+            }
+            Event::CandidateEliminated { idx, value } => {
+                lines.push(tr(Text::TeachEliminated, &[&value, &cell(idx), &reason(grid, collapsed, idx, value)]));
+            }
+            Event::Contradiction { idx } => {
+                lines.push(tr(Text::TeachContradiction, &[&cell(idx)]));
+                let backjumped = matches!(events.get(i + 1), Some(Event::Backjump { .. }));
+                if !backjumped {
+                    lines.push(tr(Text::TeachRestart, &[]));
+                }
+            }
+            Event::Backjump { idx, value } => lines.push(tr(Text::TeachBackjump, &[&value, &cell(idx)])),
+            Event::Solved => lines.push(tr(Text::TeachSolved, &[])),
+        }
+    }
+    lines
+}
+
+// House shared with a cell left with the eliminated digit only, the collapsed cell
+// first. The other constraints when none is
+fn reason(grid: &Grid, collapsed: Option<(usize, u8)>, idx: usize, value: u8) -> String {
+    let side = grid.size.side();
+    let fixed = |peer: usize| peer != idx && grid.cells[peer].possible_values == [value];
+    let peers = collapsed
+        .filter(|&(peer, collapsed)| collapsed == value && peer != idx)
+        .map(|(peer, _)| peer)
+        .into_iter()
+        .chain((0..grid.cells.len()).filter(|&peer| fixed(peer)));
+    let houses = grid.houses();
+    for peer in peers {
+        let (at, of) = (Coord::from_index(idx, side), Coord::from_index(peer, side));
+        let text = if at.row == of.row {
+            Text::TeachSameRow
+        } else if at.col == of.col {
+            Text::TeachSameColumn
+        } else if grid.region_of(idx) == grid.region_of(peer) {
+            Text::TeachSameBox
+        } else if houses.iter().any(|house| house.contains(&idx) && house.contains(&peer)) {
+            Text::TeachSameHouse
+        } else {
+            continue;
+        };
+        return tr(text, &[&of]);
+    }
+    tr(Text::TeachConstraints, &[])
+}
+
+#[cfg(test)]
+mod tests {
+    use sudoku::grid::{BoardSize, Variants};
+
+    use super::*;
+
+    // The collapse then every elimination, each of them down to a row, column or box on a
+    // classic grid
+    #[test]
+    fn steps_are_told_in_full() {
+        let size = BoardSize::default();
+        let mut grid = Grid::new(size, Variants::default());
+        grid.seed(0x5eed);
+        let (observer, receiver) = events::channel();
+        grid.subscribe(observer);
+        let before = grid.cells.clone();
+        grid.step().unwrap();
+        let events: Vec<Event> = receiver.try_iter().collect();
+        let lines = explain(&grid, &before, &events);
+
+        let idx = grid.last_collapse().unwrap();
+        let coord = Coord::from_index(idx, size.side());
+        let expected = tr(Text::TeachFewest, &[&coord, &grid.cells[idx].possible_values[0], &before[idx].possible_values.len()]);
+        assert_eq!(lines.first(), Some(&expected));
+        let eliminated = events.iter().filter(|event| matches!(event, Event::CandidateEliminated { .. })).count();
+        assert_eq!(lines.len(), 1 + eliminated);
+        let unexplained = format!("({})", tr(Text::TeachConstraints, &[]));
+        assert!(!lines.iter().any(|line| line.ends_with(&unexplained)), "no house given");
+    }
+}
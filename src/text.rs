@@ -0,0 +1,39 @@
+use macroquad::prelude::*;
+
+
+// macroquad rasterizes glyphs at the font size times the DPI scale rounded up, but
+// lays them out at the exact scale, so on fractional scales like 1.5 text comes out
+// a third too large. Sizes are asked for smaller to compensate, the rest of the
+// fraction going through the font scale
+fn font_size(size: f32) -> (u16, f32) {
+    let dpi = unsafe { get_internal_gl() }.quad_context.dpi_scale();
+    let size = size * dpi / dpi.ceil();
+    let rounded = size.round().max(1.);
+    (rounded as u16, size / rounded)
+}
+
+// Same as macroquad's draw_text, at the right size on any display
+pub fn draw(text: &str, x: f32, y: f32, size: f32, color: Color) {
+    let (font_size, font_scale) = font_size(size);
+    draw_text_ex(text, x, y, TextParams { font_size, font_scale, color, ..Default::default() });
+}
+
+pub fn measure(text: &str, size: f32) -> TextDimensions {
+    let (font_size, font_scale) = font_size(size);
+    measure_text(text, None, font_size, font_scale)
+}
+
+// Words of the text spread over as many lines as the width takes
+pub fn wrap(text: &str, width: f32, size: f32) -> Vec<String> {
+    let mut lines = vec![String::new()];
+    for word in text.split(' ') {
+        let current = lines.last_mut().unwrap();
+        let longer = if current.is_empty() { word.to_string() } else { format!("{} {}", current, word) };
+        if current.is_empty() || measure(&longer, size).width <= width {
+            *current = longer;
+        } else {
+            lines.push(word.to_string());
+        }
+    }
+    lines
+}
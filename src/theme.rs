@@ -0,0 +1,447 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use macroquad::prelude::*;
+
+
+// Declares the theme sections and their keys along with the compiled in defaults,
+// each section being a table of the theme file
+macro_rules! theme {
+    ($($section:ident: $Section:ident {
+        $($key:ident: $Type:ty = $default:expr,)*
+    })*) => {
+        #[derive(Debug, Clone, PartialEq)]
+        pub struct Theme {
+            $(pub $section: $Section,)*
+        }
+
+        $(
+            #[derive(Debug, Clone, PartialEq)]
+            pub struct $Section {
+                $(pub $key: $Type,)*
+            }
+        )*
+
+        impl Default for Theme {
+            fn default() -> Self {
+                Self {
+                    $($section: $Section {
+                        $($key: $default,)*
+                    },)*
+                }
+            }
+        }
+
+        impl Theme {
+            fn set(&mut self, section: &str, key: &str, value: &str) -> Result<(), String> {
+                match (section, key) {
+                    $($((stringify!($section), stringify!($key)) => {
+                        self.$section.$key = Value::parse(value).ok_or(format!("invalid {}.{}", section, key))?;
+                    })*)*
+                    _ => return Err(format!("unknown key {}.{}", section, key)),
+                }
+                Ok(())
+            }
+
+            fn tables(&self) -> Vec<(&'static str, Vec<(&'static str, String)>)> {
+                vec![$((
+                    stringify!($section),
+                    vec![$((stringify!($key), self.$section.$key.serialize()),)*],
+                ),)*]
+            }
+        }
+    };
+}
+
+theme! {
+    // Line thicknesses, like font sizes, are for 50px cells and scale with the board
+    board: Board {
+        screen_padding: f32 = 10.,
+        background_color: Color = BLACK,
+        grid_color: Color = WHITE,
+        small_lines_thickness: f32 = 1.,
+        big_lines_thickness: f32 = 3.,
+        diagonal_color: Color = GRAY,
+        window_color: Color = Color::new(0.15, 0.15, 0.25, 1.),
+    }
+    digits: Digits {
+        big_font_size: f32 = 40.,
+        small_font_size: f32 = 20.,
+        // Givens are drawn again this many pixels to the right, thickening them
+        given_weight: f32 = 1.5,
+        given_color: Color = WHITE,
+        solved_color: Color = Color::new(0.55, 0.75, 1., 1.),
+        candidate_color: Color = WHITE,
+        entry_color: Color = Color::new(1., 0.8, 0.4, 1.),
+        conflict_color: Color = Color::new(0.8, 0.1, 0.1, 0.5),
+        // Entries found wrong when checked against the solution
+        mistake_color: Color = Color::new(1., 0.3, 0.3, 1.),
+    }
+    text: Text {
+        font_size: f32 = 20.,
+        color: Color = WHITE,
+        message_color: Color = ORANGE,
+    }
+    hint: Hint {
+        house_color: Color = Color::new(0.3, 0.6, 1., 0.15),
+        cell_color: Color = Color::new(0.3, 0.9, 0.4, 0.35),
+        candidate_color: Color = Color::new(0.3, 1., 0.4, 1.),
+        elimination_color: Color = Color::new(1., 0.35, 0.35, 1.),
+    }
+    selection: Selection {
+        color: Color = YELLOW,
+        thickness: f32 = 3.,
+        // Cells holding the selected digit, and those where it's still a candidate
+        digit_color: Color = Color::new(1., 1., 0.4, 0.18),
+        candidate_color: Color = Color::new(1., 1., 0.4, 0.07),
+    }
+    wave: Wave {
+        collapse_color: Color = Color::new(1., 0.85, 0.2, 0.3),
+        front_color: Color = Color::new(0.3, 0.6, 1., 0.25),
+        // Houses just filled in, fading out
+        flash_color: Color = Color::new(1., 1., 1., 0.35),
+    }
+    heatmap: Heatmap {
+        // Hues go from 0 to 1 around the color wheel
+        hottest_hue: f32 = 0.,
+        coldest_hue: f32 = 0.66,
+        alpha: f32 = 0.35,
+    }
+    volatility: Volatility {
+        calmest_hue: f32 = 0.66,
+        wildest_hue: f32 = 0.,
+        alpha: f32 = 0.6,
+        count_color: Color = WHITE,
+        // Relative to the cell size
+        count_font_scale: f32 = 0.3,
+        summary_color: Color = GOLD,
+    }
+    victory: Victory {
+        sparkle_alpha: f32 = 0.45,
+        font_size: f32 = 24.,
+        text_color: Color = GOLD,
+        panel_color: Color = Color::new(0., 0., 0., 0.75),
+        padding: f32 = 10.,
+    }
+    watchdog: Watchdog {
+        overlay_color: Color = Color::new(0., 0., 0., 0.8),
+        title_color: Color = ORANGE,
+        padding: f32 = 10.,
+    }
+    numpad: Numpad {
+        panel_color: Color = Color::new(0.05, 0.05, 0.08, 0.92),
+        key_color: Color = Color::new(0.22, 0.22, 0.3, 1.),
+        digit_color: Color = WHITE,
+        font_size: f32 = 28.,
+    }
+    browser: Browser {
+        font_size: f32 = 22.,
+        panel_color: Color = Color::new(0., 0., 0., 0.85),
+        heading_color: Color = GOLD,
+        text_color: Color = WHITE,
+        selected_color: Color = Color::new(0.3, 0.45, 0.8, 0.6),
+        padding: f32 = 10.,
+    }
+    editor: Editor {
+        path_color: Color = Color::new(1., 0.85, 0.2, 0.35),
+        region_alpha: f32 = 0.25,
+    }
+    // Lengths are relative to the cell size, font sizes to the reference cell
+    constraints: Constraints {
+        clue_font_size: f32 = 30.,
+        clue_color: Color = WHITE,
+        cage_color: Color = LIGHTGRAY,
+        cage_thickness: f32 = 1.,
+        cage_inset: f32 = 0.08,
+        cage_dash_length: f32 = 0.1,
+        cage_sum_font_size: f32 = 14.,
+        thermometer_color: Color = Color::new(0.5, 0.5, 0.5, 0.8),
+        thermometer_thickness: f32 = 0.25,
+        bulb_radius: f32 = 0.35,
+        arrow_color: Color = Color::new(0.6, 0.6, 0.6, 0.9),
+        arrow_thickness: f32 = 0.06,
+        arrow_circle_radius: f32 = 0.4,
+        arrow_head_length: f32 = 0.3,
+        palindrome_color: Color = Color::new(0.5, 0.5, 0.5, 0.8),
+        palindrome_thickness: f32 = 0.2,
+        entropy_color: Color = Color::new(1., 0.6, 0.2, 0.6),
+        modular_color: Color = Color::new(0.2, 0.7, 0.7, 0.6),
+        class_line_thickness: f32 = 0.2,
+        relation_color: Color = Color::new(0.6, 0.3, 0.8, 0.8),
+        relation_thickness: f32 = 0.15,
+        relation_font_size: f32 = 14.,
+        clone_color: Color = Color::new(0.4, 0.4, 0.4, 0.5),
+        clone_inset: f32 = 0.1,
+        kropki_radius: f32 = 0.12,
+        kropki_white_color: Color = WHITE,
+        kropki_black_color: Color = BLACK,
+        kropki_outline_color: Color = GRAY,
+        quadruple_radius: f32 = 0.3,
+        quadruple_color: Color = WHITE,
+        quadruple_outline_color: Color = BLACK,
+        quadruple_font_size: f32 = 15.,
+        quadruple_digit_color: Color = BLACK,
+        parity_color: Color = Color::new(0.5, 0.5, 0.5, 0.4),
+        parity_size: f32 = 0.8,
+    }
+    // Highlight colors replaced for color vision deficiencies
+    palette: PaletteChoice {
+        name: Palette = Palette::Normal,
+    }
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Palette {
+    Normal,
+    Deuteranopia,
+    Protanopia,
+    Tritanopia,
+}
+
+impl Palette {
+    const ALL: [Palette; 4] = [Palette::Normal, Palette::Deuteranopia, Palette::Protanopia, Palette::Tritanopia];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Palette::Normal => "normal",
+            Palette::Deuteranopia => "deuteranopia",
+            Palette::Protanopia => "protanopia",
+            Palette::Tritanopia => "tritanopia",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|&palette| palette == self).unwrap();
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+}
+
+
+impl Theme {
+    // Dark digits and lines on paper, the default theme being the dark one
+    pub fn light() -> Self {
+        let mut theme = Theme::default();
+        let ink = Color::new(0.1, 0.1, 0.12, 1.);
+        theme.board.background_color = Color::new(0.96, 0.94, 0.88, 1.);
+        theme.board.grid_color = ink;
+        theme.board.diagonal_color = Color::new(0.6, 0.6, 0.6, 1.);
+        theme.board.window_color = Color::new(0.84, 0.87, 0.95, 1.);
+        theme.digits.given_color = ink;
+        theme.digits.solved_color = Color::new(0.15, 0.3, 0.65, 1.);
+        theme.digits.candidate_color = Color::new(0.4, 0.4, 0.42, 1.);
+        theme.digits.entry_color = Color::new(0.7, 0.4, 0., 1.);
+        theme.digits.conflict_color = Color::new(0.9, 0.3, 0.3, 0.4);
+        theme.digits.mistake_color = Color::new(0.8, 0.1, 0.1, 1.);
+        theme.hint.house_color = Color::new(0.2, 0.4, 0.9, 0.12);
+        theme.hint.cell_color = Color::new(0.2, 0.7, 0.3, 0.3);
+        theme.hint.candidate_color = Color::new(0., 0.5, 0.1, 1.);
+        theme.hint.elimination_color = Color::new(0.8, 0.1, 0.1, 1.);
+        theme.text.color = ink;
+        theme.text.message_color = Color::new(0.8, 0.35, 0., 1.);
+        theme.selection.color = Color::new(0.9, 0.6, 0., 1.);
+        theme.wave.flash_color = Color::new(1., 0.8, 0.2, 0.45);
+        theme.selection.digit_color = Color::new(0.9, 0.6, 0., 0.2);
+        theme.selection.candidate_color = Color::new(0.9, 0.6, 0., 0.08);
+        theme.volatility.summary_color = Color::new(0.6, 0.45, 0., 1.);
+        theme.constraints.clue_color = ink;
+        theme.constraints.cage_color = Color::new(0.35, 0.35, 0.38, 1.);
+        theme.numpad.panel_color = Color::new(0.75, 0.73, 0.68, 0.92);
+        theme.numpad.key_color = Color::new(0.9, 0.88, 0.82, 1.);
+        theme.numpad.digit_color = ink;
+        theme.browser.panel_color = Color::new(0.9, 0.88, 0.82, 0.95);
+        theme.browser.heading_color = Color::new(0.6, 0.45, 0., 1.);
+        theme.browser.text_color = ink;
+        theme.browser.selected_color = Color::new(0.55, 0.65, 0.9, 0.6);
+        theme
+    }
+
+    // Red and green tell apart poorly with deuteranopia and protanopia, so highlights
+    // go blue against orange and yellow there. Blue and yellow being the pair lost
+    // with tritanopia, they go red and pink against cyan instead
+    fn with_palette(mut self) -> Self {
+        let (conflict, mistake, entry, selection, hot, cold, collapse, front) = match self.palette.name {
+            Palette::Normal => return self,
+            Palette::Deuteranopia | Palette::Protanopia => (
+                Color::new(0., 0.45, 0.7, 0.55),
+                Color::new(0., 0.45, 0.7, 1.),
+                Color::new(0.9, 0.6, 0., 1.),
+                Color::new(0.95, 0.9, 0.25, 1.),
+                0.08,
+                0.58,
+                Color::new(0.95, 0.9, 0.25, 0.3),
+                Color::new(0., 0.45, 0.7, 0.3),
+            ),
+            Palette::Tritanopia => (
+                Color::new(0.85, 0.15, 0.3, 0.55),
+                Color::new(0.85, 0.15, 0.3, 1.),
+                Color::new(1., 0.5, 0.6, 1.),
+                Color::new(0.8, 0.3, 0.7, 1.),
+                0.97,
+                0.5,
+                Color::new(1., 0.4, 0.5, 0.3),
+                Color::new(0.2, 0.8, 0.8, 0.25),
+            ),
+        };
+        self.digits.conflict_color = conflict;
+        self.digits.mistake_color = mistake;
+        self.digits.entry_color = entry;
+        self.selection.color = selection;
+        self.heatmap.hottest_hue = hot;
+        self.heatmap.coldest_hue = cold;
+        self.volatility.wildest_hue = hot;
+        self.volatility.calmest_hue = cold;
+        self.wave.collapse_color = collapse;
+        self.wave.front_color = front;
+        self
+    }
+}
+
+
+trait Value: Sized {
+    fn parse(text: &str) -> Option<Self>;
+    fn serialize(&self) -> String;
+}
+
+impl Value for f32 {
+    fn parse(text: &str) -> Option<Self> {
+        text.parse().ok().filter(|value: &f32| value.is_finite())
+    }
+
+    fn serialize(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+impl Value for Palette {
+    fn parse(text: &str) -> Option<Self> {
+        let name = text.strip_prefix('"')?.strip_suffix('"')?;
+        Palette::ALL.into_iter().find(|palette| palette.name() == name)
+    }
+
+    fn serialize(&self) -> String {
+        format!("\"{}\"", self.name())
+    }
+}
+
+// Colors are "#rrggbb" or "#rrggbbaa" strings
+impl Value for Color {
+    fn parse(text: &str) -> Option<Self> {
+        let hex = text.strip_prefix("\"#")?.strip_suffix('"')?;
+        if !(hex.len() == 6 || hex.len() == 8) || !hex.is_ascii() {
+            return None;
+        }
+        let mut channels = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok().map(|channel| channel as f32 / 255.));
+        let (r, g, b) = (channels.next()??, channels.next()??, channels.next()??);
+        let a = channels.next().unwrap_or(Some(1.))?;
+        Some(Color::new(r, g, b, a))
+    }
+
+    fn serialize(&self) -> String {
+        let [r, g, b, a] = [self.r, self.g, self.b, self.a].map(|channel| (channel * 255.).round() as u8);
+        match a {
+            255 => format!("\"#{:02x}{:02x}{:02x}\"", r, g, b),
+            _ => format!("\"#{:02x}{:02x}{:02x}{:02x}\"", r, g, b, a),
+        }
+    }
+}
+
+
+thread_local! {
+    // Theme as set, before its palette is applied
+    static BASE: RefCell<Theme> = RefCell::new(Theme::default());
+    static CURRENT: RefCell<Rc<Theme>> = RefCell::new(Rc::new(Theme::default()));
+    // Theme `swap` switches to, the light one until then
+    static SPARE: RefCell<Theme> = RefCell::new(Theme::light());
+}
+
+// Theme everything is drawn with
+pub fn current() -> Rc<Theme> {
+    CURRENT.with(|current| Rc::clone(&current.borrow()))
+}
+
+pub fn set(theme: Theme) {
+    let current = Rc::new(theme.clone().with_palette());
+    BASE.with(|base| *base.borrow_mut() = theme);
+    CURRENT.with(|cell| *cell.borrow_mut() = current);
+}
+
+// Switches between the current theme and the spare one, dark and light, keeping the palette
+pub fn swap() {
+    let palette = current().palette.name;
+    let mut theme = SPARE.with(|spare| BASE.with(|base| spare.replace(base.borrow().clone())));
+    theme.palette.name = palette;
+    set(theme);
+}
+
+pub fn set_palette(palette: Palette) {
+    let mut theme = BASE.with(|base| base.borrow().clone());
+    theme.palette.name = palette;
+    set(theme);
+}
+
+pub fn load(path: &str) -> Result<Theme, String> {
+    let text = std::fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+    parse(&text).map_err(|err| format!("{}: {}", path, err))
+}
+
+// Theme files are the TOML tables of the keys to restyle, anything left out
+// keeping its default:
+//
+//   [board]
+//   background_color = "#202020"
+//   big_lines_thickness = 4.0
+//
+//   [digits]
+//   solved_color = "#ffcc66"
+//
+//   [palette]
+//   name = "deuteranopia"
+pub fn parse(text: &str) -> Result<Theme, String> {
+    let mut theme = Theme::default();
+    let mut section = None;
+    for (n, line) in text.lines().enumerate() {
+        // Comments start at a '#' outside of strings
+        let mut quoted = false;
+        let end = line
+            .find(|c| {
+                quoted ^= c == '"';
+                c == '#' && !quoted
+            })
+            .unwrap_or(line.len());
+        let line = line[..end].trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+            section = Some(name.trim());
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(format!("line {}: expected key = value", n + 1));
+        };
+        let section = section.ok_or(format!("line {}: key outside of a table", n + 1))?;
+        theme
+            .set(section, key.trim(), value.trim())
+            .map_err(|err| format!("line {}: {}", n + 1, err))?;
+    }
+    Ok(theme)
+}
+
+pub fn serialize(theme: &Theme) -> String {
+    theme
+        .tables()
+        .into_iter()
+        .map(|(section, keys)| {
+            let keys: String = keys.into_iter().map(|(key, value)| format!("{} = {}\n", key, value)).collect();
+            format!("[{}]\n{}", section, keys)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn save(path: &str, theme: &Theme) -> Result<(), String> {
+    std::fs::write(path, serialize(theme)).map_err(|err| format!("{}: {}", path, err))
+}
@@ -0,0 +1,92 @@
+use macroquad::prelude::*;
+
+use sudoku::library::Difficulty;
+use sudoku::{text, theme};
+
+use crate::autosave;
+use crate::keys::{self, Action};
+use crate::language::{self, tr, Text};
+use crate::menu::Menu;
+use crate::records;
+
+
+const NAME: &str = "Wave Function Collapse Sudoku";
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Choice {
+    // The game left at the last exit
+    Resume,
+    Solve,
+    Puzzles,
+    Race,
+    Compare,
+    Analytics,
+    Tutorials,
+    Editor,
+    Options,
+    Quit,
+}
+
+fn label(choice: Choice) -> String {
+    let text = match choice {
+        Choice::Resume => Text::MenuResume,
+        Choice::Solve => Text::MenuSolve,
+        Choice::Puzzles => Text::MenuPuzzles,
+        Choice::Race => Text::MenuRace,
+        Choice::Compare => Text::MenuCompare,
+        Choice::Analytics => Text::MenuAnalytics,
+        Choice::Tutorials => Text::MenuTutorials,
+        Choice::Editor => Text::MenuEditor,
+        Choice::Options => Text::MenuOptions,
+        Choice::Quit => Text::MenuQuit,
+    };
+    tr(text, &[])
+}
+
+// Best time of each difficulty played, under a heading, none before any record
+fn best_times() -> Vec<String> {
+    let times = records::load();
+    let best: Vec<String> = Difficulty::ALL
+        .iter()
+        .filter_map(|&difficulty| {
+            let seconds = records::best(&times, difficulty)?;
+            Some(format!("{}   {}", language::difficulty(difficulty), tr(Text::Best, &[&records::format_time(seconds)])))
+        })
+        .collect();
+    match best.is_empty() {
+        true => best,
+        false => std::iter::once(tr(Text::BestTimes, &[])).chain(best).collect(),
+    }
+}
+
+// Menu shown at launch and when leaving a game, resuming offered while there's
+// an autosave, the best times in the corner
+pub async fn run() -> Choice {
+    let mut menu = Menu::default();
+    let best_times = best_times();
+    loop {
+        if is_quit_requested() {
+            std::process::exit(0);
+        }
+        let choices: Vec<Choice> = [Choice::Resume, Choice::Solve, Choice::Puzzles, Choice::Race, Choice::Compare, Choice::Analytics, Choice::Tutorials, Choice::Editor, Choice::Options, Choice::Quit]
+            .into_iter()
+            .filter(|&choice| choice != Choice::Resume || autosave::exists())
+            .collect();
+        let items: Vec<String> = choices.iter().map(|&choice| label(choice)).collect();
+        let title = format!("{}   {}", NAME, tr(Text::MenuKeys, &[&keys::label(Action::Pick)]));
+
+        clear_background(theme::current().board.background_color);
+        let picked = menu.update(&title, &items);
+        menu.draw(&title, &items);
+        let style = &theme::current().text;
+        for (i, line) in best_times.iter().rev().enumerate() {
+            let y = screen_height() - style.font_size * i as f32 - style.font_size / 2.;
+            text::draw(line, style.font_size / 2., y, style.font_size, style.color);
+        }
+        if let Some(i) = picked {
+            return choices[i];
+        }
+        next_frame().await;
+    }
+}
@@ -0,0 +1,76 @@
+use std::rc::Rc;
+
+use macroquad::prelude::*;
+
+use sudoku::constraints::Constraint;
+use sudoku::grid::{Grid, Variants};
+use sudoku::notation::format_cells;
+
+use crate::keys::{self, Action};
+use crate::language::{self, tr, Text};
+
+
+const FLAGS: [(Action, &str); 6] = [
+    (Action::Diagonal, "diagonal"),
+    (Action::Windoku, "windoku"),
+    (Action::AntiKnight, "anti-knight"),
+    (Action::AntiKing, "anti-king"),
+    (Action::NonConsecutive, "non-consecutive"),
+    (Action::DisjointGroups, "disjoint-groups"),
+];
+
+
+
+fn flag<'a>(variants: &'a mut Variants, name: &str) -> &'a mut bool {
+    match name {
+        "diagonal" => &mut variants.diagonal,
+        "windoku" => &mut variants.windoku,
+        "anti-knight" => &mut variants.anti_knight,
+        "anti-king" => &mut variants.anti_king,
+        "non-consecutive" => &mut variants.non_consecutive,
+        _ => &mut variants.disjoint_groups,
+    }
+}
+
+// Switches variants on and off in the middle of a solve, keeping the placements
+// the new rules still allow
+#[derive(Default)]
+pub struct Toggles {
+    // Variant file rules set aside while switched off
+    disabled: Vec<Rc<dyn Constraint>>,
+}
+
+impl Toggles {
+    // Returns what was switched and which placements it invalidated, if anything was
+    pub fn update(&mut self, grid: &mut Grid) -> Option<String> {
+        let mut variants = grid.variants.clone();
+        let mut disabled = self.disabled.clone();
+        let (name, on) = if let Some((_, name)) = FLAGS.iter().find(|(action, _)| keys::pressed(*action)) {
+            let flag = flag(&mut variants, name);
+            *flag = !*flag;
+            (name.to_string(), *flag)
+        } else if keys::pressed(Action::VariantRules) && !(variants.constraints.is_empty() && disabled.is_empty()) {
+            std::mem::swap(&mut variants.constraints, &mut disabled);
+            (tr(Text::VariantRules, &[]), disabled.is_empty())
+        } else {
+            return None;
+        };
+
+        let switched = tr(if on { Text::SwitchedOn } else { Text::SwitchedOff }, &[&name]);
+        let result = grid.set_variants(variants);
+        let message = match &result {
+            Ok(dropped) if dropped.is_empty() => switched,
+            Ok(dropped) => tr(Text::ToggleDropped, &[&switched, &dropped.len(), &format_cells(dropped, grid.size.side())]),
+            Err(contradiction) => tr(Text::ToggleRefused, &[&switched, &language::contradiction(contradiction, grid.size.side())]),
+        };
+        if result.is_ok() {
+            self.disabled = disabled;
+        }
+        Some(message)
+    }
+}
+
+pub fn help() -> String {
+    let flags: Vec<String> = FLAGS.iter().map(|&(action, _)| keys::label(action)).collect();
+    tr(Text::ToggleHelp, &[&flags.join("/"), &keys::label(Action::VariantRules)])
+}
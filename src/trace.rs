@@ -0,0 +1,87 @@
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::sync::mpsc::{self, Receiver, Sender};
+
+use sudoku::events::{self, Event};
+use sudoku::grid::Grid;
+use sudoku::notation::Coord;
+
+
+// Log of every solver step, written with --trace for analyzing long runs offline. Each
+// line gives the step number, the fingerprint of the grid after the step, then the event:
+//
+//   # seed 1234
+//   1 9f3a0c11d2e4b5a6 collapse R4C7 5
+//   1 9f3a0c11d2e4b5a6 eliminate R4C8 5
+//   57 0be1d3c9a2f47e10 contradiction R2C2
+//   322 51c8e0a7d3b2f964 solved
+//
+// Steps count from the start of the program, across restarts and new grids
+struct Trace {
+    path: String,
+    writer: BufWriter<File>,
+    sender: Sender<Event>,
+    receiver: Receiver<Event>,
+    step: u64,
+}
+
+thread_local! {
+    static TRACE: RefCell<Option<Trace>> = const { RefCell::new(None) };
+}
+
+pub fn start(path: &str) -> Result<(), String> {
+    let file = File::create(path).map_err(|err| format!("{}: {}", path, err))?;
+    let (sender, receiver) = mpsc::channel();
+    let trace = Trace { path: path.to_string(), writer: BufWriter::new(file), sender, receiver, step: 0 };
+    TRACE.with(|current| *current.borrow_mut() = Some(trace));
+    Ok(())
+}
+
+// Lines written as the trace goes, the trace stopping at the first error
+fn write(write: impl FnOnce(&mut Trace) -> std::io::Result<()>) {
+    TRACE.with(|current| {
+        let mut current = current.borrow_mut();
+        let Some(trace) = current.as_mut() else {
+            return;
+        };
+        if let Err(err) = write(trace).and_then(|()| trace.writer.flush()) {
+            eprintln!("Couldn't write the trace {}: {}", trace.path, err);
+            *current = None;
+        }
+    });
+}
+
+// Have the steps of a new grid traced, once for each grid
+pub fn follow(grid: &mut Grid) {
+    TRACE.with(|current| {
+        if let Some(trace) = current.borrow().as_ref() {
+            grid.subscribe(events::forward(trace.sender.clone()));
+        }
+    });
+}
+
+// A solve starting over from its seed
+pub fn seed(seed: u64) {
+    write(|trace| writeln!(trace.writer, "# seed {}", seed));
+}
+
+// Writes out what the step just taken did
+pub fn step(grid: &Grid) {
+    let side = grid.size.side();
+    let cell = |idx: usize| Coord::from_index(idx, side);
+    write(|trace| {
+        trace.step += 1;
+        let prefix = format!("{} {:016x}", trace.step, grid.fingerprint());
+        for event in trace.receiver.try_iter() {
+            match event {
+                Event::CellCollapsed { idx, value } => writeln!(trace.writer, "{} collapse {} {}", prefix, cell(idx), value)?,
+                Event::CandidateEliminated { idx, value } => writeln!(trace.writer, "{} eliminate {} {}", prefix, cell(idx), value)?,
+                Event::Contradiction { idx } => writeln!(trace.writer, "{} contradiction {}", prefix, cell(idx))?,
+                Event::Backjump { idx, value } => writeln!(trace.writer, "{} backjump {} {}", prefix, cell(idx), value)?,
+                Event::Solved => writeln!(trace.writer, "{} solved", prefix)?,
+            }
+        }
+        Ok(())
+    });
+}
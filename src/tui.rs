@@ -0,0 +1,254 @@
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+use macroquad::color::Color;
+
+use sudoku::grid::Grid;
+use sudoku::theme;
+
+use crate::metrics;
+use crate::trace;
+
+
+const NEW_GRID_KEY: u8 = b' ';
+const REPLAY_KEY: u8 = b'r';
+// Q or Ctrl+C
+const QUIT_KEYS: [u8; 2] = [b'q', 0x03];
+
+// Redrawn at most this often, fast ticks would flood slow connections
+const FRAME: Duration = Duration::from_millis(33);
+// Lines kept for the status above the board and the keys below
+const TEXT_LINES: usize = 3;
+
+
+// Terminal in raw mode on the alternate screen, put back as it was when dropped
+struct Terminal {
+    saved: libc::termios,
+}
+
+impl Terminal {
+    fn enter() -> io::Result<Self> {
+        let mut saved = unsafe { std::mem::zeroed::<libc::termios>() };
+        if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut saved) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let mut raw = saved;
+        unsafe { libc::cfmakeraw(&mut raw) };
+        if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        print!("\x1b[?1049h\x1b[?25l");
+        Ok(Self { saved })
+    }
+
+    // Columns and lines, 80x24 when the terminal won't say
+    fn size() -> (usize, usize) {
+        let mut size = unsafe { std::mem::zeroed::<libc::winsize>() };
+        match unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } {
+            0 if size.ws_col > 0 && size.ws_row > 0 => (size.ws_col as usize, size.ws_row as usize),
+            _ => (80, 24),
+        }
+    }
+
+    // Key pressed within the timeout, if any
+    fn key(timeout: Duration) -> Option<u8> {
+        let mut poll = libc::pollfd { fd: libc::STDIN_FILENO, events: libc::POLLIN, revents: 0 };
+        if unsafe { libc::poll(&mut poll, 1, timeout.as_millis() as libc::c_int) } <= 0 {
+            return None;
+        }
+        // Past the standard library's buffer, which would keep keys from poll
+        let mut key = 0u8;
+        let read = unsafe { libc::read(libc::STDIN_FILENO, &mut key as *mut u8 as *mut libc::c_void, 1) };
+        (read == 1).then_some(key)
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        print!("\x1b[0m\x1b[?25h\x1b[?1049l");
+        let _ = io::stdout().flush();
+        unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.saved) };
+    }
+}
+
+fn rgb(color: Color) -> (u8, u8, u8) {
+    ((color.r * 255.) as u8, (color.g * 255.) as u8, (color.b * 255.) as u8)
+}
+
+fn foreground(color: Color) -> String {
+    let (r, g, b) = rgb(color);
+    format!("\x1b[38;2;{};{};{}m", r, g, b)
+}
+
+fn background(color: Color) -> String {
+    let (r, g, b) = rgb(color);
+    format!("\x1b[48;2;{};{};{}m", r, g, b)
+}
+
+// Characters a cell takes across and down
+fn cell_size(grid: &Grid, full: bool) -> (usize, usize) {
+    let side = grid.size.side();
+    let digit_width = if side > 9 { 2 } else { 1 };
+    let columns = grid.size.box_width;
+    if full { (columns * digit_width + 2, side.div_ceil(columns)) } else { (digit_width, 1) }
+}
+
+// Board as lines of text in the theme colors. Walls are drawn between cells of
+// different regions, so jigsaws show as they are. Full cells list their candidates
+// in as many columns as a box has, compact ones take one line and show solved digits
+// only. Other variant markings aren't drawn
+fn board(grid: &Grid, full: bool) -> Vec<String> {
+    let theme = theme::current();
+    let side = grid.size.side();
+    let digit_width = if side > 9 { 2 } else { 1 };
+    let columns = grid.size.box_width;
+    let (width, height) = cell_size(grid, full);
+
+    let cell = |row: usize, col: usize| (row < side && col < side).then(|| row * side + col);
+    let wall = |a: Option<usize>, b: Option<usize>| match (a, b) {
+        (Some(a), Some(b)) => grid.region_of(a) != grid.region_of(b),
+        _ => true,
+    };
+    // Between columns col - 1 and col, and between rows row - 1 and row
+    let vertical = |row: usize, col: usize| wall(col.checked_sub(1).and_then(|col| cell(row, col)), cell(row, col));
+    let horizontal = |row: usize, col: usize| wall(row.checked_sub(1).and_then(|row| cell(row, col)), cell(row, col));
+
+    let base = background(theme.board.background_color);
+    let lines_color = foreground(theme.board.grid_color);
+    let mut lines = Vec::new();
+    for row in 0..=side {
+        let mut line = format!("{}{}", base, lines_color);
+        for col in 0..=side {
+            let corner = (row > 0 && vertical(row - 1, col))
+                || (row < side && vertical(row, col))
+                || (col > 0 && horizontal(row, col - 1))
+                || (col < side && horizontal(row, col));
+            line.push(if corner { '+' } else { ' ' });
+            if col < side {
+                let fill = if horizontal(row, col) { "-" } else { " " };
+                line.push_str(&fill.repeat(width));
+            }
+        }
+        lines.push(line);
+        if row == side {
+            break;
+        }
+
+        for text_line in 0..height {
+            let mut line = base.clone();
+            for col in 0..=side {
+                line.push_str(&lines_color);
+                line.push(if vertical(row, col) { '|' } else { ' ' });
+                if col == side {
+                    break;
+                }
+                let idx = row * side + col;
+                let values = &grid.cells[idx].possible_values;
+                if grid.last_collapse() == Some(idx) {
+                    line.push_str(&background(theme.wave.collapse_color));
+                }
+                let text = if values.len() == 1 {
+                    let color = if grid.is_given(idx) { theme.digits.given_color } else { theme.digits.solved_color };
+                    line.push_str(&foreground(color));
+                    let digit = if text_line == height / 2 { values[0].to_string() } else { String::new() };
+                    format!("{:^width$}", digit, width = width)
+                } else if full {
+                    line.push_str(&foreground(theme.digits.candidate_color));
+                    let shown = values.iter().skip(text_line * columns).take(columns);
+                    let digits: String = shown.map(|v| format!("{:>w$}", v, w = digit_width)).collect();
+                    format!(" {:<w$} ", digits, w = width - 2)
+                } else {
+                    line.push_str(&foreground(theme.digits.candidate_color));
+                    format!("{:>w$}", ".", w = width)
+                };
+                line.push_str(&text);
+                line.push_str(&base);
+            }
+            lines.push(line);
+        }
+    }
+    lines
+}
+
+// Solves in the terminal with the same tick loop as the window: a step every tick,
+// starting over on contradictions, a new seed or the same one again on request
+pub fn run(mut grid: Grid, seed: Option<u64>, tick_seconds: f64) -> io::Result<()> {
+    let _terminal = Terminal::enter()?;
+    let tick = Duration::from_secs_f64(tick_seconds.max(0.));
+    let mut seed = seed.unwrap_or_else(::rand::random);
+    grid.seed(seed);
+    trace::follow(&mut grid);
+    trace::seed(seed);
+    let mut solve_start = Instant::now();
+    let mut solve_time: Option<Duration> = None;
+    let mut restarts = 0;
+    let mut next_tick = Instant::now();
+    let mut last_draw: Option<Instant> = None;
+
+    loop {
+        if last_draw.is_none_or(|last| last.elapsed() >= FRAME) {
+            last_draw = Some(Instant::now());
+            draw(&grid, seed, restarts, solve_time)?;
+        }
+
+        let wait = if grid.is_resolve() { FRAME } else { next_tick.saturating_duration_since(Instant::now()) };
+        match Terminal::key(wait.min(FRAME)) {
+            Some(key) if QUIT_KEYS.contains(&key) => return Ok(()),
+            Some(key) if key == NEW_GRID_KEY || key == REPLAY_KEY => {
+                if key == NEW_GRID_KEY {
+                    seed = ::rand::random();
+                }
+                grid.seed(seed);
+                grid.reset();
+                trace::seed(seed);
+                solve_start = Instant::now();
+                solve_time = None;
+                restarts = 0;
+                next_tick = Instant::now();
+                last_draw = None;
+            }
+            _ => {}
+        }
+
+        if !grid.is_resolve() && Instant::now() >= next_tick {
+            next_tick += tick;
+            let result = grid.step();
+            trace::step(&grid);
+            if result.is_err() {
+                grid.reset();
+                restarts += 1;
+            }
+            if grid.is_resolve() {
+                solve_time = Some(solve_start.elapsed());
+                metrics::record(&grid, seed, solve_start.elapsed().as_secs_f64(), restarts);
+                last_draw = None;
+            }
+        }
+    }
+}
+
+fn draw(grid: &Grid, seed: u64, restarts: usize, solve_time: Option<Duration>) -> io::Result<()> {
+    let theme = theme::current();
+    let (columns, lines) = Terminal::size();
+    let side = grid.size.side();
+    let (width, height) = cell_size(grid, true);
+    let fits = side * (width + 1) < columns && side * (height + 1) + 1 + TEXT_LINES <= lines;
+    let board = board(grid, fits);
+
+    let status = match solve_time {
+        Some(time) => format!("Solved in {:.1}s with {} restarts, seed {}", time.as_secs_f64(), restarts, seed),
+        None => format!("Solving, {} restarts, seed {}", restarts, seed),
+    };
+    let keys = "[Space] new grid, [R] replay, [Q] quit";
+
+    let text = foreground(theme.text.color);
+    let mut frame = String::from("\x1b[H");
+    frame.push_str(&format!("\x1b[0m{}{}\x1b[K\r\n\x1b[K\r\n", text, status));
+    for line in board {
+        frame.push_str(&format!("{}\x1b[0m\x1b[K\r\n", line));
+    }
+    frame.push_str(&format!("{}{}\x1b[0m\x1b[K\x1b[J", text, keys));
+    let mut stdout = io::stdout().lock();
+    stdout.write_all(frame.as_bytes())?;
+    stdout.flush()
+}
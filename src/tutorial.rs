@@ -0,0 +1,279 @@
+use macroquad::prelude::*;
+
+use sudoku::grid::{BoardSize, Grid, Variants};
+use sudoku::hints::{self, Hint, Technique};
+use sudoku::layout::{Layout, Margins};
+use sudoku::notation::format_cells;
+use sudoku::{puzzle, text, theme};
+
+use crate::keys::{self, Action};
+use crate::language::{self, tr, Text};
+use crate::menu::Menu;
+use crate::play;
+
+
+// Examples of its technique a lesson goes through at most
+const EXAMPLES: usize = 3;
+// Cells kept above the board for the prompt to wrap into, on top of the help lines
+const PROMPT_ROOM: f32 = 1.;
+
+
+pub struct Lesson {
+    pub technique: Technique,
+    title: Text,
+    intro: Text,
+    // 9x9 grid on which the technique is the simplest step to take
+    givens: &'static str,
+}
+
+// The classic puzzle, falling to naked singles alone, then grids met following hints
+// through generated puzzles, taken where the technique came first with only singles
+// before it
+pub const LESSONS: [Lesson; 3] = [
+    Lesson {
+        technique: Technique::NakedSingle,
+        title: Text::LessonNakedSingle,
+        intro: Text::IntroNakedSingle,
+        givens: "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79",
+    },
+    Lesson {
+        technique: Technique::HiddenSingle,
+        title: Text::LessonHiddenSingle,
+        intro: Text::IntroHiddenSingle,
+        givens: "..35.2...82..71.4.......7...6...7..4..2..5..19..........52..93.28.3541...........",
+    },
+    Lesson {
+        technique: Technique::NakedPair,
+        title: Text::LessonNakedPair,
+        intro: Text::IntroNakedPair,
+        givens: "241387956935641278678592413784126539...9758..592834761...7.8.9...9.53.878.7..93..",
+    },
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stage {
+    // What the technique is, on the bare grid
+    Intro,
+    // The houses and cells of an example highlighted, for the player to work it out
+    Look,
+    // The candidates it rests on and the step it gives
+    Explain,
+    // The step taken
+    Applied,
+    Done,
+}
+
+// A lesson worked through one prompt at a time, on the candidates a player would note.
+// Steps simpler than the technique are taken on the way to each example
+pub struct Tutorial {
+    lesson: &'static Lesson,
+    grid: Grid,
+    values: Vec<Option<u8>>,
+    marks: Vec<Vec<u8>>,
+    hint: Option<Hint>,
+    stage: Stage,
+    examples: usize,
+    // Simpler steps taken before the example
+    filled: usize,
+}
+
+impl Tutorial {
+    pub fn new(lesson: &'static Lesson) -> Self {
+        let size = BoardSize::default();
+        // Lessons are all valid 9x9 grids
+        let values = puzzle::parse(lesson.givens, size).unwrap();
+        let grid = Grid::with_givens(size, Variants::default(), values.clone()).unwrap();
+        let marks = grid.pencil_marks(&values);
+        Self { lesson, grid, values, marks, hint: None, stage: Stage::Intro, examples: 0, filled: 0 }
+    }
+
+    // Example on the board, between finding it and taking its step
+    pub fn hint(&self) -> Option<&Hint> {
+        self.hint.as_ref().filter(|_| matches!(self.stage, Stage::Look | Stage::Explain))
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.stage == Stage::Done
+    }
+
+    // On to the next prompt
+    pub fn advance(&mut self) {
+        self.stage = match self.stage {
+            Stage::Intro => return self.next_example(),
+            Stage::Look => Stage::Explain,
+            Stage::Explain => {
+                if let Some(hint) = &self.hint {
+                    hints::apply(&self.grid, hint, &mut self.values, &mut self.marks);
+                }
+                self.examples += 1;
+                Stage::Applied
+            }
+            Stage::Applied if self.examples < EXAMPLES => return self.next_example(),
+            Stage::Applied | Stage::Done => Stage::Done,
+        };
+    }
+
+    // Takes the simpler steps up to an example of the technique, the lesson being over
+    // when a harder one or none comes first
+    fn next_example(&mut self) {
+        self.filled = 0;
+        while let Some(hint) = hints::find_among(&self.grid, &self.values, &self.marks) {
+            if hint.technique == self.lesson.technique {
+                self.hint = Some(hint);
+                self.stage = Stage::Look;
+                return;
+            }
+            if hint.technique > self.lesson.technique {
+                break;
+            }
+            hints::apply(&self.grid, &hint, &mut self.values, &mut self.marks);
+            self.filled += 1;
+        }
+        self.hint = None;
+        self.stage = Stage::Done;
+    }
+
+    fn prompt(&self) -> Vec<String> {
+        let side = self.grid.size.side();
+        let mut lines = Vec::new();
+        match (self.stage, &self.hint) {
+            (Stage::Intro, _) => lines.push(tr(self.lesson.intro, &[])),
+            (Stage::Look, _) if self.filled > 0 => {
+                lines.push(tr(Text::TutorialFilled, &[&self.filled]));
+                lines.push(tr(Text::TutorialLook, &[]));
+            }
+            (Stage::Look, _) => lines.push(tr(Text::TutorialLook, &[])),
+            (Stage::Explain, Some(hint)) => lines.push(language::hint(hint, side)),
+            (Stage::Applied, Some(hint)) => {
+                if let Some((idx, digit)) = hint.placement {
+                    lines.push(tr(Text::TutorialPlaced, &[&digit, &format_cells(&[idx], side)]));
+                }
+                let eliminated: Vec<usize> = hint.eliminations.iter().map(|&(idx, _)| idx).collect();
+                if !eliminated.is_empty() {
+                    let digits = hint.candidates.iter().map(u8::to_string).collect::<Vec<_>>().join("/");
+                    lines.push(tr(Text::TutorialEliminated, &[&format_cells(&eliminated, side), &digits]));
+                }
+            }
+            _ => lines.push(tr(Text::TutorialDone, &[&self.examples, &keys::label(Action::Pick)])),
+        }
+        lines
+    }
+
+    fn draw(&self, layout: &Layout) {
+        let theme = theme::current();
+        let hint = self.hint();
+        if let Some(hint) = hint {
+            play::tint_hint(hint, layout);
+        }
+        let applied = self.hint.as_ref().filter(|_| self.stage == Stage::Applied);
+        if let Some((idx, _)) = applied.and_then(|hint| hint.placement) {
+            let p = layout.cell_position(idx);
+            draw_rectangle(p.x, p.y, layout.cell_size, layout.cell_size, theme.hint.cell_color);
+        }
+        self.grid.draw_board(layout);
+        for (idx, value) in self.values.iter().enumerate() {
+            match value {
+                _ if self.grid.is_given(idx) => self.grid.draw_given(layout, idx),
+                Some(value) => self.grid.draw_digit(layout, idx, *value, theme.digits.solved_color),
+                None => self.grid.draw_marks(layout, idx, &self.marks[idx]),
+            }
+        }
+        match (self.stage, hint, applied) {
+            (Stage::Explain, Some(hint), _) => play::mark_hint(&self.grid, hint, layout),
+            // Candidates just eliminated, gone from the marks
+            (Stage::Applied, _, Some(hint)) => {
+                for &(idx, value) in &hint.eliminations {
+                    self.grid.draw_mark(layout, idx, value, theme.hint.elimination_color);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn title(lesson: &Lesson) -> String {
+    tr(lesson.title, &[])
+}
+
+// Lessons to pick from until going back to the title
+pub async fn run() {
+    let mut menu = Menu::default();
+    loop {
+        if is_quit_requested() {
+            std::process::exit(0);
+        }
+        let mut items: Vec<String> = LESSONS.iter().map(title).collect();
+        items.push(tr(Text::Back, &[]));
+        let heading = tr(Text::TutorialsTitle, &[&keys::label(Action::Pick), &keys::label(Action::Back)]);
+
+        clear_background(theme::current().board.background_color);
+        let picked = menu.update(&heading, &items);
+        menu.draw(&heading, &items);
+        match picked {
+            Some(i) if i < LESSONS.len() => teach(Tutorial::new(&LESSONS[i])).await,
+            Some(_) => return,
+            None if keys::pressed(Action::Back) => return,
+            None => {}
+        }
+        next_frame().await;
+    }
+}
+
+// The lesson on the board under its prompt, until it's over or left
+async fn teach(mut tutorial: Tutorial) {
+    // The key picking the lesson is still down
+    next_frame().await;
+    loop {
+        if is_quit_requested() {
+            std::process::exit(0);
+        }
+        if keys::pressed(Action::Back) {
+            return;
+        }
+        if keys::pressed(Action::Pick) {
+            if tutorial.is_done() {
+                return;
+            }
+            tutorial.advance();
+        }
+
+        let theme = theme::current();
+        clear_background(theme.board.background_color);
+        let layout = Layout::new(tutorial.grid.size, Margins { top: PROMPT_ROOM, ..Margins::default() });
+        tutorial.draw(&layout);
+        let keys = tr(Text::TutorialKeys, &[&title(tutorial.lesson), &keys::label(Action::Pick), &keys::label(Action::Back)]);
+        let font_size = theme.text.font_size;
+        text::draw(&keys, 0., font_size, font_size, theme.text.color);
+        let lines = tutorial.prompt().iter().flat_map(|line| text::wrap(line, screen_width() - font_size, font_size)).collect::<Vec<_>>();
+        for (i, line) in lines.iter().enumerate() {
+            text::draw(line, 0., (i + 2) as f32 * font_size, font_size, theme.text.message_color);
+        }
+        next_frame().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Every lesson shows examples of its own technique only, the digits placed on the way
+    // those of the solution
+    #[test]
+    fn lessons_keep_to_their_technique() {
+        for lesson in &LESSONS {
+            let mut tutorial = Tutorial::new(lesson);
+            let mut examples = 0;
+            while !tutorial.is_done() {
+                tutorial.advance();
+                if let Some(hint) = tutorial.hint() {
+                    assert_eq!(hint.technique, lesson.technique);
+                    examples += 1;
+                    tutorial.advance();
+                }
+            }
+            assert!(examples > 0, "{:?} lesson has no example", lesson.technique);
+            let mut grid = Grid::with_givens(BoardSize::default(), Variants::default(), tutorial.values.clone()).unwrap();
+            assert_eq!(grid.count_solutions(2), 1, "{:?} lesson went off the solution", lesson.technique);
+        }
+    }
+}
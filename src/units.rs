@@ -0,0 +1,116 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::cell::Cell;
+use crate::grid::BoardSize;
+use crate::notation::Coord;
+
+
+// A cell of the grid along with its position
+#[derive(Debug, Clone, Copy)]
+pub struct CellView<'a> {
+    pub idx: usize,
+    pub coord: Coord,
+    pub cell: &'a Cell,
+}
+
+impl CellView<'_> {
+    // Digit of the cell once collapsed
+    pub fn value(&self) -> Option<u8> {
+        match self.cell.possible_values[..] {
+            [value] => Some(value),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HouseKind {
+    Row(usize),
+    Col(usize),
+    // Jigsaw region when the grid has some
+    Box(usize),
+}
+
+// Cells that must hold distinct digits
+#[derive(Debug, Clone)]
+pub struct House<'a> {
+    pub kind: HouseKind,
+    side: usize,
+    cells: &'a [Cell],
+    indices: &'a [usize],
+}
+
+impl<'a> House<'a> {
+    pub(crate) fn new(kind: HouseKind, side: usize, cells: &'a [Cell], indices: &'a [usize]) -> Self {
+        Self { kind, side, cells, indices }
+    }
+
+    pub fn indices(&self) -> &'a [usize] {
+        self.indices
+    }
+
+    pub fn contains(&self, idx: usize) -> bool {
+        self.indices.contains(&idx)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = CellView<'a>> + '_ {
+        self.indices.iter().map(|&idx| view(self.cells, self.side, idx))
+    }
+}
+
+pub(crate) fn view(cells: &[Cell], side: usize, idx: usize) -> CellView<'_> {
+    CellView { idx, coord: Coord::from_index(idx, side), cell: &cells[idx] }
+}
+
+// Cells of every row, column and box or region, and the peers of each cell, the cells
+// sharing one of them. Worked out once for the board and shared by its grids, batch
+// generation building one for every given it tries to take away
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct UnitTables {
+    size: BoardSize,
+    regions: Option<Vec<usize>>,
+    pub rows: Vec<Vec<usize>>,
+    pub cols: Vec<Vec<usize>>,
+    pub boxes: Vec<Vec<usize>>,
+    pub peers: Vec<Vec<usize>>,
+}
+
+thread_local! {
+    // Tables of the last board asked for
+    static LAST: RefCell<Option<Rc<UnitTables>>> = const { RefCell::new(None) };
+}
+
+impl UnitTables {
+    pub fn get(size: BoardSize, regions: Option<&[usize]>) -> Rc<Self> {
+        LAST.with(|last| {
+            let mut last = last.borrow_mut();
+            match last.as_ref() {
+                Some(tables) if tables.size == size && tables.regions.as_deref() == regions => Rc::clone(tables),
+                _ => Rc::clone(last.insert(Rc::new(Self::new(size, regions)))),
+            }
+        })
+    }
+
+    fn new(size: BoardSize, regions: Option<&[usize]>) -> Self {
+        let side = size.side();
+        let region_of = |idx: usize| regions.map_or_else(|| Coord::from_index(idx, side).box_index(size), |regions| regions[idx]);
+        let rows: Vec<Vec<usize>> = (0..side).map(|row| (0..side).map(|col| Coord::new(row, col).index(side)).collect()).collect();
+        let cols: Vec<Vec<usize>> = (0..side).map(|col| (0..side).map(|row| Coord::new(row, col).index(side)).collect()).collect();
+        let mut boxes = vec![Vec::with_capacity(side); side];
+        for idx in 0..size.cell_count() {
+            boxes[region_of(idx)].push(idx);
+        }
+        let peers = (0..size.cell_count())
+            .map(|idx| {
+                let coord = Coord::from_index(idx, side);
+                let houses = [&rows[coord.row], &cols[coord.col], &boxes[region_of(idx)]];
+                let mut peers: Vec<usize> = houses.into_iter().flatten().copied().filter(|&peer| peer != idx).collect();
+                peers.sort_unstable();
+                peers.dedup();
+                peers
+            })
+            .collect();
+        Self { size, regions: regions.map(<[usize]>::to_vec), rows, cols, boxes, peers }
+    }
+}
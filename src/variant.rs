@@ -0,0 +1,549 @@
+use std::rc::Rc;
+
+use crate::constraints::{
+    Arrow, Cage, ClassLine, CloneRegions, Constraint, Dot, Kropki, LineClasses, Palindrome, Parity,
+    ParityCells, Quadruple, Relation, Sandwich, Skyscraper, Thermometer, XSum,
+};
+use crate::expression::Expression;
+use crate::grid::{BoardSize, Variants};
+use crate::layout::Edge;
+use crate::notation::Coord;
+
+
+pub const FORMAT_VERSION: u32 = 1;
+
+const OUTSIDE_CLUE_RULES: [&str; 3] = ["skyscraper", "xsum", "sandwich"];
+const REGION_SYMBOLS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+
+// Variant files list one rule per line, `#` starting a comment:
+//
+//   version 1
+//   diagonal
+//   windoku
+//   anti-knight
+//   anti-king
+//   non-consecutive
+//   disjoint-groups
+//   skyscraper top 2 . . 3 . . . . .
+//   xsum left . 15 . . . . . . 20
+//   sandwich top 0 . 35 . . 12 . . .
+//   quadruple r4c5 1 3 3
+//   clone r1c1 r1c2 / r9c8 r9c9
+//   palindrome r2c2 r3c3 r4c4 r5c3
+//   entropy r1c1 r1c2 r1c3 r2c3
+//   modular r9c1 r8c2 r7c3
+//   cage 15 r1c1 r1c2 r2c1
+//   kropki white r1c1 r1c2
+//   kropki black r4c4 r5c4
+//   kropki-negative
+//   thermo r1c1 r2c1 r3c2
+//   arrow r5c5 r4c6 r3c7
+//   relation r1c1 r1c2 r2c2 : abs(a - b) >= 4
+//   odd r1c1 r5c5
+//   even r2c8 r9c9
+//   regions AAABBBCCC AAABBBCCC ...
+//
+// The optional version names the format revision a file was written for, files without
+// one are read as version 1. `serialize` writes the canonical form of a configuration,
+// shared by everything saving or exchanging puzzles so encodings can't drift apart.
+// Relations hold between each pair of consecutive cells, see `expression` for the syntax.
+// Outside clues give the edge then one clue per row/column, `.` for none.
+// Cells are written r<row>c<column>, both counted from 1. Jigsaw regions give
+// one word per row, cells with the same symbol belonging to the same region.
+pub fn load(path: &str, size: BoardSize) -> Result<Variants, String> {
+    let text = std::fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+    parse(&text, size).map_err(|err| format!("{}: {}", path, err))
+}
+
+pub fn parse(text: &str, size: BoardSize) -> Result<Variants, String> {
+    let mut variants = Variants::default();
+    // Dots are gathered in one constraint since the negative convention depends on all of them
+    let mut kropki = Kropki::new(size.side());
+    for (n, line) in text.lines().enumerate() {
+        let mut words = line.split('#').next().unwrap_or_default().split_whitespace();
+        if let Some(rule) = words.next() {
+            let args: Vec<&str> = words.collect();
+            parse_rule(&mut variants, &mut kropki, rule, &args, size)
+                .map_err(|err| format!("line {}: {}", n + 1, err))?;
+        }
+    }
+    if !kropki.is_empty() {
+        variants.constraints.push(Rc::new(kropki));
+    }
+    Ok(variants)
+}
+
+// Constraints of a single rule, checked as when reading a file. Rules setting flags or
+// regions are refused, they add no constraint
+pub fn parse_constraints(rule: &str, size: BoardSize) -> Result<Vec<Rc<dyn Constraint>>, String> {
+    let mut words = rule.split_whitespace();
+    let name = words.next().ok_or("empty rule")?;
+    let args: Vec<&str> = words.collect();
+    let mut variants = Variants::default();
+    let mut kropki = Kropki::new(size.side());
+    parse_rule(&mut variants, &mut kropki, name, &args, size)?;
+    if !kropki.is_empty() {
+        variants.constraints.push(Rc::new(kropki));
+    }
+    if variants.constraints.is_empty() {
+        return Err(format!("'{}' adds no constraint", name));
+    }
+    Ok(variants.constraints)
+}
+
+fn parse_rule(
+    variants: &mut Variants,
+    kropki: &mut Kropki,
+    rule: &str,
+    args: &[&str],
+    size: BoardSize,
+) -> Result<(), String> {
+    let side = size.side();
+    match rule {
+        "version" => {
+            let version = args.first().and_then(|v| v.parse::<u32>().ok()).ok_or("invalid version")?;
+            if version == 0 || version > FORMAT_VERSION {
+                return Err(format!(
+                    "unsupported format version {}, the newest known is {}",
+                    version, FORMAT_VERSION
+                ));
+            }
+        }
+        "diagonal" => variants.diagonal = true,
+        "windoku" => variants.windoku = true,
+        "anti-knight" => variants.anti_knight = true,
+        "anti-king" => variants.anti_king = true,
+        "non-consecutive" => variants.non_consecutive = true,
+        "disjoint-groups" => variants.disjoint_groups = true,
+        "skyscraper" => {
+            let (edge, clues) = parse_outside_clues(args, side)?;
+            for (line, clue) in clues {
+                if clue == 0 || clue as usize > side {
+                    return Err(format!("skyscraper clue {} out of range", clue));
+                }
+                variants.constraints.push(Rc::new(Skyscraper::new(side, edge, line, clue)));
+            }
+        }
+        "xsum" => {
+            let (edge, clues) = parse_outside_clues(args, side)?;
+            // From a 1 in front to the whole line summed
+            let most = side * (side + 1) / 2;
+            for (line, clue) in clues {
+                if clue == 0 || clue as usize > most {
+                    return Err(format!("xsum clue {} out of range, expected 1 to {}", clue, most));
+                }
+                variants.constraints.push(Rc::new(XSum::new(side, edge, line, clue)));
+            }
+        }
+        "sandwich" => {
+            let (edge, clues) = parse_outside_clues(args, side)?;
+            // From the 1 and the side touching to every other digit between them
+            let most = side * (side + 1) / 2 - 1 - side;
+            for (line, clue) in clues {
+                if clue as usize > most {
+                    return Err(format!("sandwich clue {} out of range, expected 0 to {}", clue, most));
+                }
+                variants.constraints.push(Rc::new(Sandwich::new(side, edge, line, clue)));
+            }
+        }
+        "quadruple" => {
+            let (cell, digits) = args.split_first().ok_or("missing cell")?;
+            let top_left = parse_cell(cell, side)?;
+            if top_left % side == side - 1 || top_left / side == side - 1 {
+                return Err(format!("no intersection below and right of {}", cell));
+            }
+            let digits = digits
+                .iter()
+                .map(|d| parse_digit(d, side))
+                .collect::<Result<Vec<u8>, String>>()?;
+            if digits.is_empty() || digits.len() > 4 {
+                return Err("a quadruple lists one to four digits".to_string());
+            }
+            variants.constraints.push(Rc::new(Quadruple::new(side, top_left, digits)));
+        }
+        "clone" => {
+            let split = args.iter().position(|&a| a == "/").ok_or("missing '/' between regions")?;
+            let first = parse_cells(&args[..split], side)?;
+            let second = parse_cells(&args[split + 1..], side)?;
+            if first.is_empty() || first.len() != second.len() {
+                return Err("clone regions must have the same number of cells".to_string());
+            }
+            variants.constraints.push(Rc::new(CloneRegions::new(first, second)));
+        }
+        "palindrome" => {
+            let cells = parse_cells(args, side)?;
+            if cells.len() < 2 {
+                return Err("a palindrome needs at least two cells".to_string());
+            }
+            variants.constraints.push(Rc::new(Palindrome::new(cells)));
+        }
+        "entropy" | "modular" => {
+            let classes = if rule == "entropy" { LineClasses::Entropy } else { LineClasses::Modular };
+            let cells = parse_cells(args, side)?;
+            if cells.len() < 3 {
+                return Err(format!("an {} line needs at least three cells", rule));
+            }
+            variants.constraints.push(Rc::new(ClassLine::new(classes, side, cells)));
+        }
+        "cage" => {
+            let (sum, cells) = args.split_first().ok_or("missing sum")?;
+            let sum = sum.parse().map_err(|_| format!("invalid sum '{}'", sum))?;
+            let cells = parse_cells(cells, side)?;
+            if cells.is_empty() || cells.len() > side {
+                return Err(format!("a cage has one to {} cells", side));
+            }
+            variants.constraints.push(Rc::new(Cage::new(sum, side, cells)));
+        }
+        "kropki" => {
+            let dot = match args.first() {
+                Some(&"white") => Dot::White,
+                Some(&"black") => Dot::Black,
+                _ => return Err("kropki dots are white or black".to_string()),
+            };
+            let cells = parse_cells(&args[1..], side)?;
+            match cells[..] {
+                [a, b] if Coord::from_index(a, side).is_adjacent(Coord::from_index(b, side)) => {
+                    kropki.add_dot(dot, a, b);
+                }
+                _ => return Err("a kropki dot sits between two adjacent cells".to_string()),
+            }
+        }
+        "kropki-negative" => kropki.set_negative(),
+        "thermo" => {
+            let cells = parse_cells(args, side)?;
+            if cells.len() < 2 || cells.len() > side {
+                return Err(format!("a thermometer has two to {} cells", side));
+            }
+            variants.constraints.push(Rc::new(Thermometer::new(cells)));
+        }
+        "arrow" => {
+            let cells = parse_cells(args, side)?;
+            if cells.len() < 2 {
+                return Err("an arrow needs a circle and at least one shaft cell".to_string());
+            }
+            variants.constraints.push(Rc::new(Arrow::new(cells)));
+        }
+        "odd" | "even" => {
+            let parity = if rule == "odd" { Parity::Odd } else { Parity::Even };
+            let cells = parse_cells(args, side)?;
+            if cells.is_empty() {
+                return Err(format!("{} lists at least one cell", rule));
+            }
+            variants.constraints.push(Rc::new(ParityCells::new(parity, cells)));
+        }
+        "relation" => {
+            let split = args.iter().position(|&a| a == ":").ok_or("missing ':' before expression")?;
+            let cells = parse_cells(&args[..split], side)?;
+            if cells.len() < 2 {
+                return Err("a relation needs at least two cells".to_string());
+            }
+            let expression = Expression::parse(&args[split + 1..].join(" "))?;
+            variants.constraints.push(Rc::new(Relation::new(expression, side, cells)));
+        }
+        "regions" => variants.regions = Some(parse_regions(args, side)?),
+        _ => return Err(format!("unknown rule '{}'", rule)),
+    }
+    Ok(())
+}
+
+pub fn save(path: &str, variants: &Variants, size: BoardSize) -> Result<(), String> {
+    std::fs::write(path, serialize(variants, size)).map_err(|err| format!("{}: {}", path, err))
+}
+
+pub fn serialize(variants: &Variants, size: BoardSize) -> String {
+    let side = size.side();
+    let mut rules = vec![format!("version {}", FORMAT_VERSION)];
+    let flags = [
+        (variants.diagonal, "diagonal"),
+        (variants.windoku, "windoku"),
+        (variants.anti_knight, "anti-knight"),
+        (variants.anti_king, "anti-king"),
+        (variants.non_consecutive, "non-consecutive"),
+        (variants.disjoint_groups, "disjoint-groups"),
+    ];
+    rules.extend(flags.iter().filter(|(set, _)| *set).map(|(_, name)| name.to_string()));
+    if let Some(regions) = &variants.regions {
+        let rows: Vec<String> = regions
+            .chunks(side)
+            .map(|row| row.iter().map(|&region| REGION_SYMBOLS[region] as char).collect())
+            .collect();
+        rules.push(format!("regions {}", rows.join(" ")));
+    }
+    for constraint in &variants.constraints {
+        rules.extend(constraint.rule(side).lines().map(str::to_string));
+    }
+
+    let mut text = merge_outside_clues(rules, side).join("\n");
+    text.push('\n');
+    text
+}
+
+// Outside clue constraints each write their own line, gather those of the same edge
+fn merge_outside_clues(rules: Vec<String>, side: usize) -> Vec<String> {
+    let mut merged: Vec<Vec<String>> = Vec::new();
+    for rule in rules {
+        let words: Vec<String> = rule.split_whitespace().map(str::to_string).collect();
+        let is_outside_clue = OUTSIDE_CLUE_RULES.contains(&words[0].as_str())
+            && words.len() == side + 2;
+        let target = merged.iter_mut().find(|other| {
+            is_outside_clue
+                && other.len() == words.len()
+                && other[..2] == words[..2]
+                && other[2..].iter().zip(&words[2..]).all(|(a, b)| a == "." || b == ".")
+        });
+        match target {
+            Some(other) => {
+                for (clue, new) in other[2..].iter_mut().zip(&words[2..]) {
+                    if new != "." {
+                        clue.clone_from(new);
+                    }
+                }
+            }
+            None => merged.push(words),
+        }
+    }
+    merged.into_iter().map(|words| words.join(" ")).collect()
+}
+
+// Region layout files hold the rows of a `regions` rule, one per line
+pub fn load_regions(path: &str, size: BoardSize) -> Result<Vec<usize>, String> {
+    let text = std::fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+    parse_region_layout(&text, size).map_err(|err| format!("{}: {}", path, err))
+}
+
+pub fn parse_region_layout(text: &str, size: BoardSize) -> Result<Vec<usize>, String> {
+    let rows: Vec<&str> = text.lines().map(str::trim).filter(|row| !row.is_empty()).collect();
+    parse_regions(&rows, size.side())
+}
+
+fn parse_regions(rows: &[&str], side: usize) -> Result<Vec<usize>, String> {
+    if rows.len() != side {
+        return Err(format!("regions need {} rows of {} cells", side, side));
+    }
+    if let Some(n) = rows.iter().position(|row| row.chars().count() != side) {
+        return Err(format!("region row {} has {} cells instead of {}", n + 1, rows[n].chars().count(), side));
+    }
+    let symbols: Vec<char> = rows.iter().flat_map(|row| row.chars()).collect();
+
+    let mut names: Vec<char> = Vec::new();
+    let regions: Vec<usize> = symbols
+        .iter()
+        .map(|symbol| match names.iter().position(|name| name == symbol) {
+            Some(region) => region,
+            None => {
+                names.push(*symbol);
+                names.len() - 1
+            }
+        })
+        .collect();
+
+    check_region_cells(&regions, side, |region| names[region])?;
+    Ok(regions)
+}
+
+// Every region must be a connected group of `side` cells
+pub fn check_regions(regions: &[usize], side: usize) -> Result<(), String> {
+    check_region_cells(regions, side, |region| REGION_SYMBOLS[region] as char)
+}
+
+fn check_region_cells(regions: &[usize], side: usize, name: impl Fn(usize) -> char) -> Result<(), String> {
+    let count = regions.iter().max().map_or(0, |max| max + 1);
+    for region in 0..count {
+        let cells: Vec<usize> = (0..regions.len()).filter(|&idx| regions[idx] == region).collect();
+        if cells.len() != side {
+            return Err(format!("region '{}' has {} cells instead of {}", name(region), cells.len(), side));
+        }
+        if !is_connected(&cells, side) {
+            return Err(format!("region '{}' is not connected", name(region)));
+        }
+    }
+    Ok(())
+}
+
+fn is_connected(cells: &[usize], side: usize) -> bool {
+    let mut reached = vec![cells[0]];
+    let mut i = 0;
+    while i < reached.len() {
+        let idx = reached[i];
+        for &other in cells {
+            let adjacent = Coord::from_index(idx, side).is_adjacent(Coord::from_index(other, side));
+            if adjacent && !reached.contains(&other) {
+                reached.push(other);
+            }
+        }
+        i += 1;
+    }
+    reached.len() == cells.len()
+}
+
+fn parse_outside_clues(args: &[&str], side: usize) -> Result<(Edge, Vec<(usize, u32)>), String> {
+    let (edge, clues) = args.split_first().ok_or("missing edge")?;
+    let edge = Edge::from_name(edge).ok_or_else(|| format!("unknown edge '{}'", edge))?;
+    if clues.len() != side {
+        return Err(format!("expected {} clues, got {}", side, clues.len()));
+    }
+
+    let mut parsed = Vec::new();
+    for (line, clue) in clues.iter().enumerate() {
+        if *clue != "." {
+            let clue = clue.parse().map_err(|_| format!("invalid clue '{}'", clue))?;
+            parsed.push((line, clue));
+        }
+    }
+    Ok((edge, parsed))
+}
+
+fn parse_cell(word: &str, side: usize) -> Result<usize, String> {
+    Coord::parse(word, side).map(|coord| coord.index(side))
+}
+
+// Cells listed once each
+fn parse_cells(words: &[&str], side: usize) -> Result<Vec<usize>, String> {
+    let mut cells = Vec::with_capacity(words.len());
+    for word in words {
+        let cell = parse_cell(word, side)?;
+        if cells.contains(&cell) {
+            return Err(format!("cell '{}' listed twice", word));
+        }
+        cells.push(cell);
+    }
+    Ok(cells)
+}
+
+fn parse_digit(word: &str, side: usize) -> Result<u8, String> {
+    match word.parse() {
+        Ok(digit) if (1..=side).contains(&(digit as usize)) => Ok(digit),
+        _ => Err(format!("invalid digit '{}'", word)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/variants");
+
+    fn round_trip(text: &str, size: BoardSize) -> String {
+        let first = serialize(&parse(text, size).unwrap(), size);
+        let second = serialize(&parse(&first, size).unwrap(), size);
+        assert_eq!(first, second, "serialized form isn't stable");
+        first
+    }
+
+    #[test]
+    fn example_files_round_trip() {
+        for entry in std::fs::read_dir(EXAMPLES).unwrap() {
+            let path = entry.unwrap().path();
+            // Region layout files aren't variant files
+            if load_regions(path.to_str().unwrap(), BoardSize::default()).is_ok() {
+                continue;
+            }
+            let text = std::fs::read_to_string(&path).unwrap();
+            let serialized = round_trip(&text, BoardSize::default());
+            assert!(serialized.starts_with("version 1\n"), "{}", path.display());
+        }
+    }
+
+    #[test]
+    fn files_without_version_are_version_one() {
+        let text = "diagonal\ncage 10 r1c1 r1c2\n";
+        let serialized = round_trip(text, BoardSize::default());
+        assert_eq!(serialized, format!("version 1\n{}", text));
+    }
+
+    #[test]
+    fn repeated_cells_are_rejected() {
+        assert!(parse("cage 10 r1c1 r1c1", BoardSize::default()).is_err());
+        assert!(parse("thermo r1c1 r1c2 r1c1", BoardSize::default()).is_err());
+        assert!(parse("clone r1c1 r1c2 / r2c1 r2c2", BoardSize::default()).is_ok());
+    }
+
+    #[test]
+    fn ragged_regions_are_rejected() {
+        let size = BoardSize::from_side(6).unwrap();
+        let err = parse("regions AAABB AAABBBB CCCDDD CCCDDD EEEFFF EEEFFF", size).unwrap_err();
+        assert!(err.contains("row 1"), "{}", err);
+        assert!(parse_region_layout("AAABBB\nAAABBB\nCCCDDD\nCCCDDDD\nEEEFFF\nEEEFF\n", size).is_err());
+    }
+
+    // What the editor builds, held to the same limits as files
+    #[test]
+    fn single_rules_are_checked_like_files() {
+        let size = BoardSize::default();
+        assert!(parse_constraints("thermo r1c1", size).is_err());
+        assert!(parse_constraints("thermo r1c1 r1c2 r1c3 r1c4 r1c5 r1c6 r1c7 r1c8 r1c9 r2c9", size).is_err());
+        assert!(parse_constraints("cage 45 r1c1 r1c2 r1c3 r1c4 r1c5 r1c6 r1c7 r1c8 r1c9 r2c9", size).is_err());
+        assert!(parse_constraints("relation r1c1 r1c2 : a +", size).is_err());
+        assert!(parse_constraints("diagonal", size).is_err());
+        assert_eq!(parse_constraints("quadruple r4c5 1 3 3", size).unwrap().len(), 1);
+        assert_eq!(parse_constraints("skyscraper top 2 . . 3 . . . . 1", size).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn xsum_clues_are_range_checked() {
+        let size = BoardSize::default();
+        assert!(parse("xsum top 1 . . . . . . . 45", size).is_ok());
+        assert!(parse("xsum top 0 . . . . . . . .", size).is_err());
+        let err = parse("xsum top 46 . . . . . . . .", size).unwrap_err();
+        assert!(err.contains("1 to 45"), "{}", err);
+        assert!(parse("xsum left . . 11 .", BoardSize::from_side(4).unwrap()).is_err());
+    }
+
+    #[test]
+    fn sandwich_clues_are_range_checked() {
+        let size = BoardSize::default();
+        assert!(parse("sandwich top 0 . . . . . . . 35", size).is_ok());
+        let err = parse("sandwich top 36 . . . . . . . .", size).unwrap_err();
+        assert!(err.contains("0 to 35"), "{}", err);
+        assert!(parse("sandwich left . . 6 .", BoardSize::from_side(4).unwrap()).is_err());
+    }
+
+    #[test]
+    fn newer_versions_are_rejected() {
+        assert!(parse("version 1", BoardSize::default()).is_ok());
+        assert!(parse("version 2\ndiagonal", BoardSize::default()).is_err());
+        assert!(parse("version 0", BoardSize::default()).is_err());
+    }
+
+    #[test]
+    fn outside_clues_of_an_edge_share_a_line() {
+        let text = "version 1\n\
+            skyscraper top 2 . . 3 . . . . 1\n\
+            skyscraper left . 4 . . . . . . .\n\
+            xsum top . 10 . . . . . . .\n";
+        assert_eq!(round_trip(text, BoardSize::default()), text);
+    }
+
+    #[test]
+    fn every_rule_is_kept() {
+        let text = "version 1\n\
+            windoku\n\
+            anti-knight\n\
+            anti-king\n\
+            non-consecutive\n\
+            disjoint-groups\n\
+            sandwich bottom . . . . 35 . . . .\n\
+            quadruple r4c5 1 3 3\n\
+            clone r1c1 r1c2 / r9c8 r9c9\n\
+            palindrome r2c2 r3c3 r4c4 r5c3\n\
+            entropy r1c1 r1c2 r1c3 r2c3\n\
+            modular r9c1 r8c2 r7c3\n\
+            thermo r1c1 r2c1 r3c2\n\
+            arrow r5c5 r4c6 r3c7\n\
+            relation r1c1 r1c2 r2c2 : abs(a - b) >= 4\n\
+            odd r1c1 r5c5\n\
+            even r2c8 r9c9\n\
+            kropki white r1c1 r1c2\n\
+            kropki black r4c4 r5c4\n\
+            kropki-negative\n";
+        assert_eq!(round_trip(text, BoardSize::default()), text);
+    }
+
+    #[test]
+    fn regions_round_trip() {
+        let size = BoardSize::from_side(6).unwrap();
+        let text = "version 1\nregions AAABBB AAABBB CCCDDD CCCDDD EEEFFF EEEFFF\n";
+        assert_eq!(round_trip(text, size), text);
+    }
+}
@@ -0,0 +1,67 @@
+use std::f64::consts::TAU;
+
+use macroquad::prelude::*;
+
+use sudoku::grid::Grid;
+use sudoku::layout::Layout;
+use sudoku::text;
+use sudoku::theme;
+
+use crate::language::{tr, Text};
+
+
+const FLASH_SECONDS: f64 = 2.5;
+// Time for the sparkle to sweep from one corner of the board to the other
+const SWEEP_SECONDS: f64 = 0.8;
+
+
+// Solved state, entered once the grid resolves
+pub struct Victory {
+    solve_seconds: f64,
+    restarts: usize,
+    since: f64,
+}
+
+impl Victory {
+    pub fn new(solve_seconds: f64, restarts: usize, time: f64) -> Self {
+        Self { solve_seconds, restarts, since: time }
+    }
+
+    // Colored waves sweep the board diagonally for a moment, then the result stays shown
+    pub fn draw(&self, grid: &Grid, layout: &Layout, time: f64, keys: &str) {
+        let theme = theme::current();
+        let elapsed = time - self.since;
+        if elapsed < FLASH_SECONDS {
+            let side = grid.size.side();
+            let fade = 1. - (elapsed / FLASH_SECONDS) as f32;
+            for idx in 0..grid.cells.len() {
+                let diagonal = (idx / side + idx % side) as f64 / (2 * side) as f64;
+                let phase = (elapsed / SWEEP_SECONDS - diagonal) * TAU;
+                let mut color = macroquad::color::hsl_to_rgb((diagonal + elapsed / SWEEP_SECONDS).fract() as f32, 0.9, 0.6);
+                color.a = theme.victory.sparkle_alpha * fade * (0.5 + 0.5 * phase.sin() as f32);
+                let p = layout.cell_position(idx);
+                draw_rectangle(p.x, p.y, layout.cell_size, layout.cell_size, color);
+            }
+        }
+
+        let lines = [
+            match self.restarts {
+                1 => tr(Text::SolvedOneRestart, &[&format!("{:.1}", self.solve_seconds)]),
+                restarts => tr(Text::Solved, &[&format!("{:.1}", self.solve_seconds), &restarts]),
+            },
+            keys.to_string(),
+        ];
+        let width = lines
+            .iter()
+            .map(|line| text::measure(line, theme.victory.font_size).width)
+            .fold(0., f32::max) + 2. * theme.victory.padding;
+        let height = lines.len() as f32 * theme.victory.font_size + 2. * theme.victory.padding;
+        let x = (screen_width() - width) / 2.;
+        let y = (screen_height() - height) / 2.;
+        draw_rectangle(x, y, width, height, theme.victory.panel_color);
+        for (i, line) in lines.iter().enumerate() {
+            let baseline = y + theme.victory.padding + (i + 1) as f32 * theme.victory.font_size - theme.victory.font_size / 4.;
+            text::draw(line, x + theme.victory.padding, baseline, theme.victory.font_size, theme.victory.text_color);
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use macroquad::prelude::*;
+
+use sudoku::cell::Cell;
+use sudoku::layout::Layout;
+use sudoku::notation::format_cells;
+use sudoku::text;
+use sudoku::theme;
+
+use crate::language::{tr, Text};
+
+
+// How many times each cell's candidates changed over a solve, restarts included,
+// showing where the search struggled
+pub struct Volatility {
+    changes: Vec<u32>,
+}
+
+impl Volatility {
+    pub fn new(cells: usize) -> Self {
+        Self { changes: vec![0; cells] }
+    }
+
+    pub fn clear(&mut self) {
+        self.changes.fill(0);
+    }
+
+    // Counts the cells that differ between two states of the grid
+    pub fn record(&mut self, before: &[Cell], after: &[Cell]) {
+        for (changes, (before, after)) in self.changes.iter_mut().zip(before.iter().zip(after)) {
+            if before != after {
+                *changes += 1;
+            }
+        }
+    }
+
+    // Tints cells from blue for the calmest to red for the most volatile, with
+    // their counts and a summary above the board
+    pub fn draw(&self, layout: &Layout, side: usize) {
+        let theme = theme::current();
+        let style = &theme.volatility;
+        let most = self.changes.iter().copied().max().unwrap_or_default();
+        let size = layout.cell_size;
+        let font_size = size * style.count_font_scale;
+        for (idx, &changes) in self.changes.iter().enumerate() {
+            let wildness = changes as f32 / most.max(1) as f32;
+            let hue = style.calmest_hue + (style.wildest_hue - style.calmest_hue) * wildness;
+            let mut color = macroquad::color::hsl_to_rgb(hue, 0.8, 0.4);
+            color.a = style.alpha;
+            let p = layout.cell_position(idx);
+            draw_rectangle(p.x, p.y, size, size, color);
+            text::draw(&changes.to_string(), p.x + font_size / 4., p.y + font_size, font_size, style.count_color);
+        }
+
+        let wildest: Vec<usize> = (0..self.changes.len()).filter(|&idx| self.changes[idx] == most).collect();
+        let summary = tr(Text::Volatility, &[&self.changes.iter().sum::<u32>(), &format_cells(&wildest, side), &most]);
+        text::draw(&summary, 0., 3. * theme.text.font_size, theme.text.font_size, style.summary_color);
+    }
+}
@@ -0,0 +1,92 @@
+use macroquad::prelude::*;
+
+use sudoku::contradiction::Contradiction;
+use sudoku::grid::Grid;
+use sudoku::text;
+use sudoku::theme;
+
+use crate::keys::{self, Action};
+use crate::language::{self, tr, Text};
+
+
+pub const DEFAULT_TIMEOUT_SECONDS: f64 = 60.;
+
+
+// Notices the solver stalling, restarting over and over without any attempt getting
+// further than the best one so far, as happens when the rules contradict each other
+pub struct Watchdog {
+    timeout: f64,
+    // Most cells resolved in one attempt, and when that record was set
+    best: usize,
+    progress_time: f64,
+    restarts: usize,
+    last_contradiction: Option<Contradiction>,
+}
+
+impl Watchdog {
+    // A zero timeout never reports anything
+    pub fn new(timeout: f64, time: f64) -> Self {
+        Self { timeout, best: 0, progress_time: time, restarts: 0, last_contradiction: None }
+    }
+
+    pub fn reset(&mut self, time: f64) {
+        *self = Self::new(self.timeout, time);
+    }
+
+    // Follow a step, a contradiction meaning the solver had to restart
+    pub fn record(&mut self, grid: &Grid, contradiction: Option<&Contradiction>, time: f64) {
+        if let Some(contradiction) = contradiction {
+            self.restarts += 1;
+            self.last_contradiction = Some(contradiction.clone());
+            return;
+        }
+        let resolved = grid.cells.iter().filter(|cell| cell.possible_values.len() == 1).count();
+        if resolved > self.best {
+            self.best = resolved;
+            self.progress_time = time;
+        }
+    }
+
+    pub fn is_stalled(&self, grid: &Grid, time: f64) -> bool {
+        self.timeout > 0. && !grid.is_resolve() && time - self.progress_time > self.timeout
+    }
+
+    // Overlay explaining the stall, dismissing it waits for another full period
+    pub fn update(&mut self, grid: &Grid, time: f64) {
+        let theme = theme::current();
+        if !self.is_stalled(grid, time) {
+            return;
+        }
+        if keys::pressed(Action::Back) {
+            self.progress_time = time;
+            return;
+        }
+
+        let mut lines = vec![
+            (tr(Text::NoProgress, &[&format!("{:.0}", time - self.progress_time)]), theme.watchdog.title_color),
+            (tr(Text::BestAttempt, &[&self.restarts, &self.best, &grid.cells.len()]), theme.text.color),
+        ];
+        if let Some(contradiction) = &self.last_contradiction {
+            let description = language::contradiction(contradiction, grid.size.side());
+            lines.push((tr(Text::LastContradiction, &[&description]), theme.text.color));
+        }
+        lines.extend([
+            (tr(Text::StallReason, &[]), theme.text.color),
+            (tr(Text::StallAdvice, &[]), theme.text.color),
+            (tr(Text::Dismiss, &[&keys::label(Action::Back)]), theme.text.color),
+        ]);
+        let width = lines
+            .iter()
+            .map(|(line, _)| text::measure(line, theme.text.font_size).width)
+            .fold(0., f32::max) + 2. * theme.watchdog.padding;
+        let height = lines.len() as f32 * theme.text.font_size + 2. * theme.watchdog.padding;
+        let x = (screen_width() - width) / 2.;
+        let y = (screen_height() - height) / 2.;
+
+        draw_rectangle(x, y, width, height, theme.watchdog.overlay_color);
+        for (i, (line, color)) in lines.iter().enumerate() {
+            let baseline = y + theme.watchdog.padding + (i + 1) as f32 * theme.text.font_size - theme.text.font_size / 4.;
+            text::draw(line, x + theme.watchdog.padding, baseline, theme.text.font_size, *color);
+        }
+    }
+}
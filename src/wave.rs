@@ -0,0 +1,69 @@
+use macroquad::prelude::*;
+
+use sudoku::cell::Cell;
+use sudoku::grid::Grid;
+use sudoku::layout::Layout;
+use sudoku::notation::Coord;
+use sudoku::theme;
+
+
+// Time for the wave to move one cell further from the collapse
+const RING_SECONDS: f64 = 0.03;
+
+
+// Eliminations of a step spreading out from the collapsed cell: each cell keeps
+// showing its previous candidates until the wave, growing one ring of cells at a
+// time, reaches it
+pub struct Wave {
+    origin: usize,
+    before: Vec<Cell>,
+    start: f64,
+}
+
+impl Wave {
+    pub fn new(origin: usize, before: Vec<Cell>, start: f64) -> Self {
+        Self { origin, before, start }
+    }
+
+    fn radius(&self, time: f64) -> usize {
+        ((time - self.start) / RING_SECONDS) as usize
+    }
+
+    fn distance(&self, idx: usize, side: usize) -> usize {
+        let (a, b) = (Coord::from_index(self.origin, side), Coord::from_index(idx, side));
+        a.row.abs_diff(b.row).max(a.col.abs_diff(b.col))
+    }
+
+    // Candidates as they appear at `time`
+    pub fn cells(&self, grid: &Grid, time: f64) -> Vec<Cell> {
+        let (side, radius) = (grid.size.side(), self.radius(time));
+        grid.cells
+            .iter()
+            .zip(&self.before)
+            .enumerate()
+            .map(|(idx, (after, before))| {
+                let reached = self.distance(idx, side) <= radius;
+                if reached { after.clone() } else { before.clone() }
+            })
+            .collect()
+    }
+
+    pub fn draw(&self, grid: &Grid, layout: &Layout, time: f64) {
+        let theme = theme::current();
+        grid.draw_cells(layout, &self.cells(grid, time));
+
+        let (side, radius) = (grid.size.side(), self.radius(time));
+        let size = layout.cell_size;
+        for idx in 0..grid.cells.len() {
+            let color = if idx == self.origin {
+                theme.wave.collapse_color
+            } else if self.distance(idx, side) == radius && grid.cells[idx] != self.before[idx] {
+                theme.wave.front_color
+            } else {
+                continue;
+            };
+            let p = layout.cell_position(idx);
+            draw_rectangle(p.x, p.y, size, size, color);
+        }
+    }
+}
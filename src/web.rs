@@ -0,0 +1,119 @@
+// Running in a web page built for wasm32, see web/index.html. The page URL stands in
+// for the command line: `?preset=killer&seed=42&diagonal` runs like
+// `--preset killer --seed 42 --diagonal`, and arguments naming files hold their
+// contents instead, there being no files to read.
+
+
+// Options the page may set, the others needing files or a terminal
+#[cfg(target_arch = "wasm32")]
+const PARAMETERS: [&str; 11] = [
+    "seed", "preset", "size", "variant", "regions", "puzzle", "speed", "watchdog", "theme", "language", "input",
+];
+#[cfg(target_arch = "wasm32")]
+const FLAGS: [&str; 7] = ["diagonal", "windoku", "anti-knight", "anti-king", "non-consecutive", "disjoint-groups", "edit"];
+
+// Plugin version checked by the page's JS bundle, 0.1.0
+#[cfg(target_arch = "wasm32")]
+const PLUGIN_VERSION: u32 = 1 << 16;
+
+
+#[cfg(target_arch = "wasm32")]
+extern "C" {
+    // Query string of the page URL, written into a buffer of its length
+    fn sudoku_url_query_length() -> u32;
+    fn sudoku_url_query(buffer: *mut u8, length: u32);
+}
+
+#[cfg(target_arch = "wasm32")]
+#[no_mangle]
+pub extern "C" fn sudoku_url_crate_version() -> u32 {
+    PLUGIN_VERSION
+}
+
+#[cfg(target_arch = "wasm32")]
+getrandom::register_custom_getrandom!(random_bytes);
+
+// Splitmix64 over the clock, new seeds only need to differ from one grid to the next
+#[cfg(target_arch = "wasm32")]
+fn random_bytes(buffer: &mut [u8]) -> Result<(), getrandom::Error> {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let mut state = macroquad::miniquad::date::now().to_bits() ^ COUNTER.fetch_add(1, Ordering::Relaxed);
+    for chunk in buffer.chunks_mut(8) {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        chunk.copy_from_slice(&z.to_le_bytes()[..chunk.len()]);
+    }
+    Ok(())
+}
+
+// Decodes `+` and `%XX` escapes, leaving malformed ones as they are
+#[cfg(target_arch = "wasm32")]
+fn decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|hex| std::str::from_utf8(hex).ok());
+        match (bytes[i], hex.and_then(|hex| u8::from_str_radix(hex, 16).ok())) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+            (b'+', _) => decoded.push(b' '),
+            (byte, _) => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn args() -> Vec<String> {
+    let mut query = vec![0; unsafe { sudoku_url_query_length() } as usize];
+    unsafe { sudoku_url_query(query.as_mut_ptr(), query.len() as u32) };
+    let query = String::from_utf8_lossy(&query).into_owned();
+
+    let mut args = vec!["sudoku".to_string()];
+    for pair in query.trim_start_matches('?').split('&') {
+        let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let name = decode(name);
+        if PARAMETERS.contains(&name.as_str()) {
+            args.push(format!("--{}", name));
+            args.push(decode(value));
+        } else if FLAGS.contains(&name.as_str()) {
+            args.push(format!("--{}", name));
+        }
+    }
+    args
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn args() -> Vec<String> {
+    std::env::args().collect()
+}
+
+// Text of a file argument given inline, on the web
+pub fn inline(value: &str) -> Option<&str> {
+    cfg!(target_arch = "wasm32").then_some(value)
+}
+
+// Stops on bad arguments. The web has no stderr and can't exit, the message goes to
+// the browser console before the page halts
+pub fn fail(message: &str) -> ! {
+    #[cfg(target_arch = "wasm32")]
+    {
+        macroquad::logging::error!("{}", message);
+        panic!("{}", message);
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        eprintln!("{}", message);
+        std::process::exit(1);
+    }
+}